@@ -0,0 +1,296 @@
+// Benchmarks for the verkle tree, comparing the KZG backend (`VerkleTree`) with the
+// pointproofs backend (`VerkleTree_point`) over several widths, tree sizes and batch sizes.
+//
+// Timings are written by criterion to `target/criterion/<group>/<backend>/<parameters>/new/estimates.json`.
+// Proof sizes are not timings, so they are written separately to
+// `target/verkle-bench/proof_sizes.csv` and `target/verkle-bench/proof_sizes.json`.
+//
+// The parameters can be overridden with comma separated lists in environment variables:
+//      VERKLE_BENCH_WIDTHS  (default 2,4,8,16,32,64,128,256)
+//      VERKLE_BENCH_LEAVES  (default 256,4096)
+//      VERKLE_BENCH_BATCH   (default 16,256)
+// All data and index selections are drawn from a fixed seed, so two runs measure the same trees.
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use ark_bls12_381::Fr as F;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::{Rng, SeedableRng};
+use verkle_tree::{ProofSize, VerkleTree, VerkleTree_point};
+
+const SEED: u64 = 0x5eed;
+
+struct Setup {
+    width: usize,
+    leaves: usize,
+    datas: Vec<F>,
+    datas_point: Vec<Vec<u8>>,
+}
+
+fn env_list(name: &str, default: &[usize]) -> Vec<usize> {
+    match std::env::var(name) {
+        Ok(list) => list
+            .split(',')
+            .map(|s| s.trim().parse().expect("expected a comma separated list of numbers"))
+            .collect(),
+        Err(_) => default.to_vec(),
+    }
+}
+
+fn widths() -> Vec<usize> {
+    env_list("VERKLE_BENCH_WIDTHS", &[2, 4, 8, 16, 32, 64, 128, 256])
+}
+
+fn leaf_counts() -> Vec<usize> {
+    env_list("VERKLE_BENCH_LEAVES", &[256, 4096])
+}
+
+fn batch_sizes() -> Vec<usize> {
+    env_list("VERKLE_BENCH_BATCH", &[16, 256])
+}
+
+fn setups() -> Vec<Setup> {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut setups = Vec::new();
+    for width in widths() {
+        for leaves in leaf_counts() {
+            let mut datas = Vec::with_capacity(leaves);
+            let mut datas_point = Vec::with_capacity(leaves);
+            for _ in 0..leaves {
+                let v: u64 = rng.gen();
+                datas.push(F::from(v));
+                datas_point.push(v.to_le_bytes().to_vec());
+            }
+            setups.push(Setup { width, leaves, datas, datas_point });
+        }
+    }
+    setups
+}
+
+fn random_indices(rng: &mut StdRng, leaves: usize, amount: usize) -> Vec<usize> {
+    sample(rng, leaves, amount.min(leaves)).into_vec()
+}
+
+fn parameter(setup: &Setup) -> String {
+    format!("w{}/n{}", setup.width, setup.leaves)
+}
+
+fn bench_build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+    group.sample_size(10);
+    for setup in setups() {
+        group.bench_with_input(BenchmarkId::new("kzg", parameter(&setup)), &setup, |b, s| {
+            b.iter(|| VerkleTree::new(&s.datas, s.width).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("pointproofs", parameter(&setup)), &setup, |b, s| {
+            b.iter(|| VerkleTree_point::new(&s.datas_point, s.width).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_single_proof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_proof");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for setup in setups() {
+        let index = rng.gen_range(0..setup.leaves);
+        let tree = VerkleTree::new(&setup.datas, setup.width).unwrap();
+        group.bench_function(BenchmarkId::new("kzg", parameter(&setup)), |b| {
+            b.iter(|| tree.generate_proof(index, &setup.datas[index]).unwrap())
+        });
+        let tree = VerkleTree_point::new(&setup.datas_point, setup.width).unwrap();
+        group.bench_function(BenchmarkId::new("pointproofs", parameter(&setup)), |b| {
+            b.iter(|| tree.generate_proof(index, &setup.datas_point[index]).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_batch_proof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch_proof");
+    group.sample_size(10);
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for setup in setups() {
+        let tree = VerkleTree::new(&setup.datas, setup.width).unwrap();
+        let tree_point = VerkleTree_point::new(&setup.datas_point, setup.width).unwrap();
+        for batch in batch_sizes() {
+            let indices = random_indices(&mut rng, setup.leaves, batch);
+            let id = format!("{}/b{}", parameter(&setup), indices.len());
+            group.bench_function(BenchmarkId::new("kzg", &id), |b| {
+                b.iter(|| tree.prove_batch(&indices).unwrap().1)
+            });
+            group.bench_function(BenchmarkId::new("pointproofs", &id), |b| {
+                b.iter(|| tree_point.prove_batch(&indices).unwrap().1)
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify");
+    group.sample_size(10);
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for setup in setups() {
+        let width = setup.width;
+        let index = rng.gen_range(0..setup.leaves);
+
+        let tree = VerkleTree::new(&setup.datas, width).unwrap();
+        let root = tree.root_commitment().unwrap();
+        let depth = tree.depth();
+        let proof = tree.generate_proof(index, &setup.datas[index]).unwrap();
+        group.bench_function(BenchmarkId::new("kzg/single", parameter(&setup)), |b| {
+            b.iter(|| VerkleTree::verify_proof(root, &proof, width, depth, index, setup.datas[index], tree.kzg()))
+        });
+
+        let tree_point = VerkleTree_point::new(&setup.datas_point, width).unwrap();
+        let root_point = tree_point.root_commitment().unwrap();
+        let proof_point = tree_point.generate_proof(index, &setup.datas_point[index]).unwrap();
+        group.bench_function(BenchmarkId::new("pointproofs/single", parameter(&setup)), |b| {
+            b.iter(|| VerkleTree_point::verify_proof(root_point.clone(), &proof_point, width, depth, index, &setup.datas_point[index], tree_point.params()))
+        });
+
+        for batch in batch_sizes() {
+            let indices = random_indices(&mut rng, setup.leaves, batch);
+            let id = format!("{}/b{}", parameter(&setup), indices.len());
+
+            let values: Vec<F> = indices.iter().map(|i| setup.datas[*i]).collect();
+            let proof = tree.prove_batch(&indices).unwrap().1;
+            group.bench_function(BenchmarkId::new("kzg/batch", &id), |b| {
+                b.iter(|| VerkleTree::batch_proof_verify(root, &proof, width, &indices, depth, &values, tree.kzg()))
+            });
+
+            let values: Vec<Vec<u8>> = indices.iter().map(|i| setup.datas_point[*i].clone()).collect();
+            let proof = tree_point.prove_batch(&indices).unwrap().1;
+            group.bench_function(BenchmarkId::new("pointproofs/batch", &id), |b| {
+                b.iter(|| VerkleTree_point::batch_proof_verify(root_point.clone(), &proof, width, &indices, depth, &values, tree_point.params()))
+            });
+        }
+    }
+    group.finish();
+}
+
+// `update` sets one value of the tree, which updates one commitment per level on the path to it.
+// The KZG backend adds the change of one child to every commitment, the pointproofs backend
+// updates every commitment with Commitment::update.
+fn bench_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    for setup in setups() {
+        let index = rng.gen_range(0..setup.leaves);
+        let mut tree = VerkleTree::new(&setup.datas, setup.width).unwrap();
+        group.bench_function(BenchmarkId::new("kzg", parameter(&setup)), |b| {
+            b.iter(|| tree.update(index, F::from(rng.gen::<u64>())).unwrap())
+        });
+        let mut tree = VerkleTree_point::new(&setup.datas_point, setup.width).unwrap();
+        group.bench_function(BenchmarkId::new("pointproofs", parameter(&setup)), |b| {
+            b.iter(|| tree.update(index, rng.gen::<u64>().to_le_bytes().to_vec()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+struct ProofSizeRow {
+    backend: &'static str,
+    kind: &'static str,
+    width: usize,
+    leaves: usize,
+    batch: usize,
+    size: ProofSize,
+}
+
+fn sum_levels(levels: Vec<ProofSize>) -> ProofSize {
+    levels.into_iter().fold(ProofSize::default(), |acc, level| acc + level)
+}
+
+// Proof sizes are deterministic, so they are computed once and written to disk
+// next to the criterion output instead of being benchmarked.
+fn proof_sizes(_c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut sizes = Vec::new();
+    for setup in setups() {
+        let index = rng.gen_range(0..setup.leaves);
+
+        let tree = VerkleTree::new(&setup.datas, setup.width).unwrap();
+        let proof = tree.generate_proof(index, &setup.datas[index]).unwrap();
+        let size = sum_levels(proof.size_breakdown());
+        sizes.push(ProofSizeRow { backend: "kzg", kind: "single", width: setup.width, leaves: setup.leaves, batch: 1, size });
+
+        let tree_point = VerkleTree_point::new(&setup.datas_point, setup.width).unwrap();
+        let proof = tree_point.generate_proof(index, &setup.datas_point[index]).unwrap();
+        let size = sum_levels(proof.size_breakdown());
+        sizes.push(ProofSizeRow { backend: "pointproofs", kind: "single", width: setup.width, leaves: setup.leaves, batch: 1, size });
+
+        for batch in batch_sizes() {
+            let indices = random_indices(&mut rng, setup.leaves, batch);
+
+            let proof = tree.prove_batch(&indices).unwrap().1;
+            let size = sum_levels(proof.size_breakdown());
+            sizes.push(ProofSizeRow { backend: "kzg", kind: "batch", width: setup.width, leaves: setup.leaves, batch: indices.len(), size });
+
+            let proof = tree_point.prove_batch(&indices).unwrap().1;
+            let size = sum_levels(proof.size_breakdown());
+            sizes.push(ProofSizeRow { backend: "pointproofs", kind: "batch", width: setup.width, leaves: setup.leaves, batch: indices.len(), size });
+        }
+    }
+    write_proof_sizes(&sizes).expect("failed to write the proof sizes");
+}
+
+fn write_proof_sizes(sizes: &[ProofSizeRow]) -> std::io::Result<()> {
+    let target = std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_string());
+    let dir = PathBuf::from(target).join("verkle-bench");
+    fs::create_dir_all(&dir)?;
+
+    let mut csv = fs::File::create(dir.join("proof_sizes.csv"))?;
+    writeln!(csv, "backend,kind,width,leaves,batch,commitments,opening_proofs,values,index_metadata,bytes")?;
+    for s in sizes {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{}",
+            s.backend,
+            s.kind,
+            s.width,
+            s.leaves,
+            s.batch,
+            s.size.commitments,
+            s.size.opening_proofs,
+            s.size.values,
+            s.size.index_metadata,
+            s.size.total()
+        )?;
+    }
+
+    let rows: Vec<String> = sizes
+        .iter()
+        .map(|s| {
+            format!(
+                "  {{\"backend\": \"{}\", \"kind\": \"{}\", \"width\": {}, \"leaves\": {}, \"batch\": {}, \"commitments\": {}, \"opening_proofs\": {}, \"values\": {}, \"index_metadata\": {}, \"bytes\": {}}}",
+                s.backend,
+                s.kind,
+                s.width,
+                s.leaves,
+                s.batch,
+                s.size.commitments,
+                s.size.opening_proofs,
+                s.size.values,
+                s.size.index_metadata,
+                s.size.total()
+            )
+        })
+        .collect();
+    let mut json = fs::File::create(dir.join("proof_sizes.json"))?;
+    writeln!(json, "[\n{}\n]", rows.join(",\n"))?;
+    Ok(())
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().measurement_time(Duration::from_secs(5));
+    targets = proof_sizes, bench_build, bench_single_proof, bench_batch_proof, bench_verify, bench_update
+);
+criterion_main!(benches);
@@ -0,0 +1,203 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <string.h>
+#include <assert.h>
+#include "verkle_tree_c.h"
+
+// #define DEBUG
+
+// very simple and basic tests for build/root/prove/verify
+int test_basic() {
+  size_t n = 100;
+  size_t width = 4;
+
+  // values of the tree
+  int counter = 0;
+  verkle_tree_value values[n];
+  for (counter = 0; counter < n; counter++) {
+    char * tmp = (char*) malloc(16 * sizeof(char));
+    sprintf(tmp, "value %d", counter);
+    values[counter].data = (const unsigned char*) tmp;
+    values[counter].len = strlen(tmp);
+  }
+
+  // the setup of the tests is public, a real deployment reads a setup written by Kzg::write with verkle_tree_read_setup
+  verkle_tree_setup setup;
+  assert(verkle_tree_insecure_test_setup(width, &setup) == 0);
+  verkle_tree_setup null_setup = {NULL};
+  uint8_t garbage[8] = {0};
+  verkle_tree_bytes garbage_bytes = {garbage, sizeof(garbage)};
+  verkle_tree_setup invalid_setup;
+  assert(verkle_tree_read_setup(garbage_bytes, width, &invalid_setup) == -1);
+
+  verkle_tree tree;
+  assert(verkle_tree_new(values, n, width, setup, &tree) == 0);
+  assert(verkle_tree_leaf_count(tree) == n);
+  // a width of 1 is rejected
+  verkle_tree invalid_tree;
+  assert(verkle_tree_new(values, n, 1, setup, &invalid_tree) == -1);
+  // null pointers are rejected
+  assert(verkle_tree_new(NULL, n, width, setup, &invalid_tree) == -1);
+  assert(verkle_tree_new(values, n, width, setup, NULL) == -1);
+  assert(verkle_tree_new(values, n, width, null_setup, &invalid_tree) == -1);
+
+  verkle_tree_bytes root;
+  assert(verkle_tree_root(tree, &root) == 0);
+  assert(root.len == 48);
+
+  verkle_tree_bytes proof;
+  for (counter = 0; counter < n; counter += 7) {
+    // generate a proof
+    assert(verkle_tree_prove(tree, counter, &proof) == 0);
+
+#ifdef DEBUG
+    printf("proof %d: %zu bytes\n", counter, proof.len);
+#endif
+
+    // verify the proof
+    assert(verkle_tree_verify(root, proof, width, n, counter, values[counter], setup) == true);
+    // the proof does not hold for another value or index
+    assert(verkle_tree_verify(root, proof, width, n, counter, values[(counter + 1) % n], setup) == false);
+    assert(verkle_tree_verify(root, proof, width, n, (counter + 1) % n, values[counter], setup) == false);
+    assert(verkle_tree_verify(root, proof, width, n, counter, values[counter], null_setup) == false);
+    verkle_tree_free_bytes(proof);
+  }
+  assert(verkle_tree_prove(tree, n, &proof) == -1);
+
+  verkle_tree_free_bytes(root);
+  verkle_tree_free(tree);
+  verkle_tree_free_setup(setup);
+
+  printf("basic tests: success\n");
+  return 0;
+}
+
+// batch proofs, verified against a root that is passed around as bytes
+int test_batch() {
+  size_t n = 200;
+  size_t width = 5;
+
+  // values of the tree
+  int counter = 0;
+  verkle_tree_value values[n];
+  for (counter = 0; counter < n; counter++) {
+    char * tmp = (char*) malloc(16 * sizeof(char));
+    sprintf(tmp, "batch value %d", counter);
+    values[counter].data = (const unsigned char*) tmp;
+    values[counter].len = strlen(tmp);
+  }
+
+  verkle_tree_setup setup;
+  assert(verkle_tree_insecure_test_setup(width, &setup) == 0);
+  verkle_tree tree;
+  assert(verkle_tree_new(values, n, width, setup, &tree) == 0);
+  verkle_tree_bytes root;
+  assert(verkle_tree_root(tree, &root) == 0);
+
+  size_t index[5] = {3, 199, 42, 43, 120};
+  verkle_tree_value sub_values[5];
+  for (counter = 0; counter < 5; counter++) {
+    sub_values[counter] = values[index[counter]];
+  }
+
+  verkle_tree_bytes proof;
+  assert(verkle_tree_prove_batch(tree, index, 5, &proof) == 0);
+  assert(verkle_tree_verify_batch(root, proof, width, n, index, sub_values, 5, setup) == true);
+
+  // a wrong value is rejected
+  sub_values[2] = values[0];
+  assert(verkle_tree_verify_batch(root, proof, width, n, index, sub_values, 5, setup) == false);
+
+  // a proof for another tree is rejected
+  verkle_tree other_tree;
+  verkle_tree_bytes other_root;
+  assert(verkle_tree_new(values + 1, n - 1, width, setup, &other_tree) == 0);
+  assert(verkle_tree_root(other_tree, &other_root) == 0);
+  sub_values[2] = values[index[2]];
+  assert(verkle_tree_verify_batch(other_root, proof, width, n, index, sub_values, 5, setup) == false);
+  assert(verkle_tree_verify_batch(root, proof, width, n, NULL, sub_values, 5, setup) == false);
+  assert(verkle_tree_verify_batch(root, proof, width, n, index, NULL, 5, setup) == false);
+  assert(verkle_tree_prove_batch(tree, NULL, 5, &proof) == -1);
+
+  verkle_tree_free_bytes(proof);
+  verkle_tree_free_bytes(root);
+  verkle_tree_free_bytes(other_root);
+  verkle_tree_free(tree);
+  verkle_tree_free(other_tree);
+  verkle_tree_free_setup(setup);
+
+  printf("batch tests: success\n");
+  return 0;
+}
+
+// a tree serialized and read back has the same root and proves the same values
+int test_serialize() {
+  size_t n = 50;
+  size_t width = 3;
+
+  // values of the tree
+  int counter = 0;
+  verkle_tree_value values[n];
+  for (counter = 0; counter < n; counter++) {
+    char * tmp = (char*) malloc(24 * sizeof(char));
+    sprintf(tmp, "serialized value %d", counter);
+    values[counter].data = (const unsigned char*) tmp;
+    values[counter].len = strlen(tmp);
+  }
+
+  verkle_tree_setup setup;
+  assert(verkle_tree_insecure_test_setup(width, &setup) == 0);
+  verkle_tree_setup null_setup = {NULL};
+  verkle_tree tree;
+  assert(verkle_tree_new(values, n, width, setup, &tree) == 0);
+  verkle_tree_bytes root;
+  assert(verkle_tree_root(tree, &root) == 0);
+
+  verkle_tree_bytes bytes;
+  assert(verkle_tree_serialize(tree, &bytes) == 0);
+  verkle_tree read_tree;
+  assert(verkle_tree_deserialize(bytes, setup, &read_tree) == 0);
+  assert(verkle_tree_leaf_count(read_tree) == n);
+
+  verkle_tree_bytes read_root;
+  assert(verkle_tree_root(read_tree, &read_root) == 0);
+  assert(read_root.len == root.len);
+  assert(memcmp(read_root.data, root.data, root.len) == 0);
+
+  verkle_tree_bytes proof;
+  assert(verkle_tree_prove(read_tree, 17, &proof) == 0);
+  assert(verkle_tree_verify(root, proof, width, n, 17, values[17], setup) == true);
+  verkle_tree_free_bytes(proof);
+
+  // null pointers, a null setup and truncated bytes are rejected
+  verkle_tree invalid_tree;
+  verkle_tree null_tree = {NULL};
+  assert(verkle_tree_serialize(null_tree, &bytes) == -1);
+  assert(verkle_tree_serialize(tree, NULL) == -1);
+  assert(verkle_tree_deserialize(bytes, setup, NULL) == -1);
+  assert(verkle_tree_deserialize(bytes, null_setup, &invalid_tree) == -1);
+  verkle_tree_bytes truncated = {bytes.data, bytes.len - 1};
+  assert(verkle_tree_deserialize(truncated, setup, &invalid_tree) == -1);
+  verkle_tree_bytes null_bytes = {NULL, bytes.len};
+  assert(verkle_tree_deserialize(null_bytes, setup, &invalid_tree) == -1);
+
+  verkle_tree_free_bytes(bytes);
+  verkle_tree_free_bytes(root);
+  verkle_tree_free_bytes(read_root);
+  verkle_tree_free(tree);
+  verkle_tree_free(read_tree);
+  verkle_tree_free_setup(setup);
+
+  printf("serialize tests: success\n");
+  return 0;
+}
+
+int main(){
+
+  test_basic();
+  test_batch();
+  test_serialize();
+
+  printf("Hello Verkle\n");
+}
@@ -0,0 +1,128 @@
+/* Verkle trees with KZG commitments */
+
+/* Warning, this file is autogenerated by cbindgen. Don't modify this manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * a tree, built with verkle_tree_new and freed with verkle_tree_free
+ */
+typedef struct verkle_tree {
+  void *data;
+} verkle_tree;
+
+/**
+ * serialized tree, root or proof, freed with verkle_tree_free_bytes
+ */
+typedef struct verkle_tree_bytes {
+  uint8_t *data;
+  size_t len;
+} verkle_tree_bytes;
+
+/**
+ * a KZG setup, shared by the trees and the verifiers of a width and freed with verkle_tree_free_setup
+ */
+typedef struct verkle_tree_setup {
+  void *data;
+} verkle_tree_setup;
+
+/**
+ * a value, the bytes are read as a little endian integer modulo the field order
+ */
+typedef struct verkle_tree_value {
+  const uint8_t *data;
+  size_t len;
+} verkle_tree_value;
+
+/**
+ * Read a tree serialized by verkle_tree_serialize, its commitments are recomputed with setup
+ */
+int32_t verkle_tree_deserialize(verkle_tree_bytes bytes, verkle_tree_setup setup, verkle_tree *tree);
+
+/**
+ * Free a tree
+ */
+void verkle_tree_free(verkle_tree tree);
+
+/**
+ * Free serialized bytes
+ */
+void verkle_tree_free_bytes(verkle_tree_bytes bytes);
+
+/**
+ * Free a setup, the trees built with it keep their own copy
+ */
+void verkle_tree_free_setup(verkle_tree_setup setup);
+
+/**
+ * The setup of Kzg::insecure_test_setup, its proofs can be forged by anyone: only for tests
+ */
+int32_t verkle_tree_insecure_test_setup(size_t width, verkle_tree_setup *setup);
+
+/**
+ * The number of values in the tree, 0 for a null tree
+ */
+size_t verkle_tree_leaf_count(verkle_tree tree);
+
+/**
+ * Build a tree from n values with a setup for its width
+ */
+int32_t verkle_tree_new(const verkle_tree_value *values,
+                        size_t n,
+                        size_t width,
+                        verkle_tree_setup setup,
+                        verkle_tree *tree);
+
+/**
+ * Generate a serialized proof for the value at index
+ */
+int32_t verkle_tree_prove(verkle_tree tree, size_t index, verkle_tree_bytes *proof);
+
+/**
+ * Generate a serialized proof for the values at n indices
+ */
+int32_t verkle_tree_prove_batch(verkle_tree tree,
+                                const size_t *indices,
+                                size_t n,
+                                verkle_tree_bytes *proof);
+
+/**
+ * Read a setup for trees up to width, serialized as by Kzg::write
+ */
+int32_t verkle_tree_read_setup(verkle_tree_bytes bytes, size_t width, verkle_tree_setup *setup);
+
+/**
+ * Serialize the root commitment, a compressed G1 point
+ */
+int32_t verkle_tree_root(verkle_tree tree, verkle_tree_bytes *root);
+
+/**
+ * Serialize a tree as its width and values
+ */
+int32_t verkle_tree_serialize(verkle_tree tree, verkle_tree_bytes *bytes);
+
+/**
+ * Verify a serialized proof of the value at index, for a tree of leaf_count values built with setup
+ */
+bool verkle_tree_verify(verkle_tree_bytes root,
+                        verkle_tree_bytes proof,
+                        size_t width,
+                        size_t leaf_count,
+                        size_t index,
+                        verkle_tree_value value,
+                        verkle_tree_setup setup);
+
+/**
+ * Verify a serialized batch proof of the values at n indices, for a tree of leaf_count values built with setup
+ */
+bool verkle_tree_verify_batch(verkle_tree_bytes root,
+                              verkle_tree_bytes proof,
+                              size_t width,
+                              size_t leaf_count,
+                              const size_t *indices,
+                              const verkle_tree_value *values,
+                              size_t n,
+                              verkle_tree_setup setup);
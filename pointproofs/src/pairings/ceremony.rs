@@ -0,0 +1,405 @@
+//! this file is part of the pointproofs.
+//! It defines a multi-party ceremony to generate the public parameters.
+//!
+//! The parameters start with alpha = 1. Every participant raises the powers
+//! to a secret beta, so alpha becomes the product of all betas, and publishes
+//! a proof that the new powers are consistent and that it knows beta.
+//! The parameters are secure if at least one participant deletes its beta.
+//!
+//! The ceremony works on the BLS groups, independent of `group_switched`,
+//! and exports the format of `pointproofs-paramgen` that `read_param` reads.
+use ff::{Field, PrimeField, PrimeFieldRepr};
+use pairing::serdes::SerDes;
+use pairing::{bls12_381::*, CurveAffine, CurveProjective, Engine};
+use pairings::err::*;
+use pairings::hash_to_field_pointproofs::hash_to_field_repr_pointproofs;
+use pairings::param::*;
+use sha2::{Digest, Sha512};
+
+/// The powers of alpha during a ceremony.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CeremonyParams {
+    n: usize,
+    /// g1^{alpha^i} for i in 1..=2n except n + 1
+    g1_powers: Vec<G1Affine>,
+    /// g2^{alpha^i} for i in 1..=2n except n + 1
+    g2_powers: Vec<G2Affine>,
+}
+
+/// What a participant publishes with its parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContributionProof {
+    /// the digest of the transcript the contribution builds on
+    pub previous_digest: Vec<u8>,
+    /// g1^{beta}
+    pub g1_beta: G1Affine,
+    /// Schnorr proof of knowledge of beta: commitment g1^r and response r + c * beta
+    pub pok_commitment: G1Affine,
+    pub pok_response: Fr,
+}
+
+/// The state of a ceremony: the current parameters and the digest of the transcript.
+/// The digest chains every accepted contribution to the ones before it.
+#[derive(Clone, Debug)]
+pub struct Ceremony {
+    params: CeremonyParams,
+    digest: Vec<u8>,
+    contributions: usize,
+}
+
+impl CeremonyParams {
+    /// The parameters for alpha = 1, every power is the generator.
+    fn initial(n: usize) -> Self {
+        CeremonyParams {
+            n,
+            g1_powers: vec![G1Affine::one(); 2 * n - 1],
+            g2_powers: vec![G2Affine::one(); 2 * n - 1],
+        }
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    // the powers are in the right shape and consecutive powers of the same alpha != 0
+    fn is_consistent(&self, digest: &[u8]) -> bool {
+        let n = self.n;
+        if n == 0 || self.g1_powers.len() != 2 * n - 1 || self.g2_powers.len() != 2 * n - 1 {
+            return false;
+        }
+        if self.g1_powers[0].is_zero() || self.g2_powers[0].is_zero() {
+            return false;
+        }
+        let g1 = G1Affine::one();
+        let g2 = G2Affine::one();
+        // g1^{alpha} and g2^{alpha} have the same alpha
+        if Bls12::pairing(self.g1_powers[0], g2) != Bls12::pairing(g1, self.g2_powers[0]) {
+            return false;
+        }
+
+        // every power is alpha times the one before, except at the gap where it is alpha^2 times;
+        // the pairs are checked at once with a random linear combination, the randomness is
+        // derived from the digest and the parameters
+        let rho = self.linear_combination_scalars(digest);
+        let mut g1_lhs = G1::zero();
+        let mut g1_rhs = G1::zero();
+        let mut g2_lhs = G2::zero();
+        let mut g2_rhs = G2::zero();
+        for i in 0..2 * n - 2 {
+            if i == n - 1 {
+                continue;
+            }
+            g1_lhs.add_assign(&self.g1_powers[i + 1].mul(rho[i]));
+            g1_rhs.add_assign(&self.g1_powers[i].mul(rho[i]));
+            g2_lhs.add_assign(&self.g2_powers[i + 1].mul(rho[i]));
+            g2_rhs.add_assign(&self.g2_powers[i].mul(rho[i]));
+        }
+        // e(sum rho_i g1^{alpha^{i+1}}, g2) = e(sum rho_i g1^{alpha^i}, g2^{alpha})
+        if Bls12::pairing(g1_lhs.into_affine(), g2) != Bls12::pairing(g1_rhs.into_affine(), self.g2_powers[0]) {
+            return false;
+        }
+        // e(g1, sum rho_i g2^{alpha^{i+1}}) = e(g1^{alpha}, sum rho_i g2^{alpha^i})
+        if Bls12::pairing(g1, g2_lhs.into_affine()) != Bls12::pairing(self.g1_powers[0], g2_rhs.into_affine()) {
+            return false;
+        }
+        if n >= 2 {
+            // alpha^{n+2} = alpha^n * alpha^2
+            if Bls12::pairing(self.g1_powers[n], g2) != Bls12::pairing(self.g1_powers[n - 1], self.g2_powers[1]) {
+                return false;
+            }
+            if Bls12::pairing(g1, self.g2_powers[n]) != Bls12::pairing(self.g1_powers[1], self.g2_powers[n - 1]) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn linear_combination_scalars(&self, digest: &[u8]) -> Vec<Fr> {
+        let params_digest = self.digest();
+        (0..2 * self.n - 2)
+            .map(|i| {
+                hash_scalar(&[digest, &params_digest, b"rho", &(i as u64).to_be_bytes()].concat())
+            })
+            .collect()
+    }
+
+    // raises g^{alpha^i} to beta^i, the power at index i has i = index + 1 below n and index + 2 above
+    fn rerandomize(&self, beta: &Fr) -> Self {
+        let mut next = self.clone();
+        let mut beta_power = Fr::one();
+        for i in 0..2 * self.n - 1 {
+            beta_power.mul_assign(beta);
+            if i == self.n {
+                // skip beta^{n+1}
+                beta_power.mul_assign(beta);
+            }
+            next.g1_powers[i] = self.g1_powers[i].mul(beta_power).into_affine();
+            next.g2_powers[i] = self.g2_powers[i].mul(beta_power).into_affine();
+        }
+        next
+    }
+
+    // SHA 512 of the compressed powers
+    fn digest(&self) -> Vec<u8> {
+        let mut buf: Vec<u8> = vec![];
+        buf.extend_from_slice(&(self.n as u64).to_be_bytes());
+        for e in self.g1_powers.iter() {
+            // serialization into a vector does not fail
+            e.serialize(&mut buf, true).unwrap();
+        }
+        for e in self.g2_powers.iter() {
+            e.serialize(&mut buf, true).unwrap();
+        }
+        Sha512::digest(&buf).to_vec()
+    }
+
+    /// Converts the parameters into prover and verifier parameters with ciphersuite `csid`,
+    /// through the `pointproofs-paramgen` format.
+    pub fn to_params(
+        &self,
+        csid: Ciphersuite,
+    ) -> Result<(ProverParams, VerifierParams), String> {
+        let mut buf: Vec<u8> = vec![];
+        self.write_param(&mut buf)?;
+        read_param_with_ciphersuite(&mut &buf[..], csid)
+    }
+
+    /// Writes the parameters in the format of `pointproofs-paramgen`, it is read by `read_param`.
+    pub fn write_param<W: std::io::Write>(&self, writer: &mut W) -> Result<(), String> {
+        let n = self.n;
+        let param = pointproofs_paramgen::PointproofsParams {
+            ciphersuite: 0,
+            n,
+            g1_alpha_1_to_n: self.g1_powers[..n].to_vec(),
+            g1_alpha_nplus2_to_2n: self.g1_powers[n..].to_vec(),
+            g2_alpha_1_to_n: self.g2_powers[..n].to_vec(),
+            g2_alpha_nplus2_to_2n: self.g2_powers[n..].to_vec(),
+            // e(g1^{alpha}, g2^{alpha^n})
+            gt_alpha_nplus1: Bls12::pairing(self.g1_powers[0], self.g2_powers[n - 1]),
+        };
+        param
+            .serialize(writer, true)
+            .map_err(|e| format!("write_param: {}", e))
+    }
+}
+
+impl Ceremony {
+    /// Starts a ceremony for parameters of size n.
+    pub fn new(n: usize) -> Result<Self, String> {
+        if n == 0 || n > MAX_N {
+            return Err(ERR_MAX_N.to_owned());
+        }
+        let mut hasher = Sha512::new();
+        hasher.input(b"pointproofs ceremony");
+        hasher.input((n as u64).to_be_bytes());
+        Ok(Ceremony {
+            params: CeremonyParams::initial(n),
+            digest: hasher.result().to_vec(),
+            contributions: 0,
+        })
+    }
+
+    /// The current parameters, a participant contributes on top of them.
+    pub fn params(&self) -> &CeremonyParams {
+        &self.params
+    }
+
+    /// The digest of the transcript so far.
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// The number of accepted contributions.
+    pub fn contributions(&self) -> usize {
+        self.contributions
+    }
+
+    /// Run by a participant: raises the current parameters to beta = hash(entropy).
+    /// The entropy has to be at least 32 bytes and must be deleted afterwards.
+    /// Returns the new parameters with the proof to publish.
+    pub fn contribute<Blob: AsRef<[u8]>>(
+        &self,
+        entropy: Blob,
+    ) -> Result<(CeremonyParams, ContributionProof), String> {
+        if entropy.as_ref().len() < 32 {
+            return Err(ERR_SEED_TOO_SHORT.to_owned());
+        }
+        let beta = hash_scalar(&[entropy.as_ref(), &self.digest, b"beta"].concat());
+        let next = self.params.rerandomize(&beta);
+
+        // Schnorr proof of knowledge of beta, bound to the transcript and the new parameters
+        let g1_beta = G1Affine::one().mul(beta).into_affine();
+        let r = hash_scalar(&[entropy.as_ref(), &self.digest, b"nonce"].concat());
+        let pok_commitment = G1Affine::one().mul(r).into_affine();
+        let c = pok_challenge(&self.digest, &next, &g1_beta, &pok_commitment);
+        let mut pok_response = c;
+        pok_response.mul_assign(&beta);
+        pok_response.add_assign(&r);
+
+        let proof = ContributionProof {
+            previous_digest: self.digest.clone(),
+            g1_beta,
+            pok_commitment,
+            pok_response,
+        };
+        Ok((next, proof))
+    }
+
+    /// Checks a contribution on top of the current parameters:
+    /// it builds on this transcript, the participant knows beta != 0,
+    /// the new alpha is the old alpha times beta and the new powers are consistent.
+    pub fn verify_contribution(&self, next: &CeremonyParams, proof: &ContributionProof) -> bool {
+        if proof.previous_digest != self.digest || next.n != self.params.n {
+            return false;
+        }
+        if proof.g1_beta.is_zero() || !next.is_consistent(&self.digest) {
+            return false;
+        }
+        // g1^s = g1^r * (g1^beta)^c
+        let c = pok_challenge(&self.digest, next, &proof.g1_beta, &proof.pok_commitment);
+        let mut rhs = proof.g1_beta.mul(c);
+        rhs.add_assign_mixed(&proof.pok_commitment);
+        if G1Affine::one().mul(proof.pok_response).into_affine() != rhs.into_affine() {
+            return false;
+        }
+        // e(g1^{alpha * beta}, g2) = e(g1^{beta}, g2^{alpha})
+        Bls12::pairing(next.g1_powers[0], G2Affine::one())
+            == Bls12::pairing(proof.g1_beta, self.params.g2_powers[0])
+    }
+
+    /// Verifies the contribution and makes its parameters the current ones.
+    pub fn accept(&mut self, next: CeremonyParams, proof: &ContributionProof) -> Result<(), String> {
+        if !self.verify_contribution(&next, proof) {
+            return Err(ERR_CEREMONY.to_owned());
+        }
+        let mut hasher = Sha512::new();
+        hasher.input(&self.digest);
+        hasher.input(next.digest());
+        hasher.input(pok_bytes(&proof.g1_beta, &proof.pok_commitment));
+        self.digest = hasher.result().to_vec();
+        self.params = next;
+        self.contributions += 1;
+        Ok(())
+    }
+
+    /// Replays a published transcript of n-sized parameters and returns the final ceremony.
+    /// Every step is the parameters of a participant with its proof.
+    pub fn verify_transcript(
+        n: usize,
+        steps: &[(CeremonyParams, ContributionProof)],
+    ) -> Result<Self, String> {
+        let mut ceremony = Ceremony::new(n)?;
+        for (params, proof) in steps {
+            ceremony.accept(params.clone(), proof)?;
+        }
+        Ok(ceremony)
+    }
+
+    /// Writes the final parameters in the format of `pointproofs-paramgen`.
+    /// Fails if nobody contributed, since alpha would be 1.
+    pub fn write_param<W: std::io::Write>(&self, writer: &mut W) -> Result<(), String> {
+        if self.contributions == 0 {
+            return Err(ERR_CEREMONY.to_owned());
+        }
+        self.params.write_param(writer)
+    }
+}
+
+fn hash_scalar(input: &[u8]) -> Fr {
+    // hash_to_field_repr_pointproofs produces a valid, non-zero Fr element
+    Fr::from_repr(hash_to_field_repr_pointproofs(input)).unwrap()
+}
+
+fn pok_bytes(g1_beta: &G1Affine, pok_commitment: &G1Affine) -> Vec<u8> {
+    let mut buf: Vec<u8> = vec![];
+    // serialization into a vector does not fail
+    g1_beta.serialize(&mut buf, true).unwrap();
+    pok_commitment.serialize(&mut buf, true).unwrap();
+    buf
+}
+
+// c = hash(previous digest | new parameters | g1^beta | g1^r)
+fn pok_challenge(
+    digest: &[u8],
+    next: &CeremonyParams,
+    g1_beta: &G1Affine,
+    pok_commitment: &G1Affine,
+) -> Fr {
+    hash_scalar(&[digest, &next.digest(), &pok_bytes(g1_beta, pok_commitment)].concat())
+}
+
+impl SerDes for CeremonyParams {
+    /// `| n | g1_powers | g2_powers |`, the points are compressed.
+    fn serialize<W: std::io::Write>(&self, writer: &mut W, compressed: bool) -> std::io::Result<()> {
+        if !compressed {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, ERR_COMPRESS));
+        }
+        writer.write_all(&(self.n as u32).to_le_bytes())?;
+        for e in self.g1_powers.iter() {
+            e.serialize(writer, true)?;
+        }
+        for e in self.g2_powers.iter() {
+            e.serialize(writer, true)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize<R: std::io::Read>(reader: &mut R, compressed: bool) -> std::io::Result<Self> {
+        if !compressed {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, ERR_COMPRESS));
+        }
+        let mut buf = [0u8; 4];
+        reader.read_exact(&mut buf)?;
+        let n = u32::from_le_bytes(buf) as usize;
+        if n > MAX_N || n == 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, ERR_MAX_N));
+        }
+        let mut g1_powers = Vec::with_capacity(2 * n - 1);
+        for _ in 0..2 * n - 1 {
+            g1_powers.push(G1Affine::deserialize(reader, true)?);
+        }
+        let mut g2_powers = Vec::with_capacity(2 * n - 1);
+        for _ in 0..2 * n - 1 {
+            g2_powers.push(G2Affine::deserialize(reader, true)?);
+        }
+        Ok(CeremonyParams { n, g1_powers, g2_powers })
+    }
+}
+
+impl SerDes for ContributionProof {
+    /// `| digest length | previous_digest | g1_beta | pok_commitment | pok_response |`
+    fn serialize<W: std::io::Write>(&self, writer: &mut W, compressed: bool) -> std::io::Result<()> {
+        if !compressed {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, ERR_COMPRESS));
+        }
+        writer.write_all(&[self.previous_digest.len() as u8])?;
+        writer.write_all(&self.previous_digest)?;
+        self.g1_beta.serialize(writer, true)?;
+        self.pok_commitment.serialize(writer, true)?;
+        self.pok_response.into_repr().write_be(writer)
+    }
+
+    fn deserialize<R: std::io::Read>(reader: &mut R, compressed: bool) -> std::io::Result<Self> {
+        if !compressed {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, ERR_COMPRESS));
+        }
+        let mut len = [0u8; 1];
+        reader.read_exact(&mut len)?;
+        let mut previous_digest = vec![0u8; len[0] as usize];
+        reader.read_exact(&mut previous_digest)?;
+        let g1_beta = G1Affine::deserialize(reader, true)?;
+        let pok_commitment = G1Affine::deserialize(reader, true)?;
+        let mut repr = FrRepr::default();
+        repr.read_be(reader)?;
+        let pok_response = match Fr::from_repr(repr) {
+            Ok(p) => p,
+            Err(e) => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())),
+        };
+        Ok(ContributionProof {
+            previous_digest,
+            g1_beta,
+            pok_commitment,
+            pok_response,
+        })
+    }
+}
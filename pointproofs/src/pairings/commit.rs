@@ -10,17 +10,21 @@ use pairings::param::*;
 use pairings::*;
 
 impl Commitment {
+    /// the ciphersuite the commitment was generated with
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
     /// generate a new commitment.
     ///     * input: prover parameter set
     ///     * input: a list of n values
     ///     * output: a commitment
     ///     * error: invalid ciphersuite/parameters
-    /// note that if the #values does not match the parameter n,
+    /// note that if there are more values than the parameter n,
     /// an error will be returned.
-    /// if one were to generate a commitment for a vector of length
-    /// less than n, then the caller should pad the vector.
-    /// In this scenario, the caller should define the right
-    /// format for padding.
+    /// a vector of length less than n is treated as a prefix:
+    /// the missing values are zero (not the hash of an empty value),
+    /// so the parameters for n can be used for any shorter vector.
     pub fn new<Blob: AsRef<[u8]>>(
         prover_params: &ProverParams,
         values: &[Blob],
@@ -30,19 +34,19 @@ impl Commitment {
             return Err(ERR_CIPHERSUITE.to_owned());
         };
 
-        if prover_params.n != values.len() {
+        if values.is_empty() || values.len() > prover_params.n {
             return Err(ERR_INVALID_VALUE.to_owned());
         };
 
         // hash the values into scalars
         let scalars_fr_repr: Vec<FrRepr> = values
             .iter()
-            .map(|s| hash_to_field_repr_pointproofs(s.as_ref()))
-            .collect();
+            .map(|s| hash_to_field_repr(prover_params.ciphersuite, s.as_ref()))
+            .collect::<Result<Vec<FrRepr>, String>>()?;
         let scalars_u64: Vec<&[u64; 4]> = scalars_fr_repr.iter().map(|s| &s.0).collect();
 
         // commit = \prod pp[i]^scalar[i]
-        let commit = pp_sum_of_prod_helper(&prover_params, &scalars_u64, 0, prover_params.n);
+        let commit = pp_sum_of_prod_helper(&prover_params, &scalars_u64, 0, values.len());
 
         Ok(Self {
             ciphersuite: prover_params.ciphersuite,
@@ -77,9 +81,9 @@ impl Commitment {
         };
 
         // multiplier = hash(new_value) - hash(old_value)
-        let mut multiplier = hash_to_field_pointproofs(&value_before);
+        let mut multiplier = hash_to_field(prover_params.ciphersuite, &value_before)?;
         multiplier.negate();
-        multiplier.add_assign(&hash_to_field_pointproofs(&value_after));
+        multiplier.add_assign(&hash_to_field(prover_params.ciphersuite, &value_after)?);
 
         // new_commit = old_commit * g[index]^multiplier
         let res = pp_single_exp_helper(&prover_params, multiplier, changed_index);
@@ -135,9 +139,9 @@ impl Commitment {
         let mut multiplier_set: Vec<FrRepr> = Vec::with_capacity(value_before.len());
         for i in 0..value_before.len() {
             // multiplier = hash(new_value) - hash(old_value)
-            let mut multiplier = hash_to_field_pointproofs(&value_before[i]);
+            let mut multiplier = hash_to_field(prover_params.ciphersuite, &value_before[i])?;
             multiplier.negate();
-            multiplier.add_assign(&hash_to_field_pointproofs(&value_after[i]));
+            multiplier.add_assign(&hash_to_field(prover_params.ciphersuite, &value_after[i])?);
             multiplier_set.push(multiplier.into_repr());
         }
         let scalars_u64: Vec<&[u64; 4]> = multiplier_set.iter().map(|s| &s.0).collect();
@@ -158,9 +162,7 @@ impl Commitment {
                 for e in changed_index.iter() {
                     pre = [
                         pre,
-                        prover_params.precomp
-                            [changed_index[*e] * 256..(changed_index[*e] + 1) * 256]
-                            .to_vec(),
+                        prover_params.precomp[*e * 256..(*e + 1) * 256].to_vec(),
                     ]
                     .concat();
                 }
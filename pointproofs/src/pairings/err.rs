@@ -11,5 +11,6 @@ pub(crate) const ERR_INDEX_PROOF_NOT_MATCH: &str = "Length of index and proof se
 pub(crate) const ERR_X_COM_SIZE: &str =
     "Invalid sizes for commit, proof, or values for cross commit";
 pub(crate) const ERR_MAX_N: &str = "N is too large";
+pub(crate) const ERR_CEREMONY: &str = "The contribution to the ceremony is not valid";
 pub(crate) const ERR_PARAM: &str = "The input parameter is not correct";
 pub(crate) const ERR_INDEX_VALUE_NOT_MATCH: &str = "Length of index and value sets do not match";
@@ -2,6 +2,7 @@
 //! It defines the hash_to_field functions that are more efficient than `bls::hash_to_field`
 //! The algorithms are described here:
 //! https://github.com/algorand/pointproofs/blob/master/SPEC.md#hashes
+//! Every ciphersuite selects one of the constructions, see `param::hash_to_field_of`.
 use bigint::U512;
 use ff::PrimeField;
 use pairing::bls12_381::*;
@@ -9,7 +10,7 @@ use pairing::serdes::SerDes;
 use pairings::err::*;
 use pairings::param::*;
 use pairings::Commitment;
-use sha2::{Digest, Sha512};
+use sha2::{Digest, Sha256, Sha512};
 use std::ops::Rem;
 
 // A wrapper of `hash_to_tj_repr` that outputs `Fr`s instead of `FrRepr`s.
@@ -95,13 +96,17 @@ pub(crate) fn hash_to_tj_repr<Blob: AsRef<[u8]>>(
     let digest = hasher.result();
 
     // formulate the output
+    // all commitments have the same ciphersuite, this is checked by the callers
     Ok((0..commits.len())
         .map(|i| {
             // each field element t_i is generated as
             // t_i = hash_to_field (i | C | S | m[S])
-            hash_to_field_repr_pointproofs([&i.to_be_bytes()[..], digest.as_ref()].concat())
+            hash_to_field_repr(
+                commits[i].ciphersuite,
+                [&i.to_be_bytes()[..], digest.as_ref()].concat(),
+            )
         })
-        .collect::<Vec<FrRepr>>())
+        .collect::<Result<Vec<FrRepr>, String>>()?)
 }
 
 // A wrapper of `hash_to_ti` that outputs `Fr`s instead of `FrRepr`s.
@@ -182,17 +187,45 @@ pub(crate) fn hash_to_ti_repr<Blob: AsRef<[u8]>>(
     Ok(set
         .iter()
         .map(|index| {
-            hash_to_field_repr_pointproofs([&index.to_be_bytes()[..], digest.as_ref()].concat())
+            hash_to_field_repr(
+                commit.ciphersuite,
+                [&index.to_be_bytes()[..], digest.as_ref()].concat(),
+            )
         })
-        .collect())
+        .collect::<Result<Vec<FrRepr>, String>>()?)
+}
+
+/// The hash_to_field constructions that a ciphersuite can use.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HashToField {
+    /// SHA 512 of the blob, reduced modulo r (`hash_to_field_repr_pointproofs`)
+    Sha512,
+    /// hash_to_field of RFC 9380 with expand_message_xmd, SHA 256,
+    /// L = 48 bytes per element and the given domain separation tag
+    XmdSha256(&'static [u8]),
 }
 
-/// A wrapper of `hash_to_field` that outputs `Fr`s instead of `FrRepr`s.
-/// hash_to_field_pointproofs use SHA 512 to hash a blob into a non-zero field element
-pub(crate) fn hash_to_field_pointproofs<Blob: AsRef<[u8]>>(input: Blob) -> Fr {
-    // the hash_to_field_repr_pointproofs should already produce a valid Fr element
+/// A wrapper of `hash_to_field_repr` that outputs `Fr`s instead of `FrRepr`s.
+pub(crate) fn hash_to_field<Blob: AsRef<[u8]>>(
+    ciphersuite: Ciphersuite,
+    input: Blob,
+) -> Result<Fr, String> {
+    // the hash_to_field_repr should already produce a valid Fr element
     // so it is safe to unwrap here
-    Fr::from_repr(hash_to_field_repr_pointproofs(input.as_ref())).unwrap()
+    Ok(Fr::from_repr(hash_to_field_repr(ciphersuite, input)?).unwrap())
+}
+
+/// Hashes a blob into a non-zero field element with the construction of the ciphersuite.
+/// Returns an error if the ciphersuite is not registered.
+pub(crate) fn hash_to_field_repr<Blob: AsRef<[u8]>>(
+    ciphersuite: Ciphersuite,
+    input: Blob,
+) -> Result<FrRepr, String> {
+    match hash_to_field_of(ciphersuite) {
+        Some(HashToField::XmdSha256(dst)) => Ok(hash_to_field_repr_xmd_sha256(input, dst)),
+        Some(HashToField::Sha512) => Ok(hash_to_field_repr_pointproofs(input)),
+        None => Err(ERR_CIPHERSUITE.to_owned()),
+    }
 }
 
 /// Hashes a blob into a non-zero field element.
@@ -211,6 +244,59 @@ pub(crate) fn hash_to_field_repr_pointproofs<Blob: AsRef<[u8]>>(input: Blob) ->
     t
 }
 
+/// Hashes a blob into a non-zero field element with hash_to_field of RFC 9380
+/// https://www.rfc-editor.org/rfc/rfc9380.html#section-5.2
+/// for count = 1, expand_message_xmd with SHA 256 and L = 48 = ceil((255 + 128) / 8).
+pub(crate) fn hash_to_field_repr_xmd_sha256<Blob: AsRef<[u8]>>(input: Blob, dst: &[u8]) -> FrRepr {
+    // os2ip_mod_p reads 64 bytes, the 48 uniform bytes are the least significant ones
+    let mut oct_str = [0u8; 64];
+    oct_str[16..].copy_from_slice(&expand_message_xmd_sha256(input.as_ref(), dst, 48));
+    let mut t = os2ip_mod_p(&oct_str);
+
+    // if we get 0, return 1, as for ciphersuite 0
+    // this should not happen in practise
+    if t == FrRepr([0, 0, 0, 0]) {
+        t = FrRepr([1, 0, 0, 0]);
+    }
+    t
+}
+
+/// expand_message_xmd of RFC 9380 with SHA 256
+/// https://www.rfc-editor.org/rfc/rfc9380.html#section-5.3.1
+/// the dst is at most 255 bytes and len_in_bytes at most 255 * 32.
+pub fn expand_message_xmd_sha256(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    // b_in_bytes = 32 and r_in_bytes = 64 for SHA 256
+    let ell = (len_in_bytes + 31) / 32;
+    assert!(ell <= 255 && len_in_bytes <= 65535 && dst.len() <= 255);
+    // DST_prime = DST || I2OSP(len(DST), 1)
+    let dst_prime = [dst, &[dst.len() as u8]].concat();
+
+    // b_0 = H(Z_pad || msg || I2OSP(len_in_bytes, 2) || I2OSP(0, 1) || DST_prime)
+    let mut hasher = Sha256::new();
+    hasher.input([0u8; 64]);
+    hasher.input(msg);
+    hasher.input((len_in_bytes as u16).to_be_bytes());
+    hasher.input([0u8]);
+    hasher.input(&dst_prime);
+    let b_0 = hasher.result();
+
+    // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
+    // b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime)
+    let mut uniform_bytes: Vec<u8> = Vec::with_capacity(ell * 32);
+    let mut b_i = [0u8; 32];
+    for i in 1..=ell {
+        let mut hasher = Sha256::new();
+        let xored: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+        hasher.input(xored);
+        hasher.input([i as u8]);
+        hasher.input(&dst_prime);
+        b_i.copy_from_slice(hasher.result().as_ref());
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    uniform_bytes
+}
+
 /// this is Pointproofs's Octect String to Integer Primitive (os2ip) function
 /// https://tools.ietf.org/html/rfc8017#section-4
 /// the input is a 64 bytes array, and the output is between 0 and p-1
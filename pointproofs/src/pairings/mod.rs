@@ -40,6 +40,7 @@ pub struct Proof {
     pub(crate) proof: PointproofsG1,
 }
 
+pub mod ceremony;
 pub(crate) mod commit;
 pub mod param;
 pub(crate) mod prove;
@@ -5,16 +5,59 @@ use ff::Field;
 use pairing::{bls12_381::*, CurveAffine, CurveProjective,serdes::SerDes};
 //use pairings::err::*;
 //use pairings::hash_to_field_pointproofs::hash_to_field_pointproofs;
+pub use pairings::hash_to_field_pointproofs::{expand_message_xmd_sha256, HashToField};
 use pairings::*;
 
-const VALID_CIPHERSUITE: [u8; 1] = [0u8];
+/// The largest n for which parameters are generated or read.
+/// It bounds the memory that a parameter file can make us allocate.
+/// Parameters for n can be used for any vector of at most n values.
+pub const MAX_N: usize = 1 << 24;
 
 /// Ciphersuite ID is a wrapper of u8.
 pub type Ciphersuite = u8;
 
-/// Checks if csid is supported. Currently only support csid = 0.
+/// The original pointproofs ciphersuite, values are hashed with SHA 512 modulo r.
+pub const CIPHERSUITE_SHA512: Ciphersuite = 0;
+
+/// Values are hashed with hash_to_field of RFC 9380, expand_message_xmd with SHA 256.
+pub const CIPHERSUITE_XMD_SHA256: Ciphersuite = 1;
+
+/// The domain separation tag of `CIPHERSUITE_XMD_SHA256`.
+pub const DST_XMD_SHA256: &[u8] = b"POINTPROOFS-V01-CS01-with-BLS12381FR_XMD:SHA-256_";
+
+/// The registered ciphersuites with their hash_to_field construction.
+/// A new ciphersuite is added with a new entry, in order of preference.
+const CIPHERSUITES: [(Ciphersuite, HashToField); 2] = [
+    (CIPHERSUITE_XMD_SHA256, HashToField::XmdSha256(DST_XMD_SHA256)),
+    (CIPHERSUITE_SHA512, HashToField::Sha512),
+];
+
+/// Checks if csid is supported, i.e., it is registered.
 pub fn check_ciphersuite(csid: Ciphersuite) -> bool {
-    VALID_CIPHERSUITE.contains(&csid)
+    hash_to_field_of(csid).is_some()
+}
+
+/// The hash_to_field construction of a ciphersuite, None if it is not supported.
+pub fn hash_to_field_of(csid: Ciphersuite) -> Option<HashToField> {
+    CIPHERSUITES
+        .iter()
+        .find(|(id, _)| *id == csid)
+        .map(|(_, hash)| *hash)
+}
+
+/// The supported ciphersuites, the preferred one first.
+pub fn supported_ciphersuites() -> Vec<Ciphersuite> {
+    CIPHERSUITES.iter().map(|(id, _)| *id).collect()
+}
+
+/// Picks the ciphersuite to use with a peer that supports `peer_csids`:
+/// the first of our supported ciphersuites that the peer also supports.
+/// Returns None if there is no common ciphersuite.
+pub fn negotiate_ciphersuite(peer_csids: &[Ciphersuite]) -> Option<Ciphersuite> {
+    CIPHERSUITES
+        .iter()
+        .map(|(id, _)| *id)
+        .find(|id| peer_csids.contains(id))
 }
 
 /// Generate a set of parameters from a seed and a ciphersuite ID.
@@ -36,13 +79,13 @@ pub fn paramgen_from_seed<Blob: AsRef<[u8]>>(
     if !check_ciphersuite(ciphersuite) {
         return Err(err::ERR_CIPHERSUITE.to_owned());
     }
-    if n > 65536 {
+    if n > MAX_N {
         return Err(err::ERR_MAX_N.to_owned());
     }
 
     // invoke the internal parameter generation function
     Ok(paramgen_from_alpha(
-        &hash_to_field_pointproofs::hash_to_field_pointproofs(&seed),
+        &hash_to_field_pointproofs::hash_to_field(ciphersuite, &seed)?,
         ciphersuite,
         n,
     ))
@@ -104,6 +147,16 @@ fn paramgen_from_alpha(
 }
 
 impl VerifierParams {
+    /// the maximum number of values that can be verified with these parameters
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// the ciphersuite of the parameters
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
     /// pre-process the public parameters with precomputation value set to 3
     pub fn precomp_3(&mut self) {
         self.precomp = vec![PointproofsG2Affine::zero(); 3 * self.n];
@@ -121,9 +174,30 @@ impl VerifierParams {
         }
         self.pp_len = self.n * 256;
     }
+
+    /// the number of pre-computed values per generator: 0, 3 or 256
+    pub fn precomp_level(&self) -> usize {
+        self.pp_len / self.n
+    }
+
+    /// drops the pre-computed values
+    pub fn clear_precomp(&mut self) {
+        self.precomp = vec![];
+        self.pp_len = 0;
+    }
 }
 
 impl ProverParams {
+    /// the maximum number of values that can be committed with these parameters
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// the ciphersuite of the parameters
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
     /// pre-process the public parameters with precomputation value set to 3
     pub fn precomp_3(&mut self) {
         let twice_n = self.generators.len();
@@ -144,6 +218,17 @@ impl ProverParams {
         self.pp_len = self.n * 512;
     }
 
+    /// the number of pre-computed values per generator: 0, 3 or 256
+    pub fn precomp_level(&self) -> usize {
+        self.pp_len / (2 * self.n)
+    }
+
+    /// drops the pre-computed values
+    pub fn clear_precomp(&mut self) {
+        self.precomp = vec![];
+        self.pp_len = 0;
+    }
+
     /// check if the parameters are correct -- should not be used other than testing
     #[cfg(test)]
     pub(crate) fn check_parameters(&self, vp: &VerifierParams) -> bool {
@@ -350,3 +435,18 @@ pub fn read_param<R: std::io::Read>(
 
     Ok((pp, vp))
 }
+
+/// As `read_param`, the parameters are used with the ciphersuite `csid`.
+/// The parameters of the ceremony do not depend on the ciphersuite.
+pub fn read_param_with_ciphersuite<R: std::io::Read>(
+    reader: &mut R,
+    csid: Ciphersuite,
+) -> Result<(ProverParams, VerifierParams), String> {
+    if !check_ciphersuite(csid) {
+        return Err(err::ERR_CIPHERSUITE.to_owned());
+    }
+    let (mut pp, mut vp) = read_param(reader)?;
+    pp.ciphersuite = csid;
+    vp.ciphersuite = csid;
+    Ok((pp, vp))
+}
@@ -9,18 +9,22 @@ use pairings::param::*;
 use pairings::*;
 
 impl Proof {
+    /// the ciphersuite the proof was generated with
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
     /// Generate a new proof.
     ///     * input: prover parameter set
     ///     * input: values for the proof
     ///     * input: the index of the proof
     ///     * output: a new proof
     ///     * error: invalid ciphersuite/parameters
-    /// note that if the #values does not match the parameter n,
+    /// note that if there are more values than the parameter n,
     /// an error will be returned.
-    /// if one were to generate a commitment for a vector of length
-    /// less than n, then the caller should pad the vector.
-    /// In this scenario, the caller should define the right
-    /// format for padding.
+    /// a vector of length less than n is treated as a prefix:
+    /// the missing values are zero (not the hash of an empty value),
+    /// so the parameters for n can be used for any shorter vector.
     pub fn new<Blob: AsRef<[u8]>>(
         prover_params: &ProverParams,
         values: &[Blob],
@@ -31,30 +35,30 @@ impl Proof {
             return Err(ERR_CIPHERSUITE.to_owned());
         }
 
-        // check index is valid
-        if index >= prover_params.n {
-            return Err(ERR_INVALID_INDEX.to_owned());
-        };
         // check param
-        if values.len() != prover_params.n {
+        if values.len() > prover_params.n {
             return Err(ERR_INVALID_INDEX.to_owned());
         }
+        // check index is valid
+        if index >= values.len() {
+            return Err(ERR_INVALID_INDEX.to_owned());
+        };
 
         // hash into a set of scalars
         let scalars_fr_repr: Vec<FrRepr> = values
             .iter()
-            .map(|s| hash_to_field_repr_pointproofs(&s.as_ref()))
-            .collect();
+            .map(|s| hash_to_field_repr(prover_params.ciphersuite, &s.as_ref()))
+            .collect::<Result<Vec<FrRepr>, String>>()?;
         let scalars_u64: Vec<&[u64; 4]> = scalars_fr_repr.iter().map(|s| &s.0).collect();
 
         // generate the proof use `sum of product` function
-        // proof = \sum_{i=prover_params.n - index}^{2 * prover_params.n - index}
+        // proof = \sum_{i=prover_params.n - index}^{prover_params.n - index + #values}
         //          param.generator[i]^scarlar_u64[i]
         let proof = pp_sum_of_prod_helper(
             &prover_params,
             &scalars_u64,
             prover_params.n - index,
-            2 * prover_params.n - index,
+            prover_params.n - index + values.len(),
         );
 
         Ok(Self {
@@ -87,7 +91,7 @@ impl Proof {
             return Err(ERR_INVALID_INDEX.to_owned());
         };
         for e in indices {
-            if *e >= prover_params.n {
+            if *e >= values.len() {
                 return Err(ERR_INVALID_INDEX.to_owned());
             }
         }
@@ -97,15 +101,15 @@ impl Proof {
         }
 
         // check param
-        if values.len() != prover_params.n {
+        if values.len() > prover_params.n {
             return Err(ERR_INVALID_INDEX.to_owned());
         }
 
         // hash into a set of scalars
         let scalars_fr_repr: Vec<FrRepr> = values
             .iter()
-            .map(|s| hash_to_field_repr_pointproofs(&s.as_ref()))
-            .collect();
+            .map(|s| hash_to_field_repr(prover_params.ciphersuite, &s.as_ref()))
+            .collect::<Result<Vec<FrRepr>, String>>()?;
         let scalars_u64: Vec<&[u64; 4]> = scalars_fr_repr.iter().map(|s| &s.0).collect();
 
         Ok(indices
@@ -116,7 +120,7 @@ impl Proof {
                     &prover_params,
                     &scalars_u64,
                     prover_params.n - *e,
-                    2 * prover_params.n - *e,
+                    prover_params.n - *e + values.len(),
                 ),
             })
             .collect())
@@ -152,7 +156,7 @@ impl Proof {
             return Err(ERR_INVALID_INDEX.to_owned());
         };
         for e in indices {
-            if *e >= prover_params.n {
+            if *e >= values.len() {
                 println!("index to hight");
                 return Err(ERR_INVALID_INDEX.to_owned());
             }
@@ -163,7 +167,7 @@ impl Proof {
         }
 
         // check param
-        if values.len() != prover_params.n {
+        if values.len() > prover_params.n {
             println!("not correct length"); 
             println!("length values {}", values.len());
             println!("prover params {}", prover_params.n);
@@ -179,15 +183,15 @@ impl Proof {
         // hash into a set of scalars
         let scalars_fr: Vec<Fr> = values
             .iter()
-            .map(|s| hash_to_field_pointproofs(&s.as_ref()))
-            .collect();
+            .map(|s| hash_to_field(prover_params.ciphersuite, &s.as_ref()))
+            .collect::<Result<Vec<Fr>, String>>()?;
         // get the list of scalars for each proof
         let ti = hash_to_ti_fr(commit, indices, &value_sub_vector, prover_params.n)?;
 
         // form the final scalars, which are ti[k]*m[n - indices[k] + j] for each index
         let mut final_scalars: Vec<Fr> = vec![Fr::zero(); 2 * prover_params.n];
         for k in 0..indices.len() {
-            for j in 0..prover_params.n {
+            for j in 0..values.len() {
                 let mut tmp = ti[k];
                 tmp.mul_assign(&scalars_fr[j]);
                 final_scalars[prover_params.n - indices[k] + j].add_assign(&tmp);
@@ -274,9 +278,9 @@ impl Proof {
         // proof_param may be pre-computed -- the code will determine this
         // by checking the length of pre_comp
         if proof_index != changed_index {
-            let mut multiplier = hash_to_field_pointproofs(&value_before);
+            let mut multiplier = hash_to_field(prover_params.ciphersuite, &value_before)?;
             multiplier.negate();
-            multiplier.add_assign(&hash_to_field_pointproofs(&value_after));
+            multiplier.add_assign(&hash_to_field(prover_params.ciphersuite, &value_after)?);
 
             let param_index = changed_index + prover_params.n - proof_index;
             let res = pp_single_exp_helper(&prover_params, multiplier, param_index);
@@ -329,7 +333,10 @@ impl Proof {
         // to take advantage of the pairing product computation, which is faster than two pairings.
 
         // step 1. compute hash_inverse
-        let hash = hash_to_field_pointproofs(&value);
+        let hash = match hash_to_field(com.ciphersuite, &value) {
+            Err(_e) => return false,
+            Ok(p) => p,
+        };
         // we can safely assume that hash is invertible
         // see `hash_to_field` function
         let hash_inverse = hash.inverse().unwrap();
@@ -675,7 +682,10 @@ impl Proof {
         // 1.2 tmp = 1/\sum value_i*t_i
         let mut tmp = Fr::zero();
         for k in 0..set.len() {
-            let mut mi = hash_to_field_pointproofs(value_sub_vector[k].as_ref());
+            let mut mi = match hash_to_field(com.ciphersuite, value_sub_vector[k].as_ref()) {
+                Err(_e) => return false,
+                Ok(p) => p,
+            };
             mi.mul_assign(&ti[k]);
             tmp.add_assign(&mi);
         }
@@ -825,7 +835,10 @@ impl Proof {
             // tmp2 = sum_i m_ij * t_ij
             for k in 0..ti_s[j].len() {
                 let mut tmp3 = ti_s[j][k];
-                let mij = hash_to_field_pointproofs(value_sub_vector[j][k].as_ref());
+                let mij = match hash_to_field(self.ciphersuite, value_sub_vector[j][k].as_ref()) {
+                    Err(_e) => return false,
+                    Ok(p) => p,
+                };
                 tmp3.mul_assign(&mij);
                 tmp2.add_assign(&tmp3);
             }
@@ -249,7 +249,7 @@ impl SerDes for ProverParams {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
         let n = u32::from_le_bytes(buf) as usize;
-        if n > 65536 || n == 0 {
+        if n > MAX_N || n == 0 {
             // set an upper bounded of n
             // to prevent potential DoS kind of attacks
             return Err(std::io::Error::new(
@@ -270,6 +270,14 @@ impl SerDes for ProverParams {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
         let pp_len = u32::from_le_bytes(buf) as usize;
+        // the pre-computed values are either absent, or 3 or 256 per generator,
+        // any other length would not be used and is rejected
+        if pp_len != 0 && pp_len != 3 * n * 2 && pp_len != 256 * n * 2 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                ERR_INVALID_VALUE,
+            ));
+        }
 
         let mut precomp: Vec<PointproofsG1Affine> = vec![];
         for _i in 0..pp_len {
@@ -313,8 +321,8 @@ impl SerDes for VerifierParams {
                 ERR_COMPRESS,
             ));
         }
-        // check that #generators matches sp
-        if self.n != self.generators.len() {
+        // check that #generators and #precomp matches sp value
+        if self.n != self.generators.len() || self.pp_len != self.precomp.len() {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
                 ERR_INVALID_VALUE,
@@ -377,7 +385,7 @@ impl SerDes for VerifierParams {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
         let n = u32::from_le_bytes(buf) as usize;
-        if n > 65536 || n == 0 {
+        if n > MAX_N || n == 0 {
             // set an upper bounded of n
             // to prevent potential DoS kind of attacks
             return Err(std::io::Error::new(
@@ -396,10 +404,17 @@ impl SerDes for VerifierParams {
         let mut buf = [0u8; 4];
         reader.read_exact(&mut buf)?;
         let pp_len = u32::from_le_bytes(buf) as usize;
+        // the pre-computed values are either absent, or 3 or 256 per generator
+        if pp_len != 0 && pp_len != 3 * n && pp_len != 256 * n {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                ERR_INVALID_VALUE,
+            ));
+        }
 
-        let mut precomp: Vec<PointproofsG1Affine> = vec![];
+        let mut precomp: Vec<PointproofsG2Affine> = vec![];
         for _i in 0..pp_len {
-            let g = PointproofsG1Affine::deserialize(reader, true)?;
+            let g = PointproofsG2Affine::deserialize(reader, true)?;
             precomp.push(g);
         }
 
@@ -410,9 +425,71 @@ impl SerDes for VerifierParams {
             ciphersuite: csid[0],
             n,
             generators,
-            pp_len: 0,
-            precomp: vec![],
+            pp_len,
+            precomp,
             gt_elt,
         })
     }
 }
+
+impl Commitment {
+    /// the encoding of the commitment, `|ciphersuite id | commit |` with the point
+    /// compressed in the ZCash BLS12-381 format, COMMIT_LEN bytes.
+    /// it does not depend on the curve library, see the `Encoding` section of SPEC.md.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut buf: Vec<u8> = Vec::with_capacity(COMMIT_LEN);
+        self.serialize(&mut buf, true).map_err(|e| e.to_string())?;
+        Ok(buf)
+    }
+
+    /// decode a commitment from exactly COMMIT_LEN bytes.
+    /// returns an error if the ciphersuite is not supported, if the point is not on the curve
+    /// or not in the group, or if the length does not match.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() != COMMIT_LEN {
+            return Err(ERR_INVALID_VALUE.to_owned());
+        }
+        Self::deserialize(&mut &bytes[..], true).map_err(|e| e.to_string())
+    }
+}
+
+impl Proof {
+    /// the encoding of the proof, `|ciphersuite id | proof |` with the point
+    /// compressed in the ZCash BLS12-381 format, PROOF_LEN bytes.
+    /// it does not depend on the curve library, see the `Encoding` section of SPEC.md.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut buf: Vec<u8> = Vec::with_capacity(PROOF_LEN);
+        self.serialize(&mut buf, true).map_err(|e| e.to_string())?;
+        Ok(buf)
+    }
+
+    /// decode a proof from exactly PROOF_LEN bytes.
+    /// returns an error if the ciphersuite is not supported, if the point is not on the curve
+    /// or not in the group, or if the length does not match.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() != PROOF_LEN {
+            return Err(ERR_INVALID_VALUE.to_owned());
+        }
+        Self::deserialize(&mut &bytes[..], true).map_err(|e| e.to_string())
+    }
+}
+
+impl ProverParams {
+    /// the compressed encoding of the parameters,
+    /// `|ciphersuite id | n | generators | pp_len | [pre_compute]`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut buf: Vec<u8> = vec![];
+        self.serialize(&mut buf, true).map_err(|e| e.to_string())?;
+        Ok(buf)
+    }
+}
+
+impl VerifierParams {
+    /// the compressed encoding of the parameters,
+    /// `|ciphersuite id | n | generators | pp_len | pre_comp | gt_element`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut buf: Vec<u8> = vec![];
+        self.serialize(&mut buf, true).map_err(|e| e.to_string())?;
+        Ok(buf)
+    }
+}
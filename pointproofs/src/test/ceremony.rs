@@ -0,0 +1,79 @@
+use pairing::serdes::SerDes;
+use pairings::ceremony::*;
+use pairings::param::*;
+use pairings::*;
+
+#[test]
+fn test_ceremony() {
+    // three participants, simulated locally
+    let n = 8;
+    let mut ceremony = Ceremony::new(n).unwrap();
+    let mut transcript = vec![];
+    for i in 0..3 {
+        let entropy = format!("this is the secret entropy of participant {}", i);
+        let (params, proof) = ceremony.contribute(&entropy).unwrap();
+        assert!(ceremony.verify_contribution(&params, &proof));
+        ceremony.accept(params.clone(), &proof).unwrap();
+        transcript.push((params, proof));
+    }
+    assert_eq!(ceremony.contributions(), 3);
+
+    // anyone can replay the transcript
+    let replayed = Ceremony::verify_transcript(n, &transcript).unwrap();
+    assert_eq!(replayed.digest(), ceremony.digest());
+    assert_eq!(replayed.params(), ceremony.params());
+
+    // the output is read by read_param and gives working parameters
+    let mut buf: Vec<u8> = vec![];
+    ceremony.write_param(&mut buf).unwrap();
+    let (prover_params, verifier_params) = read_param(&mut &buf[..]).unwrap();
+    assert!(prover_params.check_parameters(&verifier_params));
+    let values: Vec<String> = (0..n).map(|i| format!("this is message number {}", i)).collect();
+    let com = Commitment::new(&prover_params, &values).unwrap();
+    let proof = Proof::new(&prover_params, &values, 5).unwrap();
+    assert!(proof.verify(&verifier_params, &com, &values[5], 5));
+    let (prover_params, _) = ceremony.params().to_params(CIPHERSUITE_XMD_SHA256).unwrap();
+    assert_eq!(prover_params.ciphersuite(), CIPHERSUITE_XMD_SHA256);
+}
+
+#[test]
+fn negative_test_ceremony() {
+    let n = 4;
+    let mut ceremony = Ceremony::new(n).unwrap();
+    assert!(Ceremony::new(0).is_err());
+    assert!(ceremony.contribute("too short").is_err());
+    // nobody contributed, alpha is 1
+    assert!(ceremony.write_param(&mut vec![]).is_err());
+
+    let (params, proof) = ceremony
+        .contribute("this is the secret entropy of participant 0")
+        .unwrap();
+    let (other_params, other_proof) = ceremony
+        .contribute("this is the secret entropy of participant 1")
+        .unwrap();
+    // the proof does not hold for other parameters
+    assert!(!ceremony.verify_contribution(&other_params, &proof));
+    assert!(!ceremony.verify_contribution(&params, &other_proof));
+    // the parameters are not consecutive powers
+    let mut buf: Vec<u8> = vec![];
+    params.serialize(&mut buf, true).unwrap();
+    let mut swapped = CeremonyParams::deserialize(&mut &buf[..], true).unwrap();
+    assert_eq!(swapped, params);
+    let mut other_buf: Vec<u8> = vec![];
+    other_params.serialize(&mut other_buf, true).unwrap();
+    // replace the last g1 power with the one of the other participant
+    let g1_len = 48;
+    let last_g1 = 4 + (2 * n - 2) * g1_len;
+    buf[last_g1..last_g1 + g1_len].copy_from_slice(&other_buf[last_g1..last_g1 + g1_len]);
+    swapped = CeremonyParams::deserialize(&mut &buf[..], true).unwrap();
+    assert!(!ceremony.verify_contribution(&swapped, &proof));
+
+    ceremony.accept(params, &proof).unwrap();
+    // a contribution has to build on the latest transcript
+    assert!(ceremony.accept(other_params, &other_proof).is_err());
+
+    // the proof survives serialization
+    let mut buf: Vec<u8> = vec![];
+    proof.serialize(&mut buf, true).unwrap();
+    assert_eq!(ContributionProof::deserialize(&mut &buf[..], true).unwrap(), proof);
+}
@@ -8,7 +8,7 @@ fn negative_test_commit() {
     let (prover_params, _verifier_params) =
         paramgen_from_seed("This is Leo's Favourite very very very long Seed", 0, n).unwrap();
     let mut prover_params2 = prover_params.clone();
-    prover_params2.ciphersuite = 1;
+    prover_params2.ciphersuite = 2;
 
     let mut init_values = Vec::with_capacity(n);
     for i in 0..n {
@@ -42,7 +42,7 @@ fn negative_test_commit() {
         .update(&prover_params2, 0, &values[0], &new_values[0][..].as_ref())
         .is_err());
 
-    com.ciphersuite = 1;
+    com.ciphersuite = 2;
     assert!(com
         .update(&prover_params2, 0, &values[0], &new_values[0][..].as_ref())
         .is_err());
@@ -69,7 +69,7 @@ fn negative_test_commit_batch_update() {
 
     let mut com = Commitment::new(&prover_params, &values).unwrap();
     let mut com2 = com.clone();
-    com2.ciphersuite = 1;
+    com2.ciphersuite = 2;
 
     // update values
     let mut new_init_values = Vec::with_capacity(n);
@@ -94,7 +94,7 @@ fn negative_test_commit_batch_update() {
         .is_err());
 
     let mut pp2 = prover_params.clone();
-    pp2.ciphersuite = 1;
+    pp2.ciphersuite = 2;
     assert!(com2
         .batch_update(&pp2, &indices, &value_before, &value_after)
         .is_err());
@@ -129,14 +129,15 @@ fn test_commit_edge_cases() {
         new_init_values.push(s.into_bytes());
     }
 
-    // new commitment with value.len != n
+    // new commitment with value.len == 0
     let mut values: Vec<&[u8]> = Vec::with_capacity(n);
     assert!(Commitment::new(&prover_params, &values).is_err());
 
+    // a shorter vector is committed as a prefix, the missing values are zero
     for e in init_values.iter().take(4) {
         values.push(&e);
     }
-    assert!(Commitment::new(&prover_params, &values).is_err());
+    assert!(Commitment::new(&prover_params, &values).is_ok());
     for e in init_values.iter().take(n).skip(4) {
         values.push(&e);
     }
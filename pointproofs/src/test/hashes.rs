@@ -1,6 +1,9 @@
 use ff::{Field, PrimeField};
 use pairing::bls12_381::*;
-use pairings::hash_to_field_pointproofs::os2ip_mod_p;
+use pairings::hash_to_field_pointproofs::{
+    expand_message_xmd_sha256, hash_to_field_repr, hash_to_field_repr_pointproofs, os2ip_mod_p,
+};
+use pairings::param::*;
 
 // examples from
 // https://crypto.stackexchange.com/questions/37537/what-are-i2osp-os2ip-in-rsa-pkcs1
@@ -34,3 +37,73 @@ fn test_os2ip() {
 
     assert_eq!(Fr::from_repr(FrRepr([1, 0, 0, 0])).unwrap(), Fr::one());
 }
+
+// expand_message_xmd test vectors from
+// https://www.rfc-editor.org/rfc/rfc9380.html#appendix-K.1
+#[test]
+fn test_expand_message_xmd_sha256() {
+    let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+    assert_eq!(
+        to_hex(&expand_message_xmd_sha256(b"", dst, 0x20)),
+        "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"
+    );
+    assert_eq!(
+        to_hex(&expand_message_xmd_sha256(b"abc", dst, 0x20)),
+        "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"
+    );
+    assert_eq!(
+        to_hex(&expand_message_xmd_sha256(b"", dst, 0x80)),
+        "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbe\
+         e0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18\
+         eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dc\
+         c541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced"
+    );
+}
+
+// hash_to_field of ciphersuite 1, the reference values are
+// OS2IP(expand_message_xmd(msg, DST_XMD_SHA256, 48)) mod r
+#[test]
+fn test_hash_to_field_xmd_sha256() {
+    assert_eq!(
+        Fr::from_repr(hash_to_field_repr(CIPHERSUITE_XMD_SHA256, b"").unwrap()).unwrap(),
+        Fr::from_str(
+            "21457032588108123539429209975190460550568300993670103029949342587056332582634"
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        Fr::from_repr(hash_to_field_repr(CIPHERSUITE_XMD_SHA256, b"abc").unwrap()).unwrap(),
+        Fr::from_str(
+            "51429740120356259001168133691812355506987378820486845347804175943220577054739"
+        )
+        .unwrap()
+    );
+    // ciphersuite 0 is unchanged
+    assert_eq!(
+        hash_to_field_repr(CIPHERSUITE_SHA512, b"abc").unwrap(),
+        hash_to_field_repr_pointproofs(b"abc")
+    );
+}
+
+#[test]
+fn test_ciphersuite_registry() {
+    assert!(check_ciphersuite(CIPHERSUITE_SHA512));
+    assert!(check_ciphersuite(CIPHERSUITE_XMD_SHA256));
+    assert!(!check_ciphersuite(2));
+    // an unregistered ciphersuite is an error, not a fallback to ciphersuite 0
+    assert!(hash_to_field_repr(2, b"abc").is_err());
+    assert_eq!(
+        hash_to_field_of(CIPHERSUITE_XMD_SHA256),
+        Some(HashToField::XmdSha256(DST_XMD_SHA256))
+    );
+
+    // the preferred common ciphersuite is picked
+    assert_eq!(supported_ciphersuites(), vec![1, 0]);
+    assert_eq!(negotiate_ciphersuite(&[0, 1]), Some(CIPHERSUITE_XMD_SHA256));
+    assert_eq!(negotiate_ciphersuite(&[0, 5]), Some(CIPHERSUITE_SHA512));
+    assert_eq!(negotiate_ciphersuite(&[5]), None);
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
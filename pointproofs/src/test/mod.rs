@@ -1,5 +1,6 @@
 mod aggregate;
 mod c_api;
+mod ceremony;
 mod commit;
 mod hashes;
 mod param;
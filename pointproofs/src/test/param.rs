@@ -1,4 +1,4 @@
-use pairings::param::paramgen_from_seed;
+use pairings::param::{paramgen_from_seed, MAX_N};
 
 #[test]
 fn test_param() {
@@ -30,12 +30,12 @@ fn negative_test_param() {
 
     assert!(paramgen_from_seed(
         "This is Leo's Second Favourite very very very long Seed",
-        1,
+        2,
         n
     )
     .is_err());
 
-    let n = 65537;
+    let n = MAX_N + 1;
     assert!(paramgen_from_seed(
         "This is Leo's Second Favourite very very very long Seed",
         0,
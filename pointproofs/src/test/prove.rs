@@ -9,7 +9,7 @@ fn negative_test_batch_new_proof() {
         paramgen_from_seed("This is Leo's Favourite very very very long Seed", 0, n).unwrap();
 
     let mut pp2 = prover_params.clone();
-    pp2.ciphersuite = 1;
+    pp2.ciphersuite = 2;
     let mut pp3 = prover_params.clone();
     pp3.n = 2;
 
@@ -45,9 +45,9 @@ fn negative_test_proof() {
     let (prover_params, verifier_params) =
         paramgen_from_seed("This is Leo's Favourite very very very long Seed", 0, n).unwrap();
     let mut prover_params2 = prover_params.clone();
-    prover_params2.ciphersuite = 1;
+    prover_params2.ciphersuite = 2;
     let mut verifier_params2 = verifier_params.clone();
-    verifier_params2.ciphersuite = 1;
+    verifier_params2.ciphersuite = 2;
 
     let mut prover_params256 = prover_params.clone();
     prover_params256.precomp_256();
@@ -81,14 +81,14 @@ fn negative_test_proof() {
         .is_err());
 
     let mut proof2 = proof.clone();
-    proof2.ciphersuite = 1;
+    proof2.ciphersuite = 2;
     assert!(proof2
         .update(&prover_params, 0, 1, &values2[1], &values2[2])
         .is_err());
 
     let com = Commitment::new(&prover_params, &values).unwrap();
     let mut com2 = com.clone();
-    com2.ciphersuite = 1;
+    com2.ciphersuite = 2;
     assert!(!proof.verify(&verifier_params2, &com, &values[0], 0));
     assert!(!proof.verify(&verifier_params, &com, &values[0], 9));
     assert!(!proof.verify(&verifier_params, &com2, &values[0], 0));
@@ -444,3 +444,59 @@ fn test_batch_new_proof() {
         "pre_compute256 failed"
     );
 }
+
+#[test]
+fn test_prefix_proof() {
+    // a vector shorter than n is committed and proven against the full parameter set
+    let n = 16usize;
+    let (prover_params, verifier_params) =
+        paramgen_from_seed("This is Leo's Favourite very very very long Seed", 0, n).unwrap();
+
+    let mut values: Vec<String> = vec![];
+    for i in 0..5 {
+        let s = format!("this is message number {}", i);
+        values.push(s);
+    }
+    let com = Commitment::new(&prover_params, &values).unwrap();
+    for (i, value) in values.iter().enumerate() {
+        let proof = Proof::new(&prover_params, &values, i).unwrap();
+        assert!(proof.verify(&verifier_params, &com, value, i));
+        assert!(!proof.verify(&verifier_params, &com, value, i + 1));
+    }
+    assert!(Proof::new(&prover_params, &values, 5).is_err());
+
+    let indices = [4usize, 0, 2];
+    let sub_values: Vec<&String> = indices.iter().map(|i| &values[*i]).collect();
+    let proof = Proof::batch_new_aggregated(&prover_params, &com, &values, &indices).unwrap();
+    assert!(proof.same_commit_batch_verify(&verifier_params, &com, &indices, &sub_values));
+}
+
+#[test]
+fn test_xmd_ciphersuite_proof() {
+    // ciphersuite 1 hashes differently, its commitments and proofs do not mix with ciphersuite 0
+    let n = 8usize;
+    let seed = "This is Leo's Favourite very very very long Seed";
+    let (prover_params, verifier_params) = paramgen_from_seed(seed, 1, n).unwrap();
+    let (prover_params0, verifier_params0) = paramgen_from_seed(seed, 0, n).unwrap();
+
+    let mut values: Vec<String> = vec![];
+    for i in 0..n {
+        let s = format!("this is message number {}", i);
+        values.push(s);
+    }
+    let com = Commitment::new(&prover_params, &values).unwrap();
+    let com0 = Commitment::new(&prover_params0, &values).unwrap();
+    assert_eq!(com.ciphersuite(), 1);
+    assert_ne!(com, com0);
+
+    let proof = Proof::new(&prover_params, &values, 3).unwrap();
+    assert_eq!(proof.ciphersuite(), 1);
+    assert!(proof.verify(&verifier_params, &com, &values[3], 3));
+    assert!(!proof.verify(&verifier_params0, &com, &values[3], 3));
+
+    let indices = [1usize, 6];
+    let sub_values: Vec<&String> = indices.iter().map(|i| &values[*i]).collect();
+    let proof = Proof::batch_new_aggregated(&prover_params, &com, &values, &indices).unwrap();
+    assert!(proof.same_commit_batch_verify(&verifier_params, &com, &indices, &sub_values));
+    assert!(Proof::batch_new_aggregated(&prover_params, &com0, &values, &indices).is_err());
+}
@@ -44,6 +44,15 @@ fn test_serdes_prover_param() {
         assert!(ProverParams::deserialize(&mut buf[..].as_ref(), false).is_err());
         let prover_params_recover = ProverParams::deserialize(&mut buf[..].as_ref(), true).unwrap();
         assert_eq!(prover_params, prover_params_recover);
+        assert_eq!(prover_params_recover.precomp_level(), 256);
+
+        // a pre-computation length that is not 0, 3 or 256 per generator is rejected
+        let pp_len_pos = 5 + n * POINTPROOFSG1_LEN * 2;
+        buf[pp_len_pos..pp_len_pos + 4].copy_from_slice(&1u32.to_le_bytes());
+        assert!(ProverParams::deserialize(&mut buf[..].as_ref(), true).is_err());
+
+        prover_params.clear_precomp();
+        assert_eq!(prover_params.precomp_level(), 0);
     }
 }
 
@@ -95,7 +104,7 @@ fn test_serdes_prover_param_slow() {
 fn test_serdes_verifier_param() {
     let n_array = [16];
     for n in n_array.iter() {
-        let (_prover_params, verifier_params) =
+        let (_prover_params, mut verifier_params) =
             paramgen_from_seed("This is Leo's Favourite very very very long Seed", 0, *n).unwrap();
 
         let mut buf: Vec<u8> = vec![];
@@ -119,6 +128,23 @@ fn test_serdes_verifier_param() {
         assert!(VerifierParams::deserialize(&mut invalid_buf1[..].as_ref(), false).is_err());
         assert!(VerifierParams::deserialize(&mut invalid_buf2[..].as_ref(), true).is_err());
         assert!(VerifierParams::deserialize(&mut invalid_buf2[..].as_ref(), false).is_err());
+
+        // the pre-computed values are kept
+        verifier_params.precomp_3();
+        let mut buf: Vec<u8> = vec![];
+        assert!(verifier_params.serialize(&mut buf, true).is_ok());
+        let verifier_params_recover =
+            VerifierParams::deserialize(&mut buf[..].as_ref(), true).unwrap();
+        assert_eq!(verifier_params, verifier_params_recover);
+        assert_eq!(verifier_params_recover.precomp_level(), 3);
+
+        verifier_params.precomp_256();
+        let mut buf: Vec<u8> = vec![];
+        assert!(verifier_params.serialize(&mut buf, true).is_ok());
+        let verifier_params_recover =
+            VerifierParams::deserialize(&mut buf[..].as_ref(), true).unwrap();
+        assert_eq!(verifier_params, verifier_params_recover);
+        assert_eq!(verifier_params_recover.precomp_level(), 256);
     }
 }
 
@@ -172,7 +198,7 @@ fn test_serdes_commit() {
     assert!(Commitment::deserialize(&mut buf[..].as_ref(), false).is_err());
 
     let invalid_commit = Commitment {
-        ciphersuite: 1,
+        ciphersuite: 2,
         commit: PointproofsG1::one(),
     };
     assert!(invalid_commit.serialize(&mut buf, true).is_err());
@@ -203,7 +229,7 @@ fn test_serdes_proof() {
     assert!(Proof::deserialize(&mut buf[..].as_ref(), false).is_err());
 
     let invalid_proof = Proof {
-        ciphersuite: 1,
+        ciphersuite: 2,
         proof: PointproofsG1::one(),
     };
     assert!(invalid_proof.serialize(&mut buf, true).is_err());
@@ -215,3 +241,37 @@ fn test_serdes_proof() {
     assert!(Proof::deserialize(&mut invalid_buf2[..].as_ref(), true).is_err());
     assert!(Proof::deserialize(&mut invalid_buf2[..].as_ref(), false).is_err());
 }
+
+#[test]
+fn test_encoding() {
+    let commit = Commitment {
+        ciphersuite: 0,
+        commit: PointproofsG1::one(),
+    };
+    let bytes = commit.to_bytes().unwrap();
+    assert_eq!(bytes.len(), COMMIT_LEN);
+    // | ciphersuite | compressed point |, the compression flag is the top bit of the point
+    assert_eq!(bytes[0], 0);
+    assert_eq!(bytes[1] & 0x80, 0x80);
+    assert_eq!(Commitment::from_bytes(&bytes).unwrap(), commit);
+    assert!(Commitment::from_bytes(&bytes[..COMMIT_LEN - 1]).is_err());
+    assert!(Commitment::from_bytes(&[bytes.clone(), vec![0]].concat()).is_err());
+
+    let proof = Proof {
+        ciphersuite: 1,
+        proof: PointproofsG1::one(),
+    };
+    let bytes = proof.to_bytes().unwrap();
+    assert_eq!(bytes.len(), PROOF_LEN);
+    assert_eq!(bytes[0], 1);
+    assert_eq!(Proof::from_bytes(&bytes).unwrap(), proof);
+    let mut invalid_bytes = bytes.clone();
+    invalid_bytes[0] = 2;
+    assert!(Proof::from_bytes(&invalid_bytes).is_err());
+    assert!(Proof {
+        ciphersuite: 2,
+        proof: PointproofsG1::one(),
+    }
+    .to_bytes()
+    .is_err());
+}
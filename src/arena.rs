@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use crate::node_index::NodeIndex;
+
+// number of nodes in a page, a write copies the page of the node if it is shared
+const PAGE_SIZE: usize = 256;
+
+/// The nodes of a tree stored level by level and addressed by their NodeIndex.
+/// The children of a node are found by index, so no node holds pointers to other nodes.
+/// Every level is split in pages that are shared between clones of the arena,
+/// a clone only copies the page pointers and a write only copies the page it changes.
+#[derive(Debug, Clone)]
+pub(crate) struct Arena<T> {
+    levels: Vec<Level<T>>,
+}
+
+#[derive(Debug, Clone)]
+struct Level<T> {
+    len: usize,
+    pages: Vec<Arc<Vec<T>>>,
+}
+
+impl<T: Clone> Arena<T> {
+    /// `levels` holds the nodes of every level from left to right, starting at the root.
+    pub(crate) fn from_levels(levels: Vec<Vec<T>>) -> Self {
+        let levels = levels
+            .into_iter()
+            .map(|nodes| Level {
+                len: nodes.len(),
+                pages: nodes.chunks(PAGE_SIZE).map(|page| Arc::new(page.to_vec())).collect(),
+            })
+            .collect();
+        Arena { levels }
+    }
+
+    /// An arena without levels, filled with `push`.
+    pub(crate) fn new() -> Self {
+        Arena { levels: Vec::new() }
+    }
+
+    /// Appends a node to the right of `level`, which is added if it is the next level.
+    /// Only the last page of the level is written, so the nodes are not copied again.
+    pub(crate) fn push(&mut self, level: usize, node: T) {
+        if self.levels.len() == level {
+            self.levels.push(Level { len: 0, pages: Vec::new() });
+        }
+        let level = &mut self.levels[level];
+        if level.len == level.pages.len() * PAGE_SIZE {
+            level.pages.push(Arc::new(Vec::with_capacity(PAGE_SIZE)));
+        }
+        let page = level.pages.last_mut().expect("the level has a page");
+        Arc::make_mut(page).push(node);
+        level.len += 1;
+    }
+
+    /// Reverses the order of the levels, for an arena that was filled starting at the leaves.
+    pub(crate) fn reverse_levels(&mut self) {
+        self.levels.reverse();
+    }
+
+    /// The number of levels below the root.
+    pub(crate) fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.levels.iter().map(|level| level.len).sum()
+    }
+
+    pub(crate) fn get(&self, node: &NodeIndex) -> Option<&T> {
+        let level = self.levels.get(node.level)?;
+        if node.offset >= level.len {
+            return None;
+        }
+        Some(&level.pages[node.offset / PAGE_SIZE][node.offset % PAGE_SIZE])
+    }
+
+    pub(crate) fn get_mut(&mut self, node: &NodeIndex) -> Option<&mut T> {
+        let level = self.levels.get_mut(node.level)?;
+        if node.offset >= level.len {
+            return None;
+        }
+        Some(&mut Arc::make_mut(&mut level.pages[node.offset / PAGE_SIZE])[node.offset % PAGE_SIZE])
+    }
+
+    /// The nodes of one level from left to right.
+    pub(crate) fn level(&self, level: usize) -> impl Iterator<Item = &T> + '_ {
+        self.levels[level].pages.iter().flat_map(|page| page.iter())
+    }
+
+    /// Every node with its index, level by level from left to right.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (NodeIndex, &T)> + '_ {
+        (0..self.levels.len()).flat_map(move |level| {
+            self.level(level).enumerate().map(move |(offset, node)| (NodeIndex { level, offset }, node))
+        })
+    }
+
+    /// The number of bytes used by the pages, without the heap memory the nodes own.
+    pub(crate) fn page_memory_usage(&self) -> usize {
+        self.levels
+            .iter()
+            .flat_map(|level| &level.pages)
+            .map(|page| std::mem::size_of::<Arc<Vec<T>>>() + page.capacity() * std::mem::size_of::<T>())
+            .sum()
+    }
+}
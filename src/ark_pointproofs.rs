@@ -0,0 +1,622 @@
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use ark_bls12_381::Bls12_381;
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::{AffineRepr, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rayon::prelude::*;
+use sha2::{Digest, Sha512};
+
+use crate::ciphersuite::{check_ciphersuite, expand_message_xmd_sha256, hash_to_field_of, Ciphersuite, HashToField, DST_XMD_SHA256, MAX_N};
+use crate::verkle_tree_point::VerkleTreeError;
+
+/*  Pointproofs over an arkworks pairing, commit, prove, update and verify as in pointproofs/SPEC.md.
+    The curve is a type parameter, BLS12-381 and BN254 are supported. On BLS12-381 the parameters,
+    commitments and proofs are the ones of the pointproofs crate: the seed and the values are hashed the
+    same way and the points have the same encoding, so the two can be mixed.
+    The commitments, the proofs and the prover parameters are in G1 and the verifier parameters in G2.
+    The `group_switched` feature switches the groups as the feature of the pointproofs crate does:
+    proving gets slower and batch verification faster, since its multi exponentiation moves to G1.
+    The prover parameters can be precomputed for faster commit, prove and update, see `Precomputation`.
+    The ciphersuites are the ones of the pointproofs registry, see `ciphersuite`.
+*/
+
+/// The group of the commitments, proofs and prover parameters, G2 with `group_switched`.
+#[cfg(not(feature = "group_switched"))]
+pub type CommitGroup<E> = <E as Pairing>::G1;
+/// The group of the commitments, proofs and prover parameters, G2 with `group_switched`.
+#[cfg(feature = "group_switched")]
+pub type CommitGroup<E> = <E as Pairing>::G2;
+/// The affine points of `CommitGroup`.
+#[cfg(not(feature = "group_switched"))]
+pub type CommitAffine<E> = <E as Pairing>::G1Affine;
+/// The affine points of `CommitGroup`.
+#[cfg(feature = "group_switched")]
+pub type CommitAffine<E> = <E as Pairing>::G2Affine;
+/// The group of the verifier parameters, G1 with `group_switched`.
+#[cfg(not(feature = "group_switched"))]
+pub type VerifierGroup<E> = <E as Pairing>::G2;
+/// The group of the verifier parameters, G1 with `group_switched`.
+#[cfg(feature = "group_switched")]
+pub type VerifierGroup<E> = <E as Pairing>::G1;
+/// The affine points of `VerifierGroup`.
+#[cfg(not(feature = "group_switched"))]
+pub type VerifierAffine<E> = <E as Pairing>::G2Affine;
+/// The affine points of `VerifierGroup`.
+#[cfg(feature = "group_switched")]
+pub type VerifierAffine<E> = <E as Pairing>::G1Affine;
+
+// the product of the pairings of commitment group points with verifier group points, in the argument order of the pairing
+fn multi_pairing<E: Pairing>(commit_points: Vec<CommitGroup<E>>, verifier_points: Vec<VerifierGroup<E>>) -> PairingOutput<E> {
+    #[cfg(not(feature = "group_switched"))]
+    return E::multi_pairing(commit_points, verifier_points);
+    #[cfg(feature = "group_switched")]
+    return E::multi_pairing(verifier_points, commit_points);
+}
+
+/// A pairing curve pointproofs runs on.
+pub trait PointproofsCurve: Pairing {
+    /// The domain separation tag of `CIPHERSUITE_XMD_SHA256` on this curve.
+    const DST_XMD_SHA256: &'static [u8];
+    /// The length of a compressed G1 point.
+    const G1_POINT_LEN: usize;
+    /// The length of a compressed G2 point.
+    const G2_POINT_LEN: usize;
+    /// The length of a compressed commitment or proof point, a G2 point with `group_switched`.
+    const POINT_LEN: usize = if cfg!(feature = "group_switched") { Self::G2_POINT_LEN } else { Self::G1_POINT_LEN };
+}
+
+impl PointproofsCurve for Bls12_381 {
+    const DST_XMD_SHA256: &'static [u8] = DST_XMD_SHA256;
+    const G1_POINT_LEN: usize = 48;
+    const G2_POINT_LEN: usize = 96;
+}
+
+impl PointproofsCurve for ark_bn254::Bn254 {
+    const DST_XMD_SHA256: &'static [u8] = b"POINTPROOFS-V01-CS01-with-BN254FR_XMD:SHA-256_";
+    const G1_POINT_LEN: usize = 32;
+    const G2_POINT_LEN: usize = 64;
+}
+
+/// How much of the prover parameters is precomputed, as `precomp_3` and `precomp_256` of the pointproofs
+/// crate. Precomputation trades memory for speed, see `pointproofs/benchmark.md`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Precomputation {
+    #[default]
+    None,
+    /// 3 points per generator: g, g^{2^128} and their sum. A power of a generator takes 128 doublings
+    /// instead of 255, which speeds up updates.
+    Precomp3,
+    /// 256 points per generator: g^{2^i} for i < 256. A power of a generator only takes additions,
+    /// which speeds up commit, prove and update. The parameters get about 256 times larger.
+    Precomp256,
+}
+
+impl Precomputation {
+    /// The number of precomputed points per generator.
+    pub fn table_len(self) -> usize {
+        match self {
+            Precomputation::None => 0,
+            Precomputation::Precomp3 => 3,
+            Precomputation::Precomp256 => 256,
+        }
+    }
+
+    pub(crate) fn from_table_len(table_len: usize) -> Option<Self> {
+        [Precomputation::None, Precomputation::Precomp3, Precomputation::Precomp256]
+            .into_iter()
+            .find(|precomputation| precomputation.table_len() == table_len)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProverParams<E: PointproofsCurve = Bls12_381> {
+    ciphersuite: Ciphersuite,
+    n: usize,
+    // g^{α^i} for i from 1 to 2n, the entry of n + 1 is the identity
+    generators: Arc<Vec<CommitAffine<E>>>,
+    precomputation: Precomputation,
+    // `precomputation.table_len()` points per generator, see `precompute`
+    precomp: Arc<Vec<CommitAffine<E>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifierParams<E: PointproofsCurve = Bls12_381> {
+    ciphersuite: Ciphersuite,
+    n: usize,
+    // h^{α^i} for i from 1 to n
+    generators: Vec<VerifierAffine<E>>,
+    // e(g, h)^{α^{n+1}}
+    gt_elt: PairingOutput<E>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commitment<E: PointproofsCurve = Bls12_381> {
+    ciphersuite: Ciphersuite,
+    commit: CommitAffine<E>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proof<E: PointproofsCurve = Bls12_381> {
+    ciphersuite: Ciphersuite,
+    proof: CommitAffine<E>,
+}
+
+/// Parameters for up to `n` values, α is the hash of `seed` which has at least 32 bytes.
+/// Only meant for testing, as with `paramgen_from_seed` of the pointproofs crate.
+pub fn paramgen_from_seed<E: PointproofsCurve, Blob: AsRef<[u8]>>(seed: Blob, ciphersuite: Ciphersuite, n: usize) -> Result<(ProverParams<E>, VerifierParams<E>), VerkleTreeError> {
+    if seed.as_ref().len() < 32 || !check_ciphersuite(ciphersuite) || n == 0 || n > MAX_N {
+        return Err(VerkleTreeError::ParamError);
+    }
+    let alpha = hash_to_field::<E>(ciphersuite, seed.as_ref())?;
+
+    let mut prover_powers = Vec::with_capacity(2 * n);
+    let mut verifier_powers = Vec::with_capacity(n);
+    let mut alpha_power = E::ScalarField::one();
+    for i in 1..=2 * n {
+        alpha_power *= alpha;
+        if i == n + 1 {
+            // g^{α^{n+1}} would break the binding of the commitments
+            prover_powers.push(CommitGroup::<E>::zero());
+            continue;
+        }
+        prover_powers.push(CommitAffine::<E>::generator() * alpha_power);
+        if i <= n {
+            verifier_powers.push(VerifierAffine::<E>::generator() * alpha_power);
+        }
+    }
+    params_from_generators(ciphersuite, n, CommitGroup::<E>::normalize_batch(&prover_powers), VerifierGroup::<E>::normalize_batch(&verifier_powers))
+}
+
+/// Writes the parameters as `| ciphersuite | n: u32 | 2n prover points | n verifier points |`, n little-endian
+/// and the points compressed. The prover points start as in the pointproofs encoding of its prover parameters.
+/// The precomputed tables are not written, see `TreeParams::write`.
+pub fn write_params<E: PointproofsCurve, W: Write>(prover_params: &ProverParams<E>, verifier_params: &VerifierParams<E>, writer: &mut W) -> std::io::Result<()> {
+    writer.write_all(&[prover_params.ciphersuite])?;
+    writer.write_all(&(prover_params.n as u32).to_le_bytes())?;
+    prover_params.generators.iter().try_for_each(|point| point.serialize_compressed(&mut *writer)).map_err(std::io::Error::other)?;
+    verifier_params.generators.iter().try_for_each(|point| point.serialize_compressed(&mut *writer)).map_err(std::io::Error::other)
+}
+
+/// Reads parameters written with `write_params`, e(g, h)^{α^{n+1}} is recomputed.
+/// Fails if the points are not the powers of one α, see `is_consistent`.
+pub fn read_params<E: PointproofsCurve, R: Read>(reader: &mut R) -> Result<(ProverParams<E>, VerifierParams<E>), VerkleTreeError> {
+    let (ciphersuite, n) = read_header(reader)?;
+    let prover_powers = read_points::<CommitAffine<E>, _>(reader, 2 * n)?;
+    let verifier_powers = read_points::<VerifierAffine<E>, _>(reader, n)?;
+    let (prover_params, verifier_params) = params_from_generators(ciphersuite, n, prover_powers, verifier_powers)?;
+    if !is_consistent(&prover_params, &verifier_params) {
+        return Err(VerkleTreeError::ParamError);
+    }
+    Ok((prover_params, verifier_params))
+}
+
+// | ciphersuite | n: u32 |, n is bounded by MAX_N before anything is allocated for it
+pub(crate) fn read_header<R: Read>(reader: &mut R) -> Result<(Ciphersuite, usize), VerkleTreeError> {
+    let mut header = [0u8; 5];
+    reader.read_exact(&mut header).map_err(|_| VerkleTreeError::ParamError)?;
+    let n = u32::from_le_bytes(header[1..].try_into().unwrap()) as usize;
+    if !check_ciphersuite(header[0]) || n == 0 || n > MAX_N {
+        return Err(VerkleTreeError::ParamError);
+    }
+    Ok((header[0], n))
+}
+
+pub(crate) fn read_points<P: CanonicalDeserialize, R: Read>(reader: &mut R, count: usize) -> Result<Vec<P>, VerkleTreeError> {
+    (0..count).map(|_| P::deserialize_compressed(&mut *reader).map_err(|_| VerkleTreeError::ParamError)).collect()
+}
+
+// g^{α^{n+1}} has to be the identity, with it the commitments would not be binding
+pub(crate) fn params_from_generators<E: PointproofsCurve>(ciphersuite: Ciphersuite, n: usize, generators: Vec<CommitAffine<E>>, verifier_generators: Vec<VerifierAffine<E>>) -> Result<(ProverParams<E>, VerifierParams<E>), VerkleTreeError> {
+    if n == 0 || generators.len() != 2 * n || verifier_generators.len() != n || !generators[n].is_zero() {
+        return Err(VerkleTreeError::ParamError);
+    }
+    let gt_elt = multi_pairing::<E>(vec![generators[0].into_group()], vec![verifier_generators[n - 1].into_group()]);
+    Ok((
+        ProverParams { ciphersuite, n, generators: Arc::new(generators), precomputation: Precomputation::None, precomp: Arc::new(Vec::new()) },
+        VerifierParams { ciphersuite, n, generators: verifier_generators, gt_elt },
+    ))
+}
+
+/*  Checks that the parameters are the powers of one α, as the pointproofs crate checks a ceremony output.
+    With g and h the generators of the two groups, P_0 = g and P_i the prover points, V_i the verifier points:
+    - e(P_{i+1}, h) = e(P_i, V_1) for i < 2n around the identity P_{n+1}, which e(P_{n+2}, h) = e(P_n, V_2) skips,
+    - e(g, V_{i+1}) = e(P_1, V_i) for i < n,
+    so P_i = g^{α^i} and V_i = h^{α^i} with V_1 = h^α. No point but P_{n+1} may be the identity, which
+    would make α zero. The checks of each group are combined with the powers of a challenge hashed from
+    the parameters, as in `Kzg::read`.
+*/
+pub(crate) fn is_consistent<E: PointproofsCurve>(prover_params: &ProverParams<E>, verifier_params: &VerifierParams<E>) -> bool {
+    let n = prover_params.n;
+    if verifier_params.n != n || prover_params.generators.len() != 2 * n || verifier_params.generators.len() != n {
+        return false;
+    }
+    if prover_params.generators.iter().enumerate().any(|(i, point)| point.is_zero() != (i == n)) || verifier_params.generators.iter().any(|point| point.is_zero()) {
+        return false;
+    }
+    let mut transcript = Vec::new();
+    write_params(prover_params, verifier_params, &mut transcript).expect("writing to a vector does not fail");
+    let r = E::ScalarField::from_be_bytes_mod_order(&Sha512::digest(&transcript));
+    let r_powers: Vec<_> = std::iter::successors(Some(E::ScalarField::one()), |power| Some(*power * r)).take(2 * n).collect();
+
+    let powers: Vec<CommitAffine<E>> = std::iter::once(CommitAffine::<E>::generator()).chain(prover_params.generators.iter().copied()).collect();
+    let steps: Vec<usize> = (0..2 * n).filter(|i| *i != n && *i != n + 1).collect();
+    let shifted: Vec<_> = steps.iter().map(|i| powers[i + 1]).collect();
+    let combined: Vec<_> = steps.iter().map(|i| powers[*i]).collect();
+    let mut commit_points = vec![
+        CommitGroup::<E>::msm_unchecked(&shifted, &r_powers[..steps.len()]),
+        -CommitGroup::<E>::msm_unchecked(&combined, &r_powers[..steps.len()]),
+    ];
+    let (generator, verifier_generators) = (VerifierAffine::<E>::generator().into_group(), &verifier_params.generators);
+    let mut verifier_points = vec![generator, verifier_generators[0].into_group()];
+    if n >= 2 {
+        let gap_challenge = r_powers[steps.len()];
+        commit_points[0] += powers[n + 2] * gap_challenge;
+        commit_points.push(-(powers[n] * gap_challenge));
+        verifier_points.push(verifier_generators[1].into_group());
+    }
+    let verifier_shifted = VerifierGroup::<E>::msm_unchecked(&verifier_generators[1..], &r_powers[..n - 1]);
+    let verifier_combined = VerifierGroup::<E>::msm_unchecked(&verifier_generators[..n - 1], &r_powers[..n - 1]);
+    let prover_check = multi_pairing::<E>(commit_points, verifier_points);
+    let verifier_check = multi_pairing::<E>(vec![powers[0].into_group(), -powers[1].into_group()], vec![verifier_shifted, verifier_combined]);
+    prover_check.is_zero() && verifier_check.is_zero()
+}
+
+impl<E: PointproofsCurve> ProverParams<E> {
+    /// The maximum number of values that can be committed with these parameters.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
+    /// Precomputes the tables of `precomputation` for every generator, `Precomputation::None` drops them.
+    /// Commit, prove and update use the tables on their own.
+    pub fn precompute(&mut self, precomputation: Precomputation) {
+        let tables: Vec<CommitGroup<E>> = self
+            .generators
+            .par_iter()
+            .flat_map_iter(|generator| precomputed_table::<E>(generator, precomputation))
+            .collect();
+        self.precomp = Arc::new(CommitGroup::<E>::normalize_batch(&tables));
+        self.precomputation = precomputation;
+    }
+
+    pub fn precomputation(&self) -> Precomputation {
+        self.precomputation
+    }
+
+    /// The same parameters without the precomputed tables, the generators are shared.
+    pub fn without_precomputation(&self) -> Self {
+        ProverParams { precomputation: Precomputation::None, precomp: Arc::new(Vec::new()), ..self.clone() }
+    }
+
+    /// Writes the precomputed tables as `| table_len: u32 | points |`, the points uncompressed.
+    pub fn write_precomputation<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&(self.precomputation.table_len() as u32).to_le_bytes())?;
+        self.precomp.iter().try_for_each(|point| point.serialize_uncompressed(&mut *writer)).map_err(std::io::Error::other)
+    }
+
+    /*  Reads tables written with `write_precomputation`. The points are not checked, which is what makes reading
+        the tables faster than computing them, so they have to come from a trusted source such as the prover itself.
+        Only commit, prove and update use them, a wrong table gives commitments and proofs that do not verify.
+    */
+    pub fn read_precomputation<R: Read>(&mut self, reader: &mut R) -> Result<(), VerkleTreeError> {
+        let mut table_len = [0u8; 4];
+        reader.read_exact(&mut table_len).map_err(|_| VerkleTreeError::ParamError)?;
+        let precomputation = Precomputation::from_table_len(u32::from_le_bytes(table_len) as usize).ok_or(VerkleTreeError::ParamError)?;
+        let precomp = (0..precomputation.table_len() * self.generators.len())
+            .map(|_| CommitAffine::<E>::deserialize_uncompressed_unchecked(&mut *reader).map_err(|_| VerkleTreeError::ParamError))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.precomp = Arc::new(precomp);
+        self.precomputation = precomputation;
+        Ok(())
+    }
+
+    // generators[index]^scalar, with the table of the generator if there is one
+    fn mul_generator(&self, index: usize, scalar: E::ScalarField) -> CommitGroup<E> {
+        let table_len = self.precomputation.table_len();
+        let table = &self.precomp[index * table_len..(index + 1) * table_len];
+        match self.precomputation {
+            Precomputation::None => self.generators[index] * scalar,
+            Precomputation::Precomp3 => mul_precomp_3::<E>(table, scalar),
+            Precomputation::Precomp256 => mul_precomp_256::<E>(table, scalar),
+        }
+    }
+
+    // \sum_i generators[start + i]^scalars[i]
+    fn sum_of_products(&self, start: usize, scalars: &[E::ScalarField]) -> CommitAffine<E> {
+        match self.precomputation {
+            Precomputation::Precomp256 => scalars
+                .par_iter()
+                .enumerate()
+                .map(|(i, scalar)| self.mul_generator(start + i, *scalar))
+                .sum::<CommitGroup<E>>()
+                .into_affine(),
+            // a multi exponentiation is faster than one power per generator with 3 points per generator
+            Precomputation::None | Precomputation::Precomp3 => CommitGroup::<E>::msm_unchecked(&self.generators[start..start + scalars.len()], scalars).into_affine(),
+        }
+    }
+}
+
+fn precomputed_table<E: PointproofsCurve>(generator: &CommitAffine<E>, precomputation: Precomputation) -> Vec<CommitGroup<E>> {
+    let mut point = generator.into_group();
+    match precomputation {
+        Precomputation::None => Vec::new(),
+        Precomputation::Precomp3 => {
+            let high = (0..128).fold(point, |high, _| high.double());
+            vec![point, high, point + high]
+        }
+        Precomputation::Precomp256 => (0..256)
+            .map(|_| {
+                let power = point;
+                point.double_in_place();
+                power
+            })
+            .collect(),
+    }
+}
+
+// the table holds g, g^{2^128} and their sum, both halves of the scalar are multiplied at once
+fn mul_precomp_3<E: PointproofsCurve>(table: &[CommitAffine<E>], scalar: E::ScalarField) -> CommitGroup<E> {
+    let bits = scalar.into_bigint();
+    let mut result = CommitGroup::<E>::zero();
+    for i in (0..128).rev() {
+        result.double_in_place();
+        match (bits.get_bit(i), bits.get_bit(i + 128)) {
+            (true, false) => result += table[0],
+            (false, true) => result += table[1],
+            (true, true) => result += table[2],
+            (false, false) => {}
+        }
+    }
+    result
+}
+
+// the table holds g^{2^i}, the entries of the set bits of the scalar are added
+fn mul_precomp_256<E: PointproofsCurve>(table: &[CommitAffine<E>], scalar: E::ScalarField) -> CommitGroup<E> {
+    let bits = scalar.into_bigint();
+    table.iter().enumerate().filter(|(i, _)| bits.get_bit(*i)).fold(CommitGroup::<E>::zero(), |sum, (_, point)| sum + *point)
+}
+
+impl<E: PointproofsCurve> VerifierParams<E> {
+    /// The maximum number of values that can be verified with these parameters.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+}
+
+impl<E: PointproofsCurve> Commitment<E> {
+    /// Commits to at most n values, a shorter vector is a prefix where the missing values are zero.
+    pub fn new<Blob: AsRef<[u8]>>(prover_params: &ProverParams<E>, values: &[Blob]) -> Result<Self, VerkleTreeError> {
+        if values.is_empty() || values.len() > prover_params.n {
+            return Err(VerkleTreeError::BuildError);
+        }
+        let scalars = hash_values::<E, _>(prover_params.ciphersuite, values)?;
+        Ok(Commitment { ciphersuite: prover_params.ciphersuite, commit: prover_params.sum_of_products(0, &scalars) })
+    }
+
+    /// Changes the value at `changed_index` from `value_before` to `value_after`.
+    pub fn update<Blob: AsRef<[u8]>>(&mut self, prover_params: &ProverParams<E>, changed_index: usize, value_before: Blob, value_after: Blob) -> Result<(), VerkleTreeError> {
+        if self.ciphersuite != prover_params.ciphersuite || changed_index >= prover_params.n {
+            return Err(VerkleTreeError::UpdateError);
+        }
+        let multiplier = hash_to_field::<E>(self.ciphersuite, value_after.as_ref())? - hash_to_field::<E>(self.ciphersuite, value_before.as_ref())?;
+        self.commit = (prover_params.mul_generator(changed_index, multiplier) + self.commit).into_affine();
+        Ok(())
+    }
+
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
+    pub fn point(&self) -> &CommitAffine<E> {
+        &self.commit
+    }
+
+    /// `| ciphersuite | point |` with the point compressed, on BLS12-381 the encoding of the pointproofs crate.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        point_to_bytes::<E>(self.ciphersuite, &self.commit)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerkleTreeError> {
+        let (ciphersuite, commit) = point_from_bytes::<E>(bytes)?;
+        Ok(Commitment { ciphersuite, commit })
+    }
+}
+
+impl<E: PointproofsCurve> Proof<E> {
+    /// The proof of the value at `index` in `values`, which are committed as by `Commitment::new`.
+    pub fn new<Blob: AsRef<[u8]>>(prover_params: &ProverParams<E>, values: &[Blob], index: usize) -> Result<Self, VerkleTreeError> {
+        if values.len() > prover_params.n || index >= values.len() {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
+        let scalars = hash_values::<E, _>(prover_params.ciphersuite, values)?;
+        // proof = \prod_j generators[n - index + j]^m_j, the value at index meets the identity at n + 1
+        let proof = prover_params.sum_of_products(prover_params.n - index, &scalars);
+        Ok(Proof { ciphersuite: prover_params.ciphersuite, proof })
+    }
+
+    /// One proof for the values at `indices`, the proofs of the indices are aggregated with the
+    /// scalars t_i of `hash_to_ti`.
+    pub fn batch_new_aggregated<Blob: AsRef<[u8]>>(prover_params: &ProverParams<E>, commit: &Commitment<E>, values: &[Blob], indices: &[usize]) -> Result<Self, VerkleTreeError> {
+        let n = prover_params.n;
+        if prover_params.ciphersuite != commit.ciphersuite || values.len() > n || indices.is_empty() || indices.len() > n {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
+        if indices.iter().any(|index| *index >= values.len()) || !has_unique_elements(indices) {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
+        let value_sub_vector: Vec<&[u8]> = indices.iter().map(|index| values[*index].as_ref()).collect();
+        let ti = hash_to_ti(commit, indices, &value_sub_vector)?;
+        let scalars = hash_values::<E, _>(prover_params.ciphersuite, values)?;
+
+        // the scalar of generators[n - index + j] sums t_index * m_j over the indices
+        let mut final_scalars = vec![E::ScalarField::zero(); 2 * n];
+        for (t, index) in ti.iter().zip(indices) {
+            for (j, scalar) in scalars.iter().enumerate() {
+                final_scalars[n - index + j] += *t * scalar;
+            }
+        }
+        Ok(Proof { ciphersuite: prover_params.ciphersuite, proof: prover_params.sum_of_products(0, &final_scalars) })
+    }
+
+    /// Updates the proof of `proof_index` when the value at `changed_index` changes,
+    /// a proof does not change with its own value.
+    pub fn update<Blob: AsRef<[u8]>>(&mut self, prover_params: &ProverParams<E>, proof_index: usize, changed_index: usize, value_before: Blob, value_after: Blob) -> Result<(), VerkleTreeError> {
+        if self.ciphersuite != prover_params.ciphersuite || proof_index >= prover_params.n || changed_index >= prover_params.n {
+            return Err(VerkleTreeError::UpdateError);
+        }
+        if proof_index != changed_index {
+            let multiplier = hash_to_field::<E>(self.ciphersuite, value_after.as_ref())? - hash_to_field::<E>(self.ciphersuite, value_before.as_ref())?;
+            let param_index = changed_index + prover_params.n - proof_index;
+            self.proof = (prover_params.mul_generator(param_index, multiplier) + self.proof).into_affine();
+        }
+        Ok(())
+    }
+
+    /// Checks e(com, h^{α^{n-index}}) = e(g, h)^{α^{n+1} m} * e(proof, h), as
+    /// e(com^{1/m}, h^{α^{n-index}}) * e(proof^{-1/m}, h) = gt_elt with one multi pairing.
+    pub fn verify<Blob: AsRef<[u8]>>(&self, verifier_params: &VerifierParams<E>, com: &Commitment<E>, value: Blob, index: usize) -> bool {
+        if self.ciphersuite != verifier_params.ciphersuite || com.ciphersuite != verifier_params.ciphersuite {
+            return false;
+        }
+        if index >= verifier_params.n {
+            return false;
+        }
+        // hash_to_field is never zero
+        let Ok(hash) = hash_to_field::<E>(com.ciphersuite, value.as_ref()) else {
+            return false;
+        };
+        let hash_inverse = hash.inverse().unwrap();
+        multi_pairing::<E>(
+            vec![com.commit * hash_inverse, -(self.proof * hash_inverse)],
+            vec![verifier_params.generators[verifier_params.n - index - 1].into_group(), VerifierAffine::<E>::generator().into_group()],
+        ) == verifier_params.gt_elt
+    }
+
+    /// Verifies a proof of `batch_new_aggregated`, checks
+    /// e(com, h^{\sum_i α^{n+1-i} t_i / s}) * e(proof^{-1/s}, h) = gt_elt for s = \sum_i m_i t_i.
+    pub fn same_commit_batch_verify<Blob: AsRef<[u8]>>(&self, verifier_params: &VerifierParams<E>, com: &Commitment<E>, set: &[usize], value_sub_vector: &[Blob]) -> bool {
+        let n = verifier_params.n;
+        if self.ciphersuite != verifier_params.ciphersuite || com.ciphersuite != verifier_params.ciphersuite {
+            return false;
+        }
+        if set.is_empty() || set.len() != value_sub_vector.len() || set.len() > n {
+            return false;
+        }
+        if set.iter().any(|index| *index >= n) || !has_unique_elements(set) {
+            return false;
+        }
+        if set.len() == 1 {
+            return self.verify(verifier_params, com, &value_sub_vector[0], set[0]);
+        }
+
+        let (Ok(ti), Ok(hashes)) = (hash_to_ti(com, set, value_sub_vector), hash_values::<E, _>(com.ciphersuite, value_sub_vector)) else {
+            return false;
+        };
+        let sum: E::ScalarField = ti.iter().zip(hashes).map(|(t, hash)| *t * hash).sum();
+        let Some(sum_inverse) = sum.inverse() else {
+            return false;
+        };
+        let scalars: Vec<E::ScalarField> = ti.iter().map(|t| *t * sum_inverse).collect();
+        let bases: Vec<VerifierAffine<E>> = set.iter().map(|index| verifier_params.generators[n - index - 1]).collect();
+        let param_subset_sum = VerifierGroup::<E>::msm_unchecked(&bases, &scalars);
+        multi_pairing::<E>(vec![com.commit.into_group(), -(self.proof * sum_inverse)], vec![param_subset_sum, VerifierAffine::<E>::generator().into_group()]) == verifier_params.gt_elt
+    }
+
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
+    pub fn point(&self) -> &CommitAffine<E> {
+        &self.proof
+    }
+
+    /// `| ciphersuite | point |` with the point compressed, on BLS12-381 the encoding of the pointproofs crate.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        point_to_bytes::<E>(self.ciphersuite, &self.proof)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerkleTreeError> {
+        let (ciphersuite, proof) = point_from_bytes::<E>(bytes)?;
+        Ok(Proof { ciphersuite, proof })
+    }
+}
+
+fn point_to_bytes<E: PointproofsCurve>(ciphersuite: Ciphersuite, point: &CommitAffine<E>) -> Vec<u8> {
+    let mut bytes = vec![ciphersuite];
+    point.serialize_compressed(&mut bytes).expect("serializing to a vector does not fail");
+    bytes
+}
+
+fn point_from_bytes<E: PointproofsCurve>(bytes: &[u8]) -> Result<(Ciphersuite, CommitAffine<E>), VerkleTreeError> {
+    match bytes.split_first() {
+        Some((ciphersuite, mut rest)) if check_ciphersuite(*ciphersuite) => {
+            let point = CommitAffine::<E>::deserialize_compressed(&mut rest).map_err(|_| VerkleTreeError::DecodeError)?;
+            // trailing bytes are not an encoding of a point
+            if !rest.is_empty() {
+                return Err(VerkleTreeError::DecodeError);
+            }
+            Ok((*ciphersuite, point))
+        }
+        _ => Err(VerkleTreeError::DecodeError),
+    }
+}
+
+fn has_unique_elements(indices: &[usize]) -> bool {
+    let mut seen = HashSet::new();
+    indices.iter().all(|index| seen.insert(index))
+}
+
+fn hash_values<E: PointproofsCurve, Blob: AsRef<[u8]>>(ciphersuite: Ciphersuite, values: &[Blob]) -> Result<Vec<E::ScalarField>, VerkleTreeError> {
+    values.iter().map(|value| hash_to_field::<E>(ciphersuite, value.as_ref())).collect()
+}
+
+/// Hashes `input` to a non-zero scalar with the construction the registry gives the ciphersuite:
+/// SHA-512 reduced mod r for `CIPHERSUITE_SHA512`, hash_to_field of RFC 9380 with expand_message_xmd,
+/// SHA-256 and 48 bytes for `CIPHERSUITE_XMD_SHA256`, with the tag of the curve.
+/// Fails with `ParamError` for a ciphersuite that is not registered.
+pub fn hash_to_field<E: PointproofsCurve>(ciphersuite: Ciphersuite, input: &[u8]) -> Result<E::ScalarField, VerkleTreeError> {
+    let scalar = match hash_to_field_of(ciphersuite) {
+        Some(HashToField::XmdSha256(_)) => E::ScalarField::from_be_bytes_mod_order(&expand_message_xmd_sha256(input, E::DST_XMD_SHA256, 48)),
+        Some(HashToField::Sha512) => {
+            let mut hasher = Sha512::new();
+            hasher.input(input);
+            E::ScalarField::from_be_bytes_mod_order(hasher.result().as_ref())
+        }
+        None => return Err(VerkleTreeError::ParamError),
+    };
+    // zero is mapped to one, this should not happen in practise
+    if scalar.is_zero() {
+        Ok(E::ScalarField::one())
+    } else {
+        Ok(scalar)
+    }
+}
+
+// t_i = hash_to_field(i | SHA-512(C | S | m[S])) for i in S, a single index gets 1
+fn hash_to_ti<E: PointproofsCurve, Blob: AsRef<[u8]>>(com: &Commitment<E>, set: &[usize], value_sub_vector: &[Blob]) -> Result<Vec<E::ScalarField>, VerkleTreeError> {
+    if set.len() == 1 {
+        return Ok(vec![E::ScalarField::one()]);
+    }
+    let mut hasher = Sha512::new();
+    hasher.input(com.to_bytes());
+    for index in set {
+        hasher.input(index.to_be_bytes());
+    }
+    for value in value_sub_vector {
+        hasher.input(value.as_ref());
+    }
+    let digest = hasher.result();
+    set.iter().map(|index| hash_to_field::<E>(com.ciphersuite, &[&index.to_be_bytes()[..], digest.as_ref()].concat())).collect()
+}
@@ -0,0 +1,371 @@
+//! this file exposes the KZG verkle tree to C.
+//! the header is generated with cbindgen, see `make header`.
+//! a null pointer or a panic is reported as -1, or false for the verifiers.
+#![allow(non_camel_case_types)]
+#![allow(clippy::missing_safety_doc)]
+
+use std::collections::BTreeMap;
+use std::ffi;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use std::sync::Arc;
+
+use ark_bls12_381::{Fr as F, G1Affine};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::kzg::Kzg;
+use crate::node_index::{level_sizes, NodeIndex};
+use crate::verkle_tree::{BatchProof, ProofNode, VerkleProof, VerkleTree};
+
+/// a value, the bytes are read as a little endian integer modulo the field order
+#[repr(C)]
+#[derive(Clone)]
+pub struct verkle_tree_value {
+    pub(crate) data: *const u8,
+    pub(crate) len: libc::size_t,
+}
+
+/// a tree, built with verkle_tree_new and freed with verkle_tree_free
+#[repr(C)]
+#[derive(Clone)]
+pub struct verkle_tree {
+    data: *mut ffi::c_void,
+}
+
+/// a KZG setup, shared by the trees and the verifiers of a width and freed with verkle_tree_free_setup
+#[repr(C)]
+#[derive(Clone)]
+pub struct verkle_tree_setup {
+    data: *mut ffi::c_void,
+}
+
+/// serialized tree, root or proof, freed with verkle_tree_free_bytes
+#[repr(C)]
+pub struct verkle_tree_bytes {
+    data: *mut u8,
+    len: libc::size_t,
+}
+
+// a proof node is serialized as (commitment, proof, opened points), the points are compressed
+type SerializedNode = (G1Affine, G1Affine, Vec<(F, F)>);
+
+// a tree is serialized as (width, values), the commitments are recomputed from the setup when it is read
+type SerializedTree = (u64, Vec<F>);
+
+// a panic must not unwind into C, it is turned into the error value
+fn catch<T>(error: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(error)
+}
+
+// None for a null pointer, an empty slice may be null
+unsafe fn raw_slice<'a, T>(data: *const T, len: libc::size_t) -> Option<&'a [T]> {
+    match len {
+        0 => Some(&[]),
+        _ if data.is_null() => None,
+        _ => Some(slice::from_raw_parts(data, len)),
+    }
+}
+
+unsafe fn tree_ref<'a>(tree: &verkle_tree) -> Option<&'a VerkleTree> {
+    (tree.data as *const VerkleTree).as_ref()
+}
+
+unsafe fn setup_ref<'a>(setup: &verkle_tree_setup) -> Option<&'a Kzg> {
+    (setup.data as *const Kzg).as_ref()
+}
+
+fn value_to_field(value: &verkle_tree_value) -> Option<F> {
+    let bytes = unsafe { raw_slice(value.data, value.len) }?;
+    Some(F::from_le_bytes_mod_order(bytes))
+}
+
+unsafe fn write_bytes<T: CanonicalSerialize>(value: &T, bytes: *mut verkle_tree_bytes) -> i32 {
+    let mut buf: Vec<u8> = vec![];
+    if bytes.is_null() || value.serialize_compressed(&mut buf).is_err() {
+        return -1;
+    }
+    let mut boxed_buf = buf.into_boxed_slice();
+    let data = boxed_buf.as_mut_ptr();
+    let len = boxed_buf.len();
+    std::mem::forget(boxed_buf);
+    *bytes = verkle_tree_bytes { data, len };
+    0
+}
+
+fn read_bytes<T: CanonicalDeserialize>(bytes: &verkle_tree_bytes) -> Option<T> {
+    let bytes = unsafe { raw_slice(bytes.data, bytes.len) }?;
+    T::deserialize_compressed(bytes).ok()
+}
+
+fn to_serialized_node(node: &ProofNode) -> SerializedNode {
+    (node.commitment, node.proof, node.point.clone())
+}
+
+fn from_serialized_node((commitment, proof, point): SerializedNode) -> ProofNode {
+    ProofNode { commitment, proof, point }
+}
+
+unsafe fn write_setup(kzg: Kzg, setup: *mut verkle_tree_setup) -> i32 {
+    if setup.is_null() {
+        return -1;
+    }
+    *setup = verkle_tree_setup {
+        data: Box::into_raw(Box::new(kzg)) as *mut ffi::c_void,
+    };
+    0
+}
+
+unsafe fn write_tree(verkle: VerkleTree, tree: *mut verkle_tree) -> i32 {
+    if tree.is_null() {
+        return -1;
+    }
+    *tree = verkle_tree {
+        data: Box::into_raw(Box::new(verkle)) as *mut ffi::c_void,
+    };
+    0
+}
+
+/// Read a setup for trees up to width, serialized as by Kzg::write
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_read_setup(bytes: verkle_tree_bytes, width: libc::size_t, setup: *mut verkle_tree_setup) -> i32 {
+    catch(-1, || {
+        let Some(mut bytes) = raw_slice(bytes.data, bytes.len) else {
+            return -1;
+        };
+        match Kzg::read(&mut bytes) {
+            Ok(kzg) if width >= 2 && kzg.max_coefficients() >= width => write_setup(kzg, setup),
+            _ => -1,
+        }
+    })
+}
+
+/// The setup of Kzg::insecure_test_setup, its proofs can be forged by anyone: only for tests
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_insecure_test_setup(width: libc::size_t, setup: *mut verkle_tree_setup) -> i32 {
+    catch(-1, || {
+        if width < 2 {
+            return -1;
+        }
+        write_setup(Kzg::insecure_test_setup(width), setup)
+    })
+}
+
+/// Free a setup, the trees built with it keep their own copy
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_free_setup(setup: verkle_tree_setup) {
+    if !setup.data.is_null() {
+        catch((), || drop(Box::from_raw(setup.data as *mut Kzg)));
+    }
+}
+
+/// Build a tree from n values with a setup for its width
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_new(
+    values: *const verkle_tree_value,
+    n: libc::size_t,
+    width: libc::size_t,
+    setup: verkle_tree_setup,
+    tree: *mut verkle_tree,
+) -> i32 {
+    catch(-1, || {
+        if width < 2 || tree.is_null() {
+            return -1;
+        }
+        let (Some(kzg), Some(datas)) = (
+            setup_ref(&setup),
+            raw_slice(values, n).and_then(|values| values.iter().map(value_to_field).collect::<Option<Vec<F>>>()),
+        ) else {
+            return -1;
+        };
+        match VerkleTree::with_kzg(Arc::new(kzg.clone()), &datas, width) {
+            Ok(verkle) => write_tree(verkle, tree),
+            Err(_) => -1,
+        }
+    })
+}
+
+/// Free a tree
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_free(tree: verkle_tree) {
+    if !tree.data.is_null() {
+        catch((), || drop(Box::from_raw(tree.data as *mut VerkleTree)));
+    }
+}
+
+/// Free serialized bytes
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_free_bytes(bytes: verkle_tree_bytes) {
+    if !bytes.data.is_null() {
+        let s = slice::from_raw_parts_mut(bytes.data, bytes.len);
+        catch((), || drop(Box::from_raw(s as *mut [u8])));
+    }
+}
+
+/// The number of values in the tree, 0 for a null tree
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_leaf_count(tree: verkle_tree) -> libc::size_t {
+    catch(0, || tree_ref(&tree).map_or(0, VerkleTree::leaf_count))
+}
+
+/// Serialize the root commitment, a compressed G1 point
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_root(tree: verkle_tree, root: *mut verkle_tree_bytes) -> i32 {
+    catch(-1, || match tree_ref(&tree).and_then(VerkleTree::root_commitment) {
+        Some(commitment) => write_bytes(&commitment, root),
+        None => -1,
+    })
+}
+
+/// Serialize a tree as its width and values
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_serialize(tree: verkle_tree, bytes: *mut verkle_tree_bytes) -> i32 {
+    catch(-1, || {
+        let Some(verkle) = tree_ref(&tree) else {
+            return -1;
+        };
+        let serialized: SerializedTree = (verkle.width() as u64, verkle.iter().map(|(_, value)| value).collect());
+        write_bytes(&serialized, bytes)
+    })
+}
+
+/// Read a tree serialized by verkle_tree_serialize, its commitments are recomputed with setup
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_deserialize(
+    bytes: verkle_tree_bytes,
+    setup: verkle_tree_setup,
+    tree: *mut verkle_tree,
+) -> i32 {
+    catch(-1, || {
+        if tree.is_null() {
+            return -1;
+        }
+        let (Some(kzg), Some((width, datas))) = (setup_ref(&setup), read_bytes::<SerializedTree>(&bytes)) else {
+            return -1;
+        };
+        let Ok(width) = usize::try_from(width) else {
+            return -1;
+        };
+        if width < 2 {
+            return -1;
+        }
+        match VerkleTree::with_kzg(Arc::new(kzg.clone()), &datas, width) {
+            Ok(verkle) => write_tree(verkle, tree),
+            Err(_) => -1,
+        }
+    })
+}
+
+/// Generate a serialized proof for the value at index
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_prove(
+    tree: verkle_tree,
+    index: libc::size_t,
+    proof: *mut verkle_tree_bytes,
+) -> i32 {
+    catch(-1, || {
+        let Some(Ok((_, verkle_proof))) = tree_ref(&tree).map(|verkle| verkle.prove(index)) else {
+            return -1;
+        };
+        let nodes: Vec<SerializedNode> = verkle_proof.proofs.iter().map(to_serialized_node).collect();
+        write_bytes(&nodes, proof)
+    })
+}
+
+/// Generate a serialized proof for the values at n indices
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_prove_batch(
+    tree: verkle_tree,
+    indices: *const libc::size_t,
+    n: libc::size_t,
+    proof: *mut verkle_tree_bytes,
+) -> i32 {
+    catch(-1, || {
+        let (Some(verkle), Some(indices)) = (tree_ref(&tree), raw_slice(indices, n)) else {
+            return -1;
+        };
+        let Ok((_, batch_proof)) = verkle.prove_batch(indices) else {
+            return -1;
+        };
+        let nodes: BTreeMap<(usize, usize), SerializedNode> = batch_proof
+            .nodes
+            .iter()
+            .map(|(node_index, node)| ((node_index.level, node_index.offset), to_serialized_node(node)))
+            .collect();
+        write_bytes(&nodes, proof)
+    })
+}
+
+/// Verify a serialized proof of the value at index, for a tree of leaf_count values built with setup
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_verify(
+    root: verkle_tree_bytes,
+    proof: verkle_tree_bytes,
+    width: libc::size_t,
+    leaf_count: libc::size_t,
+    index: libc::size_t,
+    value: verkle_tree_value,
+    setup: verkle_tree_setup,
+) -> bool {
+    catch(false, || {
+        let Some(kzg) = setup_ref(&setup) else {
+            return false;
+        };
+        let (root, nodes, value) = match (read_bytes::<G1Affine>(&root), read_bytes::<Vec<SerializedNode>>(&proof), value_to_field(&value)) {
+            (Some(root), Some(nodes), Some(value)) if width >= 2 && !nodes.is_empty() => (root, nodes, value),
+            _ => return false,
+        };
+        if index >= leaf_count {
+            return false;
+        }
+        let verkle_proof = VerkleProof {
+            proofs: nodes.into_iter().map(from_serialized_node).collect(),
+        };
+        // the number of levels comes from leaf_count, so a proof for a different tree shape is rejected
+        let depth = level_sizes(leaf_count, width).len() - 1;
+        VerkleTree::verify_proof(root, &verkle_proof, width, depth, index, value, kzg)
+    })
+}
+
+/// Verify a serialized batch proof of the values at n indices, for a tree of leaf_count values built with setup
+#[no_mangle]
+pub unsafe extern "C" fn verkle_tree_verify_batch(
+    root: verkle_tree_bytes,
+    proof: verkle_tree_bytes,
+    width: libc::size_t,
+    leaf_count: libc::size_t,
+    indices: *const libc::size_t,
+    values: *const verkle_tree_value,
+    n: libc::size_t,
+    setup: verkle_tree_setup,
+) -> bool {
+    catch(false, || {
+        let Some(kzg) = setup_ref(&setup) else {
+            return false;
+        };
+        let (root, nodes) = match (
+            read_bytes::<G1Affine>(&root),
+            read_bytes::<BTreeMap<(usize, usize), SerializedNode>>(&proof),
+        ) {
+            (Some(root), Some(nodes)) if width >= 2 && leaf_count > 0 => (root, nodes),
+            _ => return false,
+        };
+        let (Some(indices), Some(values)) = (raw_slice(indices, n), raw_slice(values, n)) else {
+            return false;
+        };
+        if indices.iter().any(|&index| index >= leaf_count) {
+            return false;
+        }
+        let Some(values) = values.iter().map(value_to_field).collect::<Option<Vec<F>>>() else {
+            return false;
+        };
+        let batch_proof = BatchProof {
+            nodes: nodes
+                .into_iter()
+                .map(|((level, offset), node)| (NodeIndex { level, offset }, from_serialized_node(node)))
+                .collect(),
+        };
+        let depth = level_sizes(leaf_count, width).len() - 1;
+        VerkleTree::batch_proof_verify(root, &batch_proof, width, indices, depth, &values, kzg)
+    })
+}
@@ -0,0 +1,98 @@
+use sha2::{Digest, Sha256};
+
+/*  The pointproofs ciphersuites, as registered in `pointproofs/src/pairings/param.rs`. A ciphersuite
+    selects how values are hashed to scalars, the ids and constructions are those of the pointproofs
+    crate so both hash the same values to the same scalars. The registry lives here so the tree does
+    not need the pointproofs crate and its curve library.
+*/
+
+/// The largest n for which parameters are generated or read.
+/// It bounds the memory that a parameter file can make us allocate.
+pub const MAX_N: usize = 1 << 24;
+
+/// Ciphersuite ID is a wrapper of u8.
+pub type Ciphersuite = u8;
+
+/// The original pointproofs ciphersuite, values are hashed with SHA 512 modulo r.
+pub const CIPHERSUITE_SHA512: Ciphersuite = 0;
+
+/// Values are hashed with hash_to_field of RFC 9380, expand_message_xmd with SHA 256.
+pub const CIPHERSUITE_XMD_SHA256: Ciphersuite = 1;
+
+/// The domain separation tag of `CIPHERSUITE_XMD_SHA256` on BLS12-381.
+pub const DST_XMD_SHA256: &[u8] = b"POINTPROOFS-V01-CS01-with-BLS12381FR_XMD:SHA-256_";
+
+/// The hash_to_field constructions that a ciphersuite can use.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HashToField {
+    /// SHA 512 of the blob, reduced modulo r
+    Sha512,
+    /// hash_to_field of RFC 9380 with expand_message_xmd, SHA 256,
+    /// L = 48 bytes per element and the given domain separation tag
+    XmdSha256(&'static [u8]),
+}
+
+/// The registered ciphersuites with their hash_to_field construction.
+/// A new ciphersuite is added with a new entry, in order of preference.
+const CIPHERSUITES: [(Ciphersuite, HashToField); 2] = [
+    (CIPHERSUITE_XMD_SHA256, HashToField::XmdSha256(DST_XMD_SHA256)),
+    (CIPHERSUITE_SHA512, HashToField::Sha512),
+];
+
+/// Checks if csid is supported, i.e., it is registered.
+pub fn check_ciphersuite(csid: Ciphersuite) -> bool {
+    hash_to_field_of(csid).is_some()
+}
+
+/// The hash_to_field construction of a ciphersuite, None if it is not supported.
+pub fn hash_to_field_of(csid: Ciphersuite) -> Option<HashToField> {
+    CIPHERSUITES.iter().find(|(id, _)| *id == csid).map(|(_, hash)| *hash)
+}
+
+/// The supported ciphersuites, the preferred one first.
+pub fn supported_ciphersuites() -> Vec<Ciphersuite> {
+    CIPHERSUITES.iter().map(|(id, _)| *id).collect()
+}
+
+/// Picks the ciphersuite to use with a peer that supports `peer_csids`:
+/// the first of our supported ciphersuites that the peer also supports.
+/// Returns None if there is no common ciphersuite.
+pub fn negotiate_ciphersuite(peer_csids: &[Ciphersuite]) -> Option<Ciphersuite> {
+    CIPHERSUITES.iter().map(|(id, _)| *id).find(|id| peer_csids.contains(id))
+}
+
+/// expand_message_xmd of RFC 9380 with SHA 256
+/// https://www.rfc-editor.org/rfc/rfc9380.html#section-5.3.1
+/// the dst is at most 255 bytes and len_in_bytes at most 255 * 32.
+pub fn expand_message_xmd_sha256(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    // b_in_bytes = 32 and r_in_bytes = 64 for SHA 256
+    let ell = len_in_bytes.div_ceil(32);
+    assert!(ell <= 255 && len_in_bytes <= 65535 && dst.len() <= 255);
+    // DST_prime = DST || I2OSP(len(DST), 1)
+    let dst_prime = [dst, &[dst.len() as u8]].concat();
+
+    // b_0 = H(Z_pad || msg || I2OSP(len_in_bytes, 2) || I2OSP(0, 1) || DST_prime)
+    let mut hasher = Sha256::new();
+    hasher.input([0u8; 64]);
+    hasher.input(msg);
+    hasher.input((len_in_bytes as u16).to_be_bytes());
+    hasher.input([0u8]);
+    hasher.input(&dst_prime);
+    let b_0 = hasher.result();
+
+    // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)
+    // b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime)
+    let mut uniform_bytes: Vec<u8> = Vec::with_capacity(ell * 32);
+    let mut b_i = [0u8; 32];
+    for i in 1..=ell {
+        let mut hasher = Sha256::new();
+        let xored: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+        hasher.input(xored);
+        hasher.input([i as u8]);
+        hasher.input(&dst_prime);
+        b_i.copy_from_slice(hasher.result().as_ref());
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    uniform_bytes
+}
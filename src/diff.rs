@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use ark_bls12_381::{Fr as F, G1Affine};
+
+use crate::kzg::Kzg;
+use crate::partial_tree::PartialTree;
+use crate::verkle_tree::{BatchProof, VerkleTree, VerkleTreeError};
+
+/// A value that differs between two trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeafChange {
+    pub index: usize,
+    pub old: F,
+    pub new: F,
+}
+
+/// A diff that can be checked against the roots of both trees.
+/// The proof opens the old values of the changed indices in the old tree, applying the new values
+/// to these opened nodes has to give the new root, so no change can be left out.
+#[derive(Debug, Clone)]
+pub struct DiffProof {
+    pub changes: Vec<LeafChange>,
+    /// None if the trees are equal
+    pub proof: Option<BatchProof>,
+}
+
+/// The values that differ between two trees of the same width and size, ordered by index.
+pub fn diff(old_tree: &VerkleTree, new_tree: &VerkleTree) -> Result<Vec<LeafChange>, VerkleTreeError> {
+    if old_tree.width() != new_tree.width() || old_tree.leaf_count() != new_tree.leaf_count() {
+        return Err(VerkleTreeError::IncompatibleTrees);
+    }
+    Ok(old_tree
+        .changed_leaves(new_tree)
+        .into_iter()
+        .map(|(index, old, new)| LeafChange { index, old, new })
+        .collect())
+}
+
+/// The diff with a proof against the old tree.
+pub fn diff_with_proof(old_tree: &VerkleTree, new_tree: &VerkleTree) -> Result<DiffProof, VerkleTreeError> {
+    let changes = diff(old_tree, new_tree)?;
+    if changes.is_empty() {
+        return Ok(DiffProof { changes, proof: None });
+    }
+    let indices: Vec<usize> = changes.iter().map(|change| change.index).collect();
+    let (_, proof) = old_tree.prove_batch(&indices)?;
+    Ok(DiffProof { changes, proof: Some(proof) })
+}
+
+/// Checks that `diff_proof` lists exactly the changes from the tree with root `old_root` to the tree with root `new_root`,
+/// both trees have the setup `kzg`.
+pub fn verify_diff(old_root: G1Affine, new_root: G1Affine, diff_proof: &DiffProof, width: usize, leaf_count: usize, kzg: Arc<Kzg>) -> bool {
+    let changes = &diff_proof.changes;
+    // every index is listed once and really changes
+    if changes.windows(2).any(|pair| pair[0].index >= pair[1].index) || changes.iter().any(|change| change.old == change.new) {
+        return false;
+    }
+    let proof = match &diff_proof.proof {
+        Some(proof) => proof,
+        None => return changes.is_empty() && old_root == new_root,
+    };
+    let indices: Vec<usize> = changes.iter().map(|change| change.index).collect();
+    let old_values: Vec<F> = changes.iter().map(|change| change.old).collect();
+    let mut partial_tree = match PartialTree::new(old_root, proof, width, leaf_count, &indices, &old_values, kzg) {
+        Some(partial_tree) => partial_tree,
+        None => return false,
+    };
+    let writes: Vec<(usize, F)> = changes.iter().map(|change| (change.index, change.new)).collect();
+    partial_tree.update_batch(&writes).is_ok() && partial_tree.root_commitment() == Some(new_root)
+}
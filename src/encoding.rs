@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+
+use ark_bls12_381::Bls12_381;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use crate::ark_pointproofs::{CommitAffine, Commitment, PointproofsCurve, Proof};
+use crate::ciphersuite::Ciphersuite;
+use crate::node_index::NodeIndex;
+use crate::verkle_tree_point::{BatchProof, ProofNode, RangeProof, VerkleProof, VerkleTreeError};
+
+/*  The byte encoding of the commitments and proofs of VerkleTree_point. It is part of the format of
+    stored proofs and does not depend on the curve library:
+    - a commitment or an opening proof is `| ciphersuite: u8 | point |`, the point compressed by arkworks.
+      On BLS12-381 that is the ZCash format of the pointproofs crate (see the Encoding section of
+      pointproofs/SPEC.md), 48 bytes for a G1 point and 96 bytes with `group_switched`, where the
+      points are in G2. On BN254 the points take 32 and 64 bytes.
+    - a node is `| commitment | proof | count: u32 | count × (index: u64 | length: u32 | value) |`
+    - a VerkleProof is `| count: u32 | count × node |`, root first
+    - a BatchProof is `| count: u32 | count × (level: u64 | offset: u64 | node) |` in NodeIndex order
+    - a RangeProof is the BatchProof of its nodes followed by `| count: u32 | count × (length: u32 | value) |`
+    All integers are little-endian.
+*/
+
+/// The encoded length of a commitment on BLS12-381, 49 bytes or 97 with `group_switched`.
+pub const COMMIT_LEN: usize = <Commitment<Bls12_381> as PointEncoding>::ENCODED_LEN;
+/// The encoded length of an opening proof on BLS12-381, 49 bytes or 97 with `group_switched`.
+pub const PROOF_LEN: usize = <Proof<Bls12_381> as PointEncoding>::ENCODED_LEN;
+
+/// The encoding of the pointproofs commitments and proofs and their conversion to the arkworks point.
+pub trait PointEncoding: Sized {
+    /// The point of the commitment group, G1 or G2 with `group_switched`.
+    type Affine: CanonicalSerialize + CanonicalDeserialize;
+
+    /// The ciphersuite and the compressed point.
+    const ENCODED_LEN: usize;
+
+    fn encode(&self) -> Vec<u8>;
+
+    /// Decodes exactly `ENCODED_LEN` bytes, the point has to be in the prime order subgroup.
+    fn decode(bytes: &[u8]) -> Result<Self, VerkleTreeError>;
+
+    /// The ciphersuite and the point.
+    fn to_ark(&self) -> (Ciphersuite, Self::Affine) {
+        let bytes = self.encode();
+        let point = Self::Affine::deserialize_compressed(&bytes[1..]).expect("encoded points are valid points");
+        (bytes[0], point)
+    }
+
+    fn from_ark(ciphersuite: Ciphersuite, point: &Self::Affine) -> Result<Self, VerkleTreeError> {
+        let mut bytes = vec![ciphersuite];
+        point.serialize_compressed(&mut bytes).map_err(|_| VerkleTreeError::DecodeError)?;
+        Self::decode(&bytes)
+    }
+}
+
+impl<E: PointproofsCurve> PointEncoding for Commitment<E> {
+    type Affine = CommitAffine<E>;
+    const ENCODED_LEN: usize = 1 + E::POINT_LEN;
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, VerkleTreeError> {
+        Commitment::from_bytes(bytes)
+    }
+}
+
+impl<E: PointproofsCurve> PointEncoding for Proof<E> {
+    type Affine = CommitAffine<E>;
+    const ENCODED_LEN: usize = 1 + E::POINT_LEN;
+
+    fn encode(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, VerkleTreeError> {
+        Proof::from_bytes(bytes)
+    }
+}
+
+// reads the encoding front to back, running out of bytes is a DecodeError
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], VerkleTreeError> {
+        if self.bytes.len() < len {
+            return Err(VerkleTreeError::DecodeError);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn read_u32(&mut self) -> Result<usize, VerkleTreeError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize)
+    }
+
+    fn read_u64(&mut self) -> Result<usize, VerkleTreeError> {
+        usize::try_from(u64::from_le_bytes(self.take(8)?.try_into().unwrap())).map_err(|_| VerkleTreeError::DecodeError)
+    }
+
+    fn read_value(&mut self) -> Result<Vec<u8>, VerkleTreeError> {
+        let len = self.read_u32()?;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    fn read_node<E: PointproofsCurve>(&mut self) -> Result<ProofNode<E>, VerkleTreeError> {
+        let commitment = Commitment::decode(self.take(Commitment::<E>::ENCODED_LEN)?)?;
+        let proof = Proof::decode(self.take(Proof::<E>::ENCODED_LEN)?)?;
+        let count = self.read_u32()?;
+        let mut indices = Vec::new();
+        let mut values = Vec::new();
+        for _ in 0..count {
+            indices.push(self.read_u64()?);
+            values.push(self.read_value()?);
+        }
+        Ok(ProofNode { commitment, proof, indices, values })
+    }
+
+    fn read_nodes<E: PointproofsCurve>(&mut self) -> Result<BTreeMap<NodeIndex, ProofNode<E>>, VerkleTreeError> {
+        let count = self.read_u32()?;
+        let mut nodes = BTreeMap::new();
+        for _ in 0..count {
+            let node_index = NodeIndex { level: self.read_u64()?, offset: self.read_u64()? };
+            let node = self.read_node()?;
+            // the nodes are written in order, anything else is not an encoding we wrote
+            if nodes.last_key_value().is_some_and(|(last, _)| *last >= node_index) {
+                return Err(VerkleTreeError::DecodeError);
+            }
+            nodes.insert(node_index, node);
+        }
+        Ok(nodes)
+    }
+
+    fn finish<T>(self, decoded: T) -> Result<T, VerkleTreeError> {
+        if !self.bytes.is_empty() {
+            return Err(VerkleTreeError::DecodeError);
+        }
+        Ok(decoded)
+    }
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) {
+    bytes.extend_from_slice(&u32::try_from(len).expect("at most u32::MAX entries").to_le_bytes());
+}
+
+fn write_value(bytes: &mut Vec<u8>, value: &[u8]) {
+    write_len(bytes, value.len());
+    bytes.extend_from_slice(value);
+}
+
+fn write_nodes<E: PointproofsCurve>(bytes: &mut Vec<u8>, nodes: &BTreeMap<NodeIndex, ProofNode<E>>) {
+    write_len(bytes, nodes.len());
+    for (node_index, node) in nodes {
+        bytes.extend_from_slice(&(node_index.level as u64).to_le_bytes());
+        bytes.extend_from_slice(&(node_index.offset as u64).to_le_bytes());
+        node.write(bytes);
+    }
+}
+
+impl<E: PointproofsCurve> ProofNode<E> {
+    fn write(&self, bytes: &mut Vec<u8>) {
+        debug_assert_eq!(self.indices.len(), self.values.len());
+        bytes.extend_from_slice(&self.commitment.encode());
+        bytes.extend_from_slice(&self.proof.encode());
+        write_len(bytes, self.indices.len());
+        for (index, value) in self.indices.iter().zip(&self.values) {
+            bytes.extend_from_slice(&(*index as u64).to_le_bytes());
+            write_value(bytes, value);
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerkleTreeError> {
+        let mut reader = Reader { bytes };
+        let node = reader.read_node()?;
+        reader.finish(node)
+    }
+}
+
+impl<E: PointproofsCurve> VerkleProof<E> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_len(&mut bytes, self.proofs.len());
+        for node in &self.proofs {
+            node.write(&mut bytes);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerkleTreeError> {
+        let mut reader = Reader { bytes };
+        let count = reader.read_u32()?;
+        let proofs = (0..count).map(|_| reader.read_node()).collect::<Result<Vec<_>, _>>()?;
+        reader.finish(VerkleProof { proofs })
+    }
+}
+
+impl<E: PointproofsCurve> BatchProof<E> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_nodes(&mut bytes, &self.nodes);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerkleTreeError> {
+        let mut reader = Reader { bytes };
+        let nodes = reader.read_nodes()?;
+        reader.finish(BatchProof { nodes })
+    }
+}
+
+impl<E: PointproofsCurve> RangeProof<E> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_nodes(&mut bytes, &self.nodes);
+        write_len(&mut bytes, self.values.len());
+        for value in &self.values {
+            write_value(&mut bytes, value);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VerkleTreeError> {
+        let mut reader = Reader { bytes };
+        let nodes = reader.read_nodes()?;
+        let count = reader.read_u32()?;
+        let values = (0..count).map(|_| reader.read_value()).collect::<Result<Vec<_>, _>>()?;
+        reader.finish(RangeProof { nodes, values })
+    }
+}
@@ -0,0 +1,535 @@
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+
+use crate::kzg::Kzg;
+use crate::node_index::level_sizes;
+use crate::verkle_tree::VerkleProof;
+
+/*  Exports proofs of a VerkleTree over BN254 for verification in the EVM, with the precompiles of
+    EIP-196 and EIP-197. Every number is a big-endian uint256 word:
+    - a G1 point is `x | y`, the point at infinity is `0 | 0`
+    - a G2 point is `x_im | x_re | y_im | y_re`, the order of EIP-197
+    - a proof is `commitment | opening proof | position | value` per level, root first, 6 words per level
+    The contract of `solidity_verifier` checks the same as `verify_proof` and the C API: the first
+    commitment is the root, the positions are the digits of the index, every opened value is the
+    mapped commitment of the next level and the last one is the value. The openings of all levels
+    and indices are checked with one pairing, see the contract. `verifier_bytecode` assembles the
+    same contract without a Solidity compiler.
+*/
+
+/// A big-endian uint256.
+pub type Word = [u8; 32];
+
+/// The selector of `verify(uint256[2],uint256,uint256,uint256[])`.
+pub const VERIFY_SELECTOR: [u8; 4] = [0xdd, 0xec, 0xd2, 0xee];
+/// The selector of `verifyProofs(uint256[2],uint256[],uint256[],uint256[])`.
+pub const VERIFY_PROOFS_SELECTOR: [u8; 4] = [0xdd, 0x52, 0x13, 0xda];
+
+const WORDS_PER_LEVEL: usize = 6;
+
+pub fn field_word<F: PrimeField>(value: &F) -> Word {
+    let mut word = [0u8; 32];
+    let bytes = value.into_bigint().to_bytes_be();
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+fn modulus_word<F: PrimeField>() -> Word {
+    let mut word = [0u8; 32];
+    word.copy_from_slice(&F::MODULUS.to_bytes_be());
+    word
+}
+
+pub fn usize_word(value: usize) -> Word {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+    word
+}
+
+pub fn g1_words(point: &G1Affine) -> [Word; 2] {
+    match point.xy() {
+        Some((x, y)) => [field_word(x), field_word(y)],
+        None => [[0; 32]; 2],
+    }
+}
+
+pub fn g2_words(point: &G2Affine) -> [Word; 4] {
+    match point.xy() {
+        Some((x, y)) => [field_word(&x.c1), field_word(&x.c0), field_word(&y.c1), field_word(&y.c0)],
+        None => [[0; 32]; 4],
+    }
+}
+
+/// The words of a proof, 6 per level.
+pub fn proof_words(proof: &VerkleProof<Bn254>) -> Vec<Word> {
+    let mut words = Vec::with_capacity(proof.proofs.len() * WORDS_PER_LEVEL);
+    for node in &proof.proofs {
+        // a proof for a single index opens one child per node
+        debug_assert_eq!(node.point.len(), 1);
+        let (position, value) = node.point[0];
+        words.extend(g1_words(&node.commitment));
+        words.extend(g1_words(&node.proof));
+        words.push(field_word(&position));
+        words.push(field_word(&value));
+    }
+    words
+}
+
+// the head holds the static arguments and the offsets of the dynamic ones, which follow in order
+fn abi_encode(selector: [u8; 4], static_words: &[Word], dynamic: &[Vec<Word>]) -> Vec<u8> {
+    let mut head: Vec<Word> = static_words.to_vec();
+    let mut tail: Vec<Word> = Vec::new();
+    for array in dynamic {
+        head.push(usize_word((static_words.len() + dynamic.len() + tail.len()) * 32));
+        tail.push(usize_word(array.len()));
+        tail.extend_from_slice(array);
+    }
+    let mut calldata = selector.to_vec();
+    calldata.extend(head.iter().chain(&tail).flatten());
+    calldata
+}
+
+/// The calldata of `verify(root, index, value, proof)`.
+pub fn verify_calldata(root: &G1Affine, index: usize, value: &Fr, proof: &VerkleProof<Bn254>) -> Vec<u8> {
+    let [root_x, root_y] = g1_words(root);
+    abi_encode(VERIFY_SELECTOR, &[root_x, root_y, usize_word(index), field_word(value)], &[proof_words(proof)])
+}
+
+/*  The calldata of `verifyProofs(root, indices, values, proofs)`: one single proof per index, concatenated
+    in the order of the indices, which the contract checks with one pairing. It is not the format of a
+    `BatchProof`, whose nodes are shared by the indices.
+*/
+pub fn verify_proofs_calldata(root: &G1Affine, indices: &[usize], values: &[Fr], proofs: &[VerkleProof<Bn254>]) -> Vec<u8> {
+    let indices = indices.iter().map(|&index| usize_word(index)).collect();
+    let values = values.iter().map(field_word).collect();
+    let proofs = proofs.iter().flat_map(proof_words).collect();
+    abi_encode(VERIFY_PROOFS_SELECTOR, &g1_words(root), &[indices, values, proofs])
+}
+
+fn hex(word: &Word) -> String {
+    let digits: String = word.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("0x{}", digits)
+}
+
+/// A Solidity contract that verifies proofs of trees with `width` and `leaf_count` made with the setup `kzg`.
+/// It only needs [τ]₂ of the setup, the calldata is the output of `verify_calldata` or `verify_proofs_calldata`.
+pub fn solidity_verifier(kzg: &Kzg<Bn254>, width: usize, leaf_count: usize) -> String {
+    let levels = level_sizes(leaf_count, width).len();
+    let [neg_g2_x1, neg_g2_x0, neg_g2_y1, neg_g2_y0] = g2_words(&-G2Affine::generator());
+    let [tau_g2_x1, tau_g2_x0, tau_g2_y1, tau_g2_y0] = g2_words(&kzg.g2_powers()[1]);
+    VERIFIER_TEMPLATE
+        .replace("{P}", &hex(&modulus_word::<Fq>()))
+        .replace("{R}", &hex(&modulus_word::<Fr>()))
+        .replace("{WIDTH}", &width.to_string())
+        .replace("{LEAF_COUNT}", &leaf_count.to_string())
+        .replace("{LEVELS}", &levels.to_string())
+        .replace("{NEG_G2_X1}", &hex(&neg_g2_x1))
+        .replace("{NEG_G2_X0}", &hex(&neg_g2_x0))
+        .replace("{NEG_G2_Y1}", &hex(&neg_g2_y1))
+        .replace("{NEG_G2_Y0}", &hex(&neg_g2_y0))
+        .replace("{TAU_G2_X1}", &hex(&tau_g2_x1))
+        .replace("{TAU_G2_X0}", &hex(&tau_g2_x0))
+        .replace("{TAU_G2_Y1}", &hex(&tau_g2_y1))
+        .replace("{TAU_G2_Y0}", &hex(&tau_g2_y0))
+}
+
+const VERIFIER_TEMPLATE: &str = r#"// SPDX-License-Identifier: MIT
+// Generated by verkle-tree, see src/evm.rs for the encoding of the calldata.
+pragma solidity ^0.8.19;
+
+/// Verifies proofs of a KZG verkle tree over BN254 with {LEAF_COUNT} values and width {WIDTH}.
+/// A proof has 6 words per level, root first: the commitment, the opening proof, the position and the value.
+contract VerkleVerifier {
+    uint256 constant P = {P};
+    uint256 constant R = {R};
+    uint256 constant WIDTH = {WIDTH};
+    uint256 constant LEAF_COUNT = {LEAF_COUNT};
+    uint256 constant LEVELS = {LEVELS};
+
+    // -[1]₂ and [τ]₂ as (x_im, x_re, y_im, y_re)
+    uint256 constant NEG_G2_X1 = {NEG_G2_X1};
+    uint256 constant NEG_G2_X0 = {NEG_G2_X0};
+    uint256 constant NEG_G2_Y1 = {NEG_G2_Y1};
+    uint256 constant NEG_G2_Y0 = {NEG_G2_Y0};
+    uint256 constant TAU_G2_X1 = {TAU_G2_X1};
+    uint256 constant TAU_G2_X0 = {TAU_G2_X0};
+    uint256 constant TAU_G2_Y1 = {TAU_G2_Y1};
+    uint256 constant TAU_G2_Y0 = {TAU_G2_Y0};
+
+    function verify(uint256[2] calldata root, uint256 index, uint256 value, uint256[] calldata proof) external view returns (bool) {
+        uint256[] memory indices = new uint256[](1);
+        uint256[] memory values = new uint256[](1);
+        indices[0] = index;
+        values[0] = value;
+        return _verify(root, indices, values, proof);
+    }
+
+    // one single proof per index, concatenated in the order of the indices
+    function verifyProofs(uint256[2] calldata root, uint256[] calldata indices, uint256[] calldata values, uint256[] calldata proofs) external view returns (bool) {
+        return _verify(root, indices, values, proofs);
+    }
+
+    /*  A proof π that the polynomial of commitment C has the value y at position z satisfies
+        e(C - y·[1]₁ + z·π, [1]₂) = e(π, [τ]₂). The openings are combined with the powers of a
+        challenge r and checked at once: e(Σ rⁱ (C_i - y_i·[1]₁ + z_i·π_i), -[1]₂) · e(Σ rⁱ π_i, [τ]₂) = 1.
+        Points that are not on the curve make the precompiles fail and revert the call.
+    */
+    function _verify(uint256[2] calldata root, uint256[] memory indices, uint256[] memory values, uint256[] calldata proofs) internal view returns (bool) {
+        if (indices.length == 0 || values.length != indices.length || proofs.length != indices.length * LEVELS * 6) {
+            return false;
+        }
+        uint256 r = uint256(keccak256(abi.encode(root, indices, values, proofs))) % R;
+        uint256 rPower = 1;
+        // (Σ rⁱ (C_i - y_i·[1]₁ + z_i·π_i), Σ rⁱ π_i), (0, 0) is the point at infinity
+        uint256[4] memory sums;
+        for (uint256 i = 0; i < indices.length; i++) {
+            if (!_checkPath(root, indices[i], values[i], proofs, i * LEVELS * 6)) {
+                return false;
+            }
+            for (uint256 level = 0; level < LEVELS; level++) {
+                _accumulate(sums, proofs, (i * LEVELS + level) * 6, rPower);
+                rPower = mulmod(rPower, r, R);
+            }
+        }
+        return _pairing(sums);
+    }
+
+    // e(sums[0..2], -[1]₂) · e(sums[2..4], [τ]₂) = 1, the input is filled in parts to keep the stack small
+    function _pairing(uint256[4] memory sums) internal view returns (bool) {
+        uint256[12] memory input;
+        (input[0], input[1], input[6], input[7]) = (sums[0], sums[1], sums[2], sums[3]);
+        (input[2], input[3], input[4], input[5]) = (NEG_G2_X1, NEG_G2_X0, NEG_G2_Y1, NEG_G2_Y0);
+        (input[8], input[9], input[10], input[11]) = (TAU_G2_X1, TAU_G2_X0, TAU_G2_Y1, TAU_G2_Y0);
+        (bool ok, bytes memory out) = address(8).staticcall(abi.encode(input));
+        require(ok, "pairing failed");
+        return abi.decode(out, (uint256)) == 1;
+    }
+
+    // the root, positions and values of the path at `start`, the openings are checked by the pairing
+    function _checkPath(uint256[2] calldata root, uint256 index, uint256 value, uint256[] calldata proofs, uint256 start) internal pure returns (bool) {
+        if (index >= LEAF_COUNT || proofs[start] != root[0] || proofs[start + 1] != root[1]) {
+            return false;
+        }
+        uint256 path = 0;
+        for (uint256 level = 0; level < LEVELS; level++) {
+            uint256 o = start + level * 6;
+            // an opened value of R or more is not a scalar
+            if (proofs[o + 4] >= WIDTH || proofs[o + 5] >= R) {
+                return false;
+            }
+            path = path * WIDTH + proofs[o + 4];
+            // the value of a child is x + y of its commitment reduced mod r
+            uint256 expected = level + 1 < LEVELS ? addmod(proofs[o + 6], proofs[o + 7], P) % R : value;
+            if (proofs[o + 5] != expected) {
+                return false;
+            }
+        }
+        return path == index;
+    }
+
+    function _accumulate(uint256[4] memory sums, uint256[] calldata proofs, uint256 o, uint256 rPower) internal view {
+        uint256[2] memory commitment = [proofs[o], proofs[o + 1]];
+        uint256[2] memory opening = [proofs[o + 2], proofs[o + 3]];
+        uint256[2] memory term = _add(commitment, _mul([uint256(1), uint256(2)], R - proofs[o + 5]));
+        term = _mul(_add(term, _mul(opening, proofs[o + 4])), rPower);
+        uint256[2] memory sum = _add([sums[0], sums[1]], term);
+        uint256[2] memory openings = _add([sums[2], sums[3]], _mul(opening, rPower));
+        (sums[0], sums[1], sums[2], sums[3]) = (sum[0], sum[1], openings[0], openings[1]);
+    }
+
+    function _add(uint256[2] memory a, uint256[2] memory b) internal view returns (uint256[2] memory c) {
+        (bool ok, bytes memory out) = address(6).staticcall(abi.encode(a[0], a[1], b[0], b[1]));
+        require(ok, "invalid point");
+        (c[0], c[1]) = abi.decode(out, (uint256, uint256));
+    }
+
+    function _mul(uint256[2] memory a, uint256 scalar) internal view returns (uint256[2] memory c) {
+        (bool ok, bytes memory out) = address(7).staticcall(abi.encode(a[0], a[1], scalar));
+        require(ok, "invalid point");
+        (c[0], c[1]) = abi.decode(out, (uint256, uint256));
+    }
+}
+"#;
+
+/*  The creation bytecode of the contract of `solidity_verifier`, assembled here so it can be deployed
+    without a Solidity compiler. It takes the same calldata and does the same checks in the same order,
+    with the levels unrolled, so it returns the same results. The challenge is hashed from the same
+    `abi.encode(root, indices, values, proofs)`. Calldata that is not ABI encoded reverts, as do points
+    that are not on the curve.
+*/
+pub fn verifier_bytecode(kzg: &Kzg<Bn254>, width: usize, leaf_count: usize) -> Vec<u8> {
+    let runtime = verifier_runtime(kzg, width, leaf_count);
+    // copies the runtime code to memory and returns it
+    let length = (runtime.len() as u16).to_be_bytes();
+    let mut code = vec![op::PUSH2, length[0], length[1], op::DUP1, op::PUSH2, 0, 13, op::PUSH1, 0, op::CODECOPY, op::PUSH1, 0, op::RETURN];
+    code.extend(runtime);
+    code
+}
+
+// the opcodes of the verifier
+mod op {
+    pub const ADD: u8 = 0x01;
+    pub const MUL: u8 = 0x02;
+    pub const SUB: u8 = 0x03;
+    pub const MOD: u8 = 0x06;
+    pub const ADDMOD: u8 = 0x08;
+    pub const MULMOD: u8 = 0x09;
+    pub const LT: u8 = 0x10;
+    pub const GT: u8 = 0x11;
+    pub const EQ: u8 = 0x14;
+    pub const ISZERO: u8 = 0x15;
+    pub const SHR: u8 = 0x1c;
+    pub const KECCAK256: u8 = 0x20;
+    pub const CALLDATALOAD: u8 = 0x35;
+    pub const CALLDATASIZE: u8 = 0x36;
+    pub const CALLDATACOPY: u8 = 0x37;
+    pub const CODECOPY: u8 = 0x39;
+    pub const POP: u8 = 0x50;
+    pub const MLOAD: u8 = 0x51;
+    pub const MSTORE: u8 = 0x52;
+    pub const JUMP: u8 = 0x56;
+    pub const JUMPI: u8 = 0x57;
+    pub const GAS: u8 = 0x5a;
+    pub const JUMPDEST: u8 = 0x5b;
+    pub const PUSH1: u8 = 0x60;
+    pub const PUSH2: u8 = 0x61;
+    pub const DUP1: u8 = 0x80;
+    pub const DUP2: u8 = 0x81;
+    pub const DUP3: u8 = 0x82;
+    pub const SWAP1: u8 = 0x90;
+    pub const STATICCALL: u8 = 0xfa;
+    pub const RETURN: u8 = 0xf3;
+    pub const REVERT: u8 = 0xfd;
+}
+
+// the memory of the verifier: the input and output of the precompiles, then one word per variable
+const COUNT: usize = 0x200;
+const VALUES_LEN: usize = 0x220;
+const PROOFS_LEN: usize = 0x240;
+const INDICES: usize = 0x260;
+const VALUES: usize = 0x280;
+const PROOFS: usize = 0x2a0;
+const CHALLENGE: usize = 0x2c0;
+const R_POWER: usize = 0x2e0;
+const I: usize = 0x300;
+const START: usize = 0x320;
+const INDEX: usize = 0x340;
+const VALUE: usize = 0x360;
+const PATH: usize = 0x380;
+// Σ rⁱ (C_i - y_i·[1]₁ + z_i·π_i) and Σ rⁱ π_i
+const SUMS: usize = 0x3a0;
+const TERM: usize = 0x420;
+// abi.encode(root, indices, values, proofs)
+const TRANSCRIPT: usize = 0x460;
+
+// EVM code with labels, every jump is a PUSH2 that is patched when the code is done
+#[derive(Default)]
+struct Assembler {
+    code: Vec<u8>,
+    labels: Vec<Option<usize>>,
+    jumps: Vec<(usize, usize)>,
+}
+
+impl Assembler {
+    fn op(&mut self, opcode: u8) -> &mut Self {
+        self.code.push(opcode);
+        self
+    }
+
+    // the shortest push of a big-endian number
+    fn push(&mut self, bytes: &[u8]) -> &mut Self {
+        let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len() - 1);
+        self.code.push(op::PUSH1 + (bytes.len() - start - 1) as u8);
+        self.code.extend_from_slice(&bytes[start..]);
+        self
+    }
+
+    fn push_usize(&mut self, value: usize) -> &mut Self {
+        self.push(&(value as u64).to_be_bytes())
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels.push(None);
+        self.labels.len() - 1
+    }
+
+    fn place(&mut self, label: usize) -> &mut Self {
+        self.labels[label] = Some(self.code.len());
+        self.op(op::JUMPDEST)
+    }
+
+    fn jump(&mut self, label: usize) -> &mut Self {
+        self.push_label(label).op(op::JUMP)
+    }
+
+    // jumps if the top of the stack is not zero
+    fn jump_if(&mut self, label: usize) -> &mut Self {
+        self.push_label(label).op(op::JUMPI)
+    }
+
+    fn push_label(&mut self, label: usize) -> &mut Self {
+        self.code.push(op::PUSH2);
+        self.jumps.push((self.code.len(), label));
+        self.code.extend([0, 0]);
+        self
+    }
+
+    fn load(&mut self, address: usize) -> &mut Self {
+        self.push_usize(address).op(op::MLOAD)
+    }
+
+    // stores the top of the stack
+    fn store(&mut self, address: usize) -> &mut Self {
+        self.push_usize(address).op(op::MSTORE)
+    }
+
+    // the word at `offset` bytes into the proof of the current index
+    fn proof_word(&mut self, offset: usize) -> &mut Self {
+        self.load(START).push_usize(offset).op(op::ADD).op(op::CALLDATALOAD)
+    }
+
+    // copies the two words of a point from `from` to `to` in memory
+    fn copy_point(&mut self, from: usize, to: usize) -> &mut Self {
+        self.load(from).store(to).load(from + 32).store(to + 32)
+    }
+
+    // copies the two words of a point at `offset` bytes into the proof to memory at 0
+    fn proof_point(&mut self, offset: usize) -> &mut Self {
+        self.push_usize(64).load(START).push_usize(offset).op(op::ADD).push_usize(0).op(op::CALLDATACOPY)
+    }
+
+    // calls a precompile on `input_len` bytes at 0 and writes the 2 words of the result to `output`
+    fn precompile(&mut self, address: usize, input_len: usize, output: usize, revert: usize) -> &mut Self {
+        self.push_usize(64).push_usize(output).push_usize(input_len).push_usize(0).push_usize(address).op(op::GAS).op(op::STATICCALL);
+        self.op(op::ISZERO).jump_if(revert)
+    }
+
+    // the length and the offset of the data of the dynamic array whose offset is the argument at `head`
+    fn array(&mut self, head: usize, len: usize, data: usize, revert: usize) -> &mut Self {
+        self.push_usize(head).op(op::CALLDATALOAD);
+        self.op(op::DUP1).push_usize(u32::MAX as usize).op(op::LT).jump_if(revert);
+        self.push_usize(4).op(op::ADD).op(op::DUP1).op(op::CALLDATALOAD);
+        self.op(op::DUP1).push_usize(u32::MAX as usize).op(op::LT).jump_if(revert);
+        self.op(op::DUP1).store(len).op(op::SWAP1).push_usize(32).op(op::ADD).op(op::DUP1).store(data);
+        // the array has to be in the calldata
+        self.op(op::SWAP1).push_usize(32).op(op::MUL).op(op::ADD).op(op::CALLDATASIZE).op(op::LT).jump_if(revert)
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        for (offset, label) in self.jumps {
+            let target = self.labels[label].expect("every label is placed") as u16;
+            self.code[offset..offset + 2].copy_from_slice(&target.to_be_bytes());
+        }
+        self.code
+    }
+}
+
+fn verifier_runtime(kzg: &Kzg<Bn254>, width: usize, leaf_count: usize) -> Vec<u8> {
+    let levels = level_sizes(leaf_count, width).len();
+    let (p, r) = (modulus_word::<Fq>(), modulus_word::<Fr>());
+    let mut a = Assembler::default();
+    let (single, proofs, checks, next, pairing, reject, revert) = (a.label(), a.label(), a.label(), a.label(), a.label(), a.label(), a.label());
+
+    // the selector picks how the indices and values are read
+    a.op(op::CALLDATASIZE).push_usize(4).op(op::GT).jump_if(revert);
+    a.push_usize(0).op(op::CALLDATALOAD).push_usize(224).op(op::SHR);
+    a.op(op::DUP1).push(&VERIFY_SELECTOR).op(op::EQ).jump_if(single);
+    a.push(&VERIFY_PROOFS_SELECTOR).op(op::EQ).jump_if(proofs);
+    a.jump(revert);
+
+    // verify(root, index, value, proof), an index and a value in place of the arrays
+    a.place(single).op(op::POP);
+    a.push_usize(1).store(COUNT).push_usize(1).store(VALUES_LEN).push_usize(68).store(INDICES).push_usize(100).store(VALUES);
+    a.array(132, PROOFS_LEN, PROOFS, revert).jump(checks);
+
+    // verifyProofs(root, indices, values, proofs)
+    a.place(proofs);
+    a.array(68, COUNT, INDICES, revert).array(100, VALUES_LEN, VALUES, revert).array(132, PROOFS_LEN, PROOFS, revert);
+
+    a.place(checks);
+    a.load(COUNT).op(op::ISZERO).jump_if(reject);
+    a.load(VALUES_LEN).load(COUNT).op(op::EQ).op(op::ISZERO).jump_if(reject);
+    a.load(COUNT).push_usize(levels * WORDS_PER_LEVEL).op(op::MUL).load(PROOFS_LEN).op(op::EQ).op(op::ISZERO).jump_if(reject);
+
+    // r = keccak256(abi.encode(root, indices, values, proofs)) % R, the head holds the root and the offsets
+    a.push_usize(64).push_usize(4).push_usize(TRANSCRIPT).op(op::CALLDATACOPY);
+    a.push_usize(160).store(TRANSCRIPT + 64);
+    a.load(COUNT).push_usize(32).op(op::MUL);
+    a.op(op::DUP1).push_usize(192).op(op::ADD).store(TRANSCRIPT + 96);
+    a.op(op::DUP1).op(op::DUP1).op(op::ADD).push_usize(224).op(op::ADD).store(TRANSCRIPT + 128);
+    // the arrays follow as `length | elements` with 32·count bytes on the stack
+    a.push_usize(TRANSCRIPT + 160);
+    for data in [INDICES, VALUES] {
+        a.load(COUNT).op(op::DUP2).op(op::MSTORE);
+        a.op(op::DUP2).load(data).op(op::DUP3).push_usize(32).op(op::ADD).op(op::CALLDATACOPY);
+        a.push_usize(32).op(op::ADD).op(op::DUP2).op(op::ADD);
+    }
+    a.load(PROOFS_LEN).op(op::DUP2).op(op::MSTORE);
+    a.load(PROOFS_LEN).push_usize(32).op(op::MUL).load(PROOFS).op(op::DUP3).push_usize(32).op(op::ADD).op(op::CALLDATACOPY);
+    a.push_usize(32).op(op::ADD).load(PROOFS_LEN).push_usize(32).op(op::MUL).op(op::ADD).op(op::SWAP1).op(op::POP);
+    a.push_usize(TRANSCRIPT).op(op::SWAP1).op(op::SUB).push_usize(TRANSCRIPT).op(op::KECCAK256);
+    a.push(&r).op(op::SWAP1).op(op::MOD).store(CHALLENGE);
+    a.push_usize(1).store(R_POWER);
+
+    // the sums start at the point at infinity, every index is checked and accumulated in turn
+    a.place(next);
+    a.load(I).load(COUNT).op(op::EQ).jump_if(pairing);
+    a.load(I).push_usize(levels * WORDS_PER_LEVEL * 32).op(op::MUL).load(PROOFS).op(op::ADD).store(START);
+    a.load(I).push_usize(32).op(op::MUL).load(INDICES).op(op::ADD).op(op::CALLDATALOAD).store(INDEX);
+    a.load(I).push_usize(32).op(op::MUL).load(VALUES).op(op::ADD).op(op::CALLDATALOAD).store(VALUE);
+
+    // the root, positions and values of the path, as `_checkPath`
+    a.push_usize(leaf_count).load(INDEX).op(op::LT).op(op::ISZERO).jump_if(reject);
+    a.proof_word(0).push_usize(4).op(op::CALLDATALOAD).op(op::EQ).op(op::ISZERO).jump_if(reject);
+    a.proof_word(32).push_usize(36).op(op::CALLDATALOAD).op(op::EQ).op(op::ISZERO).jump_if(reject);
+    a.push_usize(0).store(PATH);
+    for level in 0..levels {
+        let o = level * WORDS_PER_LEVEL * 32;
+        a.push_usize(width).proof_word(o + 128).op(op::LT).op(op::ISZERO).jump_if(reject);
+        a.push(&r).proof_word(o + 160).op(op::LT).op(op::ISZERO).jump_if(reject);
+        a.proof_word(o + 128).load(PATH).push_usize(width).op(op::MUL).op(op::ADD).store(PATH);
+        if level + 1 < levels {
+            // x + y of the commitment of the next level reduced mod r
+            a.push(&r).push(&p).proof_word(o + 224).proof_word(o + 192).op(op::ADDMOD).op(op::MOD);
+        } else {
+            a.load(VALUE);
+        }
+        a.proof_word(o + 160).op(op::EQ).op(op::ISZERO).jump_if(reject);
+    }
+    a.load(PATH).load(INDEX).op(op::EQ).op(op::ISZERO).jump_if(reject);
+
+    // the openings, as `_accumulate`
+    for level in 0..levels {
+        let o = level * WORDS_PER_LEVEL * 32;
+        // (R - y)·[1]₁ + C
+        a.push_usize(1).store(0).push_usize(2).store(32).proof_word(o + 160).push(&r).op(op::SUB).store(64);
+        a.precompile(7, 96, TERM, revert);
+        a.proof_point(o).copy_point(TERM, 64).precompile(6, 128, TERM, revert);
+        // + z·π
+        a.proof_point(o + 64).proof_word(o + 128).store(64).precompile(7, 96, 64, revert);
+        a.copy_point(TERM, 0).precompile(6, 128, TERM, revert);
+        // rⁱ·term and rⁱ·π are added to the sums
+        a.copy_point(TERM, 0).load(R_POWER).store(64).precompile(7, 96, TERM, revert);
+        a.copy_point(SUMS, 0).copy_point(TERM, 64).precompile(6, 128, SUMS, revert);
+        a.proof_point(o + 64).load(R_POWER).store(64).precompile(7, 96, 64, revert);
+        a.copy_point(SUMS + 64, 0).precompile(6, 128, SUMS + 64, revert);
+        a.push(&r).load(CHALLENGE).load(R_POWER).op(op::MULMOD).store(R_POWER);
+    }
+    a.load(I).push_usize(1).op(op::ADD).store(I).jump(next);
+
+    // e(sums[0..2], -[1]₂) · e(sums[2..4], [τ]₂) = 1
+    a.place(pairing);
+    a.copy_point(SUMS, 0).copy_point(SUMS + 64, 192);
+    let g2 = g2_words(&-G2Affine::generator()).into_iter().zip([64, 96, 128, 160]);
+    let tau = g2_words(&kzg.g2_powers()[1]).into_iter().zip([256, 288, 320, 352]);
+    for (word, address) in g2.chain(tau) {
+        a.push(&word).store(address);
+    }
+    a.push_usize(32).push_usize(0).push_usize(384).push_usize(0).push_usize(8).op(op::GAS).op(op::STATICCALL).op(op::ISZERO).jump_if(revert);
+    a.push_usize(0).op(op::MLOAD).push_usize(1).op(op::EQ).store(0).push_usize(32).push_usize(0).op(op::RETURN);
+
+    a.place(reject).push_usize(0).store(0).push_usize(32).push_usize(0).op(op::RETURN);
+    a.place(revert).push_usize(0).push_usize(0).op(op::REVERT);
+    a.finish()
+}
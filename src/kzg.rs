@@ -0,0 +1,276 @@
+use std::io::{Read, Write};
+
+use ark_bls12_381::Bls12_381;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{DenseUVPolynomial, Polynomial};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::UniformRand;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
+
+use crate::tree_params::DEFAULT_SEED;
+use crate::verkle_tree::VerkleTreeError;
+
+/*  KZG commitments over an arkworks pairing, the commitment scheme of VerkleTree.
+    A polynomial p is committed as C = [p(τ)]₁. An opening claims p(x_i) = y_i for a set of points:
+    - its proof is π = [q(τ)]₁ with q = (p - I) / Z, where I is the interpolation polynomial of the points
+      and Z = Π (X - x_i) the vanishing polynomial. The division has no remainder iff p goes through the points.
+    - it is verified with e(C - [I(τ)]₁, [1]₂) = e(π, [Z(τ)]₂). For a single point (z, y), I = y and
+      Z = X - z, which is rearranged to e(C - [y]₁ + z·π, [1]₂) = e(π, [τ]₂) so Z needs no G2 commitment.
+    Several openings are verified at once with a random linear combination: the equation of opening i is
+    raised to rⁱ and all equations are multiplied, which takes one pairing product with a pairing for
+    [1]₂, one for [τ]₂ and one per opening of more than one point. A false opening makes the product
+    differ from 1 unless r is a root of a polynomial fixed by the openings, r is hashed from the openings.
+    Anyone who knows τ can open a commitment to any value, so the prover and the verifiers have to share a
+    setup whose τ nobody knows. This crate has no multi-party ceremony: `setup` draws τ and drops it, so
+    whoever runs it has to be trusted, and its output is shared with `write` and `read`.
+    The τ of `insecure_test_setup` is the hash of a public seed, its proofs can be forged by anyone.
+*/
+#[derive(Debug, Clone)]
+pub struct Kzg<E: Pairing = Bls12_381> {
+    // [τ^i]₁ and [τ^i]₂ for i = 0..=degree + 1
+    g1_powers: Vec<E::G1Affine>,
+    g2_powers: Vec<E::G2Affine>,
+}
+
+/// A claim that the polynomial committed in `commitment` goes through `points`, with its proof.
+#[derive(Debug, Clone, Copy)]
+pub struct Opening<'a, E: Pairing> {
+    pub commitment: &'a E::G1Affine,
+    pub points: &'a [(E::ScalarField, E::ScalarField)],
+    pub proof: &'a E::G1Affine,
+}
+
+// the domain separation of the batch challenge
+const BATCH_CHALLENGE_DST: &[u8] = b"VERKLE-TREE-KZG-BATCH-V01";
+
+impl<E: Pairing> Kzg<E> {
+    /// A setup for polynomials with up to `degree + 1` coefficients whose τ is SHA-512 of `DEFAULT_SEED`.
+    /// τ is public, so the proofs of this setup can be forged: it is only meant for tests and benchmarks.
+    pub fn insecure_test_setup(degree: usize) -> Self {
+        Self::from_tau(E::ScalarField::from_be_bytes_mod_order(&Sha512::digest(DEFAULT_SEED.as_bytes())), degree)
+    }
+
+    /// A setup for `degree` with a random τ that is dropped once the powers are computed.
+    /// Whoever runs it has to be trusted to not keep τ, the others load its output with `read`.
+    pub fn setup<R: RngCore + CryptoRng>(degree: usize, rng: &mut R) -> Self {
+        Self::from_tau(E::ScalarField::rand(rng), degree)
+    }
+
+    // the setup of a known τ, for test vectors
+    pub(crate) fn from_tau(tau: E::ScalarField, degree: usize) -> Self {
+        let mut g1_powers = Vec::with_capacity(degree + 2);
+        let mut g2_powers = Vec::with_capacity(degree + 2);
+        let mut tau_power = E::ScalarField::from(1u32);
+        for _ in 0..degree + 2 {
+            g1_powers.push(E::G1Affine::generator() * tau_power);
+            g2_powers.push(E::G2Affine::generator() * tau_power);
+            tau_power *= tau;
+        }
+        Kzg {
+            g1_powers: E::G1::normalize_batch(&g1_powers),
+            g2_powers: E::G2::normalize_batch(&g2_powers),
+        }
+    }
+
+    /// Writes `| degree: u32 | [τ^i]₁ | [τ^i]₂ |` for i = 0..=degree + 1, the points are compressed.
+    pub fn write<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&((self.g1_powers.len() - 2) as u32).to_le_bytes())?;
+        self.g1_powers.iter().try_for_each(|point| point.serialize_compressed(&mut *writer)).map_err(std::io::Error::other)?;
+        self.g2_powers.iter().try_for_each(|point| point.serialize_compressed(&mut *writer)).map_err(std::io::Error::other)
+    }
+
+    /// Reads a setup written with `write`, fails if the points are not the powers of one τ.
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, VerkleTreeError> {
+        let mut degree = [0u8; 4];
+        reader.read_exact(&mut degree).map_err(|_| VerkleTreeError::ParamError)?;
+        let count = u32::from_le_bytes(degree) as usize + 2;
+        let kzg = Kzg {
+            g1_powers: read_points(reader, count)?,
+            g2_powers: read_points(reader, count)?,
+        };
+        if !kzg.is_consistent() {
+            return Err(VerkleTreeError::ParamError);
+        }
+        Ok(kzg)
+    }
+
+    /*  Checks that the powers start at the generators and that every power is the previous one times the same τ,
+        with e([τ^{i+1}]₁, [1]₂) = e([τ^i]₁, [τ]₂) and e([1]₁, [τ^{i+1}]₂) = e([τ]₁, [τ^i]₂). The checks of all i
+        are combined with the powers of a challenge hashed from the setup, as in `batch_verify`.
+    */
+    fn is_consistent(&self) -> bool {
+        let count = self.g1_powers.len();
+        if count < 2 || self.g2_powers.len() != count || self.g1_powers[1].is_zero() {
+            return false;
+        }
+        if self.g1_powers[0] != E::G1Affine::generator() || self.g2_powers[0] != E::G2Affine::generator() {
+            return false;
+        }
+        let mut transcript = Vec::new();
+        self.write(&mut transcript).expect("writing to a vector does not fail");
+        let r = E::ScalarField::from_be_bytes_mod_order(&Sha512::digest(&transcript));
+        let r_powers: Vec<_> = std::iter::successors(Some(E::ScalarField::one()), |power| Some(*power * r)).take(count - 1).collect();
+        let g1_shifted = E::G1::msm_unchecked(&self.g1_powers[1..], &r_powers);
+        let g1_combined = E::G1::msm_unchecked(&self.g1_powers[..count - 1], &r_powers);
+        let g2_shifted = E::G2::msm_unchecked(&self.g2_powers[1..], &r_powers);
+        let g2_combined = E::G2::msm_unchecked(&self.g2_powers[..count - 1], &r_powers);
+        let g1_check = E::multi_pairing([g1_shifted, -g1_combined], [self.g2_powers[0], self.g2_powers[1]]);
+        let g2_check = E::multi_pairing([self.g1_powers[0].into_group(), -self.g1_powers[1].into_group()], [g2_shifted, g2_combined]);
+        g1_check.is_zero() && g2_check.is_zero()
+    }
+
+    /// The number of coefficients a committed polynomial can have.
+    pub fn max_coefficients(&self) -> usize {
+        self.g1_powers.len()
+    }
+
+    /// [τ^i]₂, the verifier needs [1]₂ and [τ]₂ for single point openings.
+    pub fn g2_powers(&self) -> &[E::G2Affine] {
+        &self.g2_powers
+    }
+
+    /// The polynomial with p(i) = values[i].
+    pub fn vector_to_polynomial(values: &[E::ScalarField]) -> DensePolynomial<E::ScalarField> {
+        let points: Vec<_> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (E::ScalarField::from(i as u64), *value))
+            .collect();
+        interpolate(&points).expect("the positions are distinct")
+    }
+
+    pub fn commit_polynomial(&self, polynomial: &DensePolynomial<E::ScalarField>) -> E::G1Affine {
+        assert!(polynomial.coeffs.len() <= self.g1_powers.len(), "the polynomial has a too high degree for the setup");
+        E::G1::msm_unchecked(&self.g1_powers, &polynomial.coeffs).into_affine()
+    }
+
+    /// A proof that `polynomial` goes through every point, fails if it does not or if two points have the same x.
+    pub fn generate_proof(&self, polynomial: &DensePolynomial<E::ScalarField>, points: &[(E::ScalarField, E::ScalarField)]) -> Result<E::G1Affine, VerkleTreeError> {
+        if points.iter().any(|(x, y)| polynomial.evaluate(x) != *y) {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
+        let interpolation = interpolate(points).ok_or(VerkleTreeError::ProofGenerateError)?;
+        let xs: Vec<_> = points.iter().map(|(x, _)| *x).collect();
+        // p - I vanishes on the points, so the division has no remainder
+        let quotient = divide(&(polynomial - &interpolation), &vanishing_polynomial(&xs));
+        Ok(self.commit_polynomial(&quotient))
+    }
+
+    /// Verifies a single opening, `points` is not empty.
+    pub fn verify_proof(&self, commitment: &E::G1Affine, points: &[(E::ScalarField, E::ScalarField)], proof: &E::G1Affine) -> bool {
+        self.batch_verify(&[Opening { commitment, points, proof }])
+    }
+
+    /// Verifies all openings with one pairing product, false if any of them is not valid.
+    /// The setup has to be the one the commitments were made with, whoever knows its τ can forge the openings.
+    pub fn batch_verify(&self, openings: &[Opening<E>]) -> bool {
+        let r = Self::batch_challenge(openings);
+        let mut r_power = E::ScalarField::one();
+        // Σ rⁱ (C_i - [I_i(τ)]₁), with z_i·π_i added for single points, is paired with [1]₂
+        let mut generator_term = E::G1::zero();
+        // Σ rⁱ π_i of the single points is paired with [τ]₂
+        let mut tau_term = E::G1::zero();
+        let mut g1_terms = Vec::new();
+        let mut g2_terms = Vec::new();
+        for opening in openings {
+            let commitment = opening.commitment.into_group();
+            match opening.points {
+                [] => return false,
+                [(z, y)] => {
+                    generator_term += (commitment - E::G1Affine::generator() * *y + *opening.proof * *z) * r_power;
+                    tau_term += *opening.proof * r_power;
+                }
+                points => {
+                    let interpolation = match interpolate(points) {
+                        Some(interpolation) => interpolation,
+                        None => return false,
+                    };
+                    let xs: Vec<_> = points.iter().map(|(x, _)| *x).collect();
+                    let vanishing = vanishing_polynomial(&xs);
+                    if vanishing.coeffs.len() > self.g2_powers.len() {
+                        return false;
+                    }
+                    generator_term += (commitment - self.commit_polynomial(&interpolation)) * r_power;
+                    g1_terms.push(-(*opening.proof * r_power));
+                    g2_terms.push(E::G2::msm_unchecked(&self.g2_powers, &vanishing.coeffs));
+                }
+            }
+            r_power *= r;
+        }
+        g1_terms.extend([generator_term, -tau_term]);
+        g2_terms.extend([self.g2_powers[0].into_group(), self.g2_powers[1].into_group()]);
+        E::multi_pairing(g1_terms, g2_terms).is_zero()
+    }
+
+    // SHA-512 of the compressed commitments, points (with their count) and proofs reduced mod r
+    fn batch_challenge(openings: &[Opening<E>]) -> E::ScalarField {
+        let mut transcript = BATCH_CHALLENGE_DST.to_vec();
+        for opening in openings {
+            opening.commitment.serialize_compressed(&mut transcript).expect("serializing to a vector does not fail");
+            opening.points.serialize_compressed(&mut transcript).expect("serializing to a vector does not fail");
+            opening.proof.serialize_compressed(&mut transcript).expect("serializing to a vector does not fail");
+        }
+        E::ScalarField::from_be_bytes_mod_order(&Sha512::digest(&transcript))
+    }
+}
+
+// the points are pushed one by one, so a count larger than the input fails before it is allocated
+fn read_points<P: CanonicalDeserialize, R: Read>(reader: &mut R, count: usize) -> Result<Vec<P>, VerkleTreeError> {
+    let mut points = Vec::new();
+    for _ in 0..count {
+        points.push(P::deserialize_compressed(&mut *reader).map_err(|_| VerkleTreeError::ParamError)?);
+    }
+    Ok(points)
+}
+
+// Π (X - x_i)
+fn vanishing_polynomial<F: Field>(xs: &[F]) -> DensePolynomial<F> {
+    let mut coeffs = vec![F::one()];
+    for x in xs {
+        coeffs.insert(0, F::zero());
+        for i in 0..coeffs.len() - 1 {
+            let shifted = coeffs[i + 1] * x;
+            coeffs[i] -= shifted;
+        }
+    }
+    DensePolynomial::from_coefficients_vec(coeffs)
+}
+
+/*  The lagrange interpolation of the points, None if two points have the same x.
+    Every lagrange polynomial is Z / (X - x_i) scaled to 1 at x_i, so this is quadratic in the number of points.
+*/
+fn interpolate<F: Field>(points: &[(F, F)]) -> Option<DensePolynomial<F>> {
+    let xs: Vec<_> = points.iter().map(|(x, _)| *x).collect();
+    let vanishing = vanishing_polynomial(&xs);
+    let mut coeffs = vec![F::zero(); points.len()];
+    for (x, y) in points {
+        let basis = divide(&vanishing, &DensePolynomial::from_coefficients_vec(vec![-*x, F::one()]));
+        let scale = *y * basis.evaluate(x).inverse()?;
+        for (coeff, basis_coeff) in coeffs.iter_mut().zip(&basis.coeffs) {
+            *coeff += scale * basis_coeff;
+        }
+    }
+    Some(DensePolynomial::from_coefficients_vec(coeffs))
+}
+
+// The quotient of the division by a polynomial with a non-zero leading coefficient, the remainder is dropped.
+fn divide<F: Field>(dividend: &DensePolynomial<F>, divisor: &DensePolynomial<F>) -> DensePolynomial<F> {
+    if dividend.coeffs.len() < divisor.coeffs.len() {
+        return DensePolynomial::zero();
+    }
+    let mut remainder = dividend.coeffs.clone();
+    let divisor_len = divisor.coeffs.len();
+    let leading_inverse = divisor.coeffs[divisor_len - 1].inverse().expect("the leading coefficient is not zero");
+    let mut quotient = vec![F::zero(); remainder.len() - divisor_len + 1];
+    for i in (0..quotient.len()).rev() {
+        quotient[i] = remainder[i + divisor_len - 1] * leading_inverse;
+        for (j, divisor_coeff) in divisor.coeffs.iter().enumerate() {
+            remainder[i + j] -= quotient[i] * divisor_coeff;
+        }
+    }
+    DensePolynomial::from_coefficients_vec(quotient)
+}
@@ -1,9 +1,39 @@
-pub use verkle_tree::{VerkleTree, VerkleProof, ProofNode};
+pub use verkle_tree::{VerkleTree, VerkleProof, ProofNode, BatchProof, RangeProof};
 mod verkle_tree;
+pub use kzg::{Kzg, Opening};
+mod kzg;
 mod verkle_tree_test;
 
-pub use verkle_tree_point::{VerkleTree as VerkleTree_point, VerkleProof as VerkleProof_point, ProofNode as ProofNode_point};
+pub use verkle_tree_point::{VerkleTree as VerkleTree_point, VerkleProof as VerkleProof_point, ProofNode as ProofNode_point, BatchProof as BatchProof_point, RangeProof as RangeProof_point};
 mod verkle_tree_point;
+pub use tree_params::{TreeParams, DEFAULT_SEED};
+mod tree_params;
+pub use encoding::{PointEncoding, COMMIT_LEN, PROOF_LEN};
+mod encoding;
+pub use ark_pointproofs::{Commitment, PointproofsCurve, Precomputation};
+pub mod ark_pointproofs;
+pub mod ciphersuite;
+pub mod evm;
+
+pub use node_index::{NodeIndex, level_sizes};
+mod node_index;
+mod arena;
+
+pub use stats::{ProofSize, TreeStats};
+mod stats;
+pub use witness::{Witness, WitnessRecorder};
+mod witness;
+pub use partial_tree::PartialTree;
+mod partial_tree;
+pub use partial_tree_point::PartialTree as PartialTree_point;
+mod partial_tree_point;
+pub use diff::{diff, diff_with_proof, verify_diff, DiffProof, LeafChange};
+mod diff;
+pub use sync::{LoopbackTransport, SyncClient, SyncError, SyncRequest, SyncResponse, SyncServer, SyncTarget, SyncTransport};
+mod sync;
+pub use tree_builder::{NodeStore, TreeBuilder};
+mod tree_builder;
+pub use seed::{seed_from_env, SEED_VAR};
+mod seed;
+mod c_api;
 
-pub use pointproofs::pairings::Commitment as Commitment;
-pub use pointproofs::pairings::pointproofs_groups::COMMIT_LEN as COMMIT_LEN;
@@ -4,14 +4,14 @@ use std::fs::OpenOptions;
 use std::io::Write;
 
 use ark_bls12_381::Fr as F;
+use ark_serialize::CanonicalSerialize;
 use rand::Rng;
 use rand::prelude::*;
+use rand::seq::index::sample;
 use verkle_tree::*;
-mod verkle_tree_point;
-use verkle_tree_point::{VerkleTree as VerkleTree_point};
 
 
-fn test_batch_proof_verify(datas: Vec<F>, filename : String) {
+fn test_batch_proof_verify(datas: Vec<F>, filename : String, rng: &mut StdRng) {
 
 
     let mut file = OpenOptions::new()
@@ -33,12 +33,12 @@ fn test_batch_proof_verify(datas: Vec<F>, filename : String) {
     //println!("depth = {}", depth);
     // //println!("datas len {}", datas.len());
     let indices: Vec<usize> = (0..=(datas.len()-1) )
-        .choose_multiple(&mut thread_rng(),(datas.len() as f64 *(0.2))as usize);
+        .choose_multiple(rng,(datas.len() as f64 *(0.2))as usize);
         //.choose_multiple(&mut thread_rng(),2_usize);
     //println!("indices = {:?}", indices);
 
     let startproof = Instant::now();
-    let proof = tree.generate_batch_proof(indices.clone(), &datas);
+    let proof = tree.generate_batch_proof(indices.clone(), &datas).unwrap();
     let endproof= startproof.elapsed();
     println!("total proof time {:?}", endproof);
 
@@ -51,7 +51,7 @@ fn test_batch_proof_verify(datas: Vec<F>, filename : String) {
 
 
     let startverify = Instant::now();
-    let b = VerkleTree::batch_proof_verify(root, proof.clone(), width, indices, depth, datas_verify);
+    let b = VerkleTree::batch_proof_verify(root, &proof, width, &indices, depth, &datas_verify, tree.kzg());
     let endverify= startverify.elapsed();
 
     writeln!(file, "{:<5} {:<15.1?} {:<15.1?} {:<15.1?} {:<15.1?}", width, endtree, endproof, endverify, endtree + endproof+endverify).expect("Failed to write values");
@@ -62,7 +62,7 @@ fn test_batch_proof_verify(datas: Vec<F>, filename : String) {
 
 
 
-fn test_batch_point_proof_verify(datas: Vec<Vec<u8>>, filename : String) {
+fn test_batch_point_proof_verify(datas: Vec<Vec<u8>>, filename : String, rng: &mut StdRng) {
 
 
     let mut file = OpenOptions::new()
@@ -84,12 +84,12 @@ fn test_batch_point_proof_verify(datas: Vec<Vec<u8>>, filename : String) {
     //println!("depth = {}", depth);
     // //println!("datas len {}", datas.len());
     let indices: Vec<usize> = (0..=(datas.len()-1) )
-        .choose_multiple(&mut thread_rng(),(datas.len() as f64 *(0.2))as usize);
+        .choose_multiple(rng,(datas.len() as f64 *(0.2))as usize);
         //.choose_multiple(&mut thread_rng(),1_usize);
     //println!("indices = {:?}", indices);
 
     let startproof = Instant::now();
-    let proof = tree.generate_batch_proof(indices.clone(), &datas);
+    let proof = tree.generate_batch_proof(indices.clone(), &datas).unwrap();
     let endproof= startproof.elapsed();
     println!("total proof time {:?}", endproof);
 
@@ -102,7 +102,7 @@ fn test_batch_point_proof_verify(datas: Vec<Vec<u8>>, filename : String) {
 
 
     let startverify = Instant::now();
-    let b = VerkleTree_point::batch_proof_verify(root, proof.clone(), width, indices, depth, datas_verify);
+    let b = VerkleTree_point::batch_proof_verify(root, &proof, width, &indices, depth, &datas_verify, tree.params());
     let endverify= startverify.elapsed();
 
     writeln!(file, "{:<5} {:<15.1?} {:<15.1?} {:<15.1?} {:<15.1?}", width, endtree, endproof, endverify, endtree + endproof+endverify).expect("Failed to write values");
@@ -112,21 +112,76 @@ fn test_batch_point_proof_verify(datas: Vec<Vec<u8>>, filename : String) {
 }
 
 
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn to_hex<T: CanonicalSerialize>(value: &T) -> String {
+    let mut bytes = Vec::new();
+    value.serialize_compressed(&mut bytes).expect("failed to serialize");
+    hex(&bytes)
+}
+
+/*  Prints the roots and proofs of trees built from the data of `seed`, every value is a hex string
+    of its compressed serialization. The output only depends on the seed, so it can be compared
+    between versions and platforms.
+*/
+fn print_test_vectors(seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let width = 4;
+    let datas: Vec<F> = (0..64).map(|_| F::from(rng.gen::<u64>())).collect();
+    let tree = VerkleTree::new(&datas, width).unwrap();
+    println!("seed {}", seed);
+    println!("width {} leaves {}", width, datas.len());
+    println!("root {}", to_hex(&tree.root_commitment().unwrap()));
+
+    let index = rng.gen_range(0..datas.len());
+    let (value, proof) = tree.prove(index).unwrap();
+    println!("proof index {} value {}", index, to_hex(&value));
+    for node in &proof.proofs {
+        println!("  commitment {} proof {}", to_hex(&node.commitment), to_hex(&node.proof));
+    }
+
+    let indices = sample(&mut rng, datas.len(), 4).into_vec();
+    let (_, batch_proof) = tree.prove_batch(&indices).unwrap();
+    println!("batch proof indices {:?}", indices);
+    for (node_index, node) in &batch_proof.nodes {
+        println!("  level {} offset {} proof {}", node_index.level, node_index.offset, to_hex(&node.proof));
+    }
+
+    let datas_point: Vec<Vec<u8>> = datas.iter().map(to_hex).map(String::into_bytes).collect();
+    let tree_point = VerkleTree_point::new(&datas_point, width).unwrap();
+    println!("point root {}", hex(&VerkleTree_point::map_commitment_to_vec_u8(&tree_point.root_commitment().unwrap())));
+}
+
+/*  `cargo run -- test-vectors [seed]` prints the test vectors of a seed (default 0),
+    without arguments the batch proofs of both trees are timed with the data of `VERKLE_SEED`.
+*/
 fn main (){
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("test-vectors") {
+        let seed = args.get(2).map_or(0, |seed| seed.parse().expect("the seed has to be a u64"));
+        print_test_vectors(seed);
+        return;
+    }
+
     println!("Hello world");
+    let seed = seed_from_env();
+    println!("{}={}", SEED_VAR, seed);
+    let mut rng = StdRng::seed_from_u64(seed);
     let mut datas: Vec<F> = Vec::new();
     let mut datas_point: Vec<Vec<u8>> =Vec::new();
 
     for _i in 0.. 4096{
-        let v = rand::thread_rng().gen_range(0..=4096*4096) as u8;
+        let v = rng.gen_range(0..=4096*4096) as u8;
         datas.push(F::from(v));
         datas_point.push(vec![v]);
     }
     println!("data length {}", datas.len());
-    test_batch_proof_verify(datas.clone(), "test_compare".to_string());
+    test_batch_proof_verify(datas.clone(), "test_compare".to_string(), &mut rng);
 
     println!("now point proofs");
-    test_batch_point_proof_verify(datas_point, "test_compare_point".to_string());
+    test_batch_point_proof_verify(datas_point, "test_compare_point".to_string(), &mut rng);
 
 
 }
\ No newline at end of file
@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// The position of a node in a tree.
+/// Level 0 is the root and the leaf nodes are at level `depth`, the offset counts the
+/// nodes of a level from the left. The children of the node (level, offset) are the
+/// nodes (level + 1, offset * width + position) for every position that exists,
+/// so the last node of a level may have less than `width` children.
+/// The ordering is level by level, from left to right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeIndex {
+    pub level: usize,
+    pub offset: usize,
+}
+
+impl NodeIndex {
+    pub fn root() -> Self {
+        NodeIndex { level: 0, offset: 0 }
+    }
+
+    /// The leaf node holding the value at `index` and the position of the value in it.
+    pub fn of_leaf(index: usize, width: usize, depth: usize) -> (Self, usize) {
+        (
+            NodeIndex {
+                level: depth,
+                offset: index / width,
+            },
+            index % width,
+        )
+    }
+
+    pub fn child(&self, width: usize, position: usize) -> Self {
+        NodeIndex {
+            level: self.level + 1,
+            offset: self.offset * width + position,
+        }
+    }
+
+    pub fn parent(&self, width: usize) -> Option<Self> {
+        if self.level == 0 {
+            return None;
+        }
+        Some(NodeIndex {
+            level: self.level - 1,
+            offset: self.offset / width,
+        })
+    }
+
+    /// The position of this node among the children of its parent.
+    pub fn position(&self, width: usize) -> usize {
+        self.offset % width
+    }
+
+    /// The positions of the children to follow from the root to reach this node.
+    pub fn path(&self, width: usize) -> Vec<usize> {
+        let mut path = Vec::with_capacity(self.level);
+        let mut offset = self.offset;
+        for _ in 0..self.level {
+            path.push(offset % width);
+            offset /= width;
+        }
+        path.reverse();
+        path
+    }
+
+    /// The indices of the values stored in the subtree below this node.
+    pub fn leaf_range(&self, width: usize, depth: usize, leaf_count: usize) -> Range<usize> {
+        let span = width.pow((depth - self.level + 1) as u32);
+        let start = self.offset * span;
+        start.min(leaf_count)..(start + span).min(leaf_count)
+    }
+
+    /// The number of children of this node, or of values for a leaf node.
+    pub fn child_count(&self, width: usize, depth: usize, leaf_count: usize) -> usize {
+        let child_span = width.pow((depth - self.level) as u32);
+        self.leaf_range(width, depth, leaf_count).len().div_ceil(child_span)
+    }
+}
+
+/// The number of nodes on every level of a tree holding `leaf_count` values, starting at the root.
+pub fn level_sizes(leaf_count: usize, width: usize) -> Vec<usize> {
+    let mut sizes = vec![leaf_count.div_ceil(width).max(1)];
+    while sizes[sizes.len() - 1] > 1 {
+        let above = sizes[sizes.len() - 1].div_ceil(width);
+        sizes.push(above);
+    }
+    sizes.reverse();
+    sizes
+}
+
+/// For every node that has to be opened to prove the values at `indices`,
+/// the sorted positions of the children (or values, for leaf nodes) to open.
+/// The map iterates the nodes from the root down, level by level from left to right.
+pub(crate) fn create_index_for_proof(indices: &[usize], width: usize, depth: usize) -> BTreeMap<NodeIndex, Vec<usize>> {
+    let mut nodes: BTreeMap<NodeIndex, Vec<usize>> = BTreeMap::new();
+    for &index in indices {
+        let (mut node, mut position) = NodeIndex::of_leaf(index, width, depth);
+        loop {
+            let positions = nodes.entry(node).or_default();
+            if positions.contains(&position) {
+                // the path above this node was already added by an earlier index
+                break;
+            }
+            positions.push(position);
+            match node.parent(width) {
+                Some(parent) => {
+                    position = node.position(width);
+                    node = parent;
+                }
+                None => break,
+            }
+        }
+    }
+    for positions in nodes.values_mut() {
+        positions.sort_unstable();
+    }
+    nodes
+}
+
+/// The nodes that have to be opened to prove all values in `range`, with the positions to open.
+/// Only nodes that are partly covered by the range are opened, a node whose subtree lies
+/// completely in the range is not in the map and has to be recomputed from the values.
+/// Partly covered nodes always contain the first or the last index of the range,
+/// so they are found on the paths to these two indices.
+/// An empty map means the range covers the whole tree. `range` must not be empty.
+pub(crate) fn create_index_for_range(range: &Range<usize>, width: usize, depth: usize, leaf_count: usize) -> BTreeMap<NodeIndex, Vec<usize>> {
+    let mut nodes = create_index_for_proof(&[range.start, range.end - 1], width, depth);
+    nodes.retain(|node, positions| {
+        let covered = node.leaf_range(width, depth, leaf_count);
+        if range.start <= covered.start && covered.end <= range.end {
+            return false;
+        }
+        // every child spans child_span values, open the children that overlap with the range
+        let child_span = width.pow((depth - node.level) as u32);
+        let first = (range.start.max(covered.start) - covered.start) / child_span;
+        let last = (range.end.min(covered.end) - 1 - covered.start) / child_span;
+        *positions = (first..=last).collect();
+        true
+    });
+    nodes
+}
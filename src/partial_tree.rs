@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use ark_bls12_381::{Fr as F, G1Affine};
+use crate::kzg::Kzg;
+
+use crate::node_index::{create_index_for_proof, level_sizes, NodeIndex};
+use crate::verkle_tree::{BatchProof, VerkleTree, VerkleTreeError};
+
+/// The part of a tree that is opened by a batch proof.
+/// Only the opened nodes are known, with their commitment and the opened values,
+/// which is enough to update the proven values and compute the new root.
+pub struct PartialTree {
+    nodes: BTreeMap<NodeIndex, PartialNode>,
+    width: usize,
+    leaf_count: usize,
+    kzg: Arc<Kzg>,
+}
+
+#[derive(Debug, Clone)]
+struct PartialNode {
+    commitment: G1Affine,
+    // the opened positions and their values
+    values: BTreeMap<usize, F>,
+}
+
+impl PartialTree {
+    /// Verifies the batch proof for the values `data` at `indices` of a tree with `leaf_count` values
+    /// and keeps the opened nodes. Returns None if the proof is not valid.
+    /// `kzg` is the setup of the tree, the proof is verified and the commitments are updated with it.
+    pub fn new(root: G1Affine, proof: &BatchProof, width: usize, leaf_count: usize, indices: &[usize], data: &[F], kzg: Arc<Kzg>) -> Option<Self> {
+        let depth = level_sizes(leaf_count, width).len() - 1;
+        if !VerkleTree::batch_proof_verify(root, proof, width, indices, depth, data, &kzg) {
+            return None;
+        }
+        // the proof opens exactly these nodes and positions
+        let nodes = create_index_for_proof(indices, width, depth)
+            .into_iter()
+            .map(|(node_index, positions)| {
+                let node = &proof.nodes[&node_index];
+                let values = positions.into_iter().zip(&node.point).map(|(position, point)| (position, point.1)).collect();
+                (node_index, PartialNode { commitment: node.commitment, values })
+            })
+            .collect();
+        Some(PartialTree {
+            nodes,
+            width,
+            leaf_count,
+            kzg,
+        })
+    }
+
+    pub fn depth(&self) -> usize {
+        level_sizes(self.leaf_count, self.width).len() - 1
+    }
+
+    /// The value at `index`, if it was proven.
+    pub fn get(&self, index: usize) -> Option<F> {
+        let (node_index, position) = NodeIndex::of_leaf(index, self.width, self.depth());
+        self.nodes.get(&node_index)?.values.get(&position).copied()
+    }
+
+    pub fn update(&mut self, index: usize, value: F) -> Result<(), VerkleTreeError> {
+        self.update_batch(&[(index, value)])
+    }
+
+    /*  Sets the values of the proven indices in `writes`, nothing is changed if an index was not proven.
+        Every changed node adds the difference of its new and old values to its commitment,
+        which changes the value opened in its parent, up to the root.
+    */
+    pub fn update_batch(&mut self, writes: &[(usize, F)]) -> Result<(), VerkleTreeError> {
+        let width = self.width;
+        let depth = self.depth();
+        let mut changes: BTreeMap<NodeIndex, BTreeMap<usize, F>> = BTreeMap::new();
+        for &(index, value) in writes {
+            if self.get(index).is_none() {
+                return Err(VerkleTreeError::UpdateError);
+            }
+            let (node_index, position) = NodeIndex::of_leaf(index, width, depth);
+            changes.entry(node_index).or_default().insert(position, value);
+        }
+
+        // deeper levels come last in the map, so every node is updated after its children
+        while let Some((node_index, changed)) = changes.pop_last() {
+            let node = self.nodes.get_mut(&node_index).ok_or(VerkleTreeError::UpdateError)?;
+            let mut deltas = Vec::with_capacity(changed.len());
+            for (position, value) in changed {
+                let old = node.values.insert(position, value).ok_or(VerkleTreeError::UpdateError)?;
+                deltas.push((position, value - old));
+            }
+            let domain_size = node_index.child_count(width, depth, self.leaf_count);
+            node.commitment = VerkleTree::update_commitment(&self.kzg, node.commitment, domain_size, &deltas);
+            if let Some(parent) = node_index.parent(width) {
+                let value = <VerkleTree>::map_commitment_to_field(&node.commitment);
+                changes.entry(parent).or_default().insert(node_index.position(width), value);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn root_commitment(&self) -> Option<G1Affine> {
+        self.nodes.get(&NodeIndex::root()).map(|node| node.commitment)
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use std::sync::Arc;
+
+use ark_bls12_381::Bls12_381;
+
+use crate::ark_pointproofs::{Commitment, PointproofsCurve};
+use crate::node_index::{create_index_for_proof, level_sizes, NodeIndex};
+use crate::tree_params::TreeParams;
+use crate::verkle_tree_point::{BatchProof, VerkleTree, VerkleTreeError};
+
+/// The part of a tree that is opened by a batch proof.
+/// Only the opened nodes are known, with their commitment and the opened values,
+/// which is enough to update the proven values and compute the new root.
+pub struct PartialTree<E: PointproofsCurve = Bls12_381> {
+    nodes: BTreeMap<NodeIndex, PartialNode<E>>,
+    width: usize,
+    leaf_count: usize,
+    params: Arc<TreeParams<E>>,
+}
+
+#[derive(Debug, Clone)]
+struct PartialNode<E: PointproofsCurve> {
+    commitment: Commitment<E>,
+    // the opened positions and their values
+    values: BTreeMap<usize, Vec<u8>>,
+}
+
+impl<E: PointproofsCurve> PartialTree<E> {
+    /// Verifies the batch proof for the values `data` at `indices` of a tree with `leaf_count` values
+    /// and keeps the opened nodes. Returns None if the proof is not valid. `params` are the parameters
+    /// the tree was built with, they are also used to update the commitments.
+    pub fn new(root: Commitment<E>, proof: &BatchProof<E>, width: usize, leaf_count: usize, indices: &[usize], data: &[Vec<u8>], params: Arc<TreeParams<E>>) -> Option<Self> {
+        let depth = level_sizes(leaf_count, width).len() - 1;
+        if !VerkleTree::batch_proof_verify(root, proof, width, indices, depth, data, &params) {
+            return None;
+        }
+        let nodes = create_index_for_proof(indices, width, depth)
+            .into_keys()
+            .map(|node_index| {
+                let node = &proof.nodes[&node_index];
+                let values = node.indices.iter().copied().zip(node.values.iter().cloned()).collect();
+                (node_index, PartialNode { commitment: node.commitment.clone(), values })
+            })
+            .collect();
+        Some(PartialTree {
+            nodes,
+            width,
+            leaf_count,
+            params,
+        })
+    }
+
+    pub fn depth(&self) -> usize {
+        level_sizes(self.leaf_count, self.width).len() - 1
+    }
+
+    /// The value at `index`, if it was proven.
+    pub fn get(&self, index: usize) -> Option<&Vec<u8>> {
+        let (node_index, position) = NodeIndex::of_leaf(index, self.width, self.depth());
+        self.nodes.get(&node_index)?.values.get(&position)
+    }
+
+    pub fn update(&mut self, index: usize, value: Vec<u8>) -> Result<(), VerkleTreeError> {
+        self.update_batch(&[(index, value)])
+    }
+
+    /*  Sets the values of the proven indices in `writes`, nothing is changed if an index was not proven.
+        Every changed value is updated in the commitment of its node with Commitment::update,
+        which changes the value opened in its parent, up to the root.
+    */
+    pub fn update_batch(&mut self, writes: &[(usize, Vec<u8>)]) -> Result<(), VerkleTreeError> {
+        let width = self.width;
+        let depth = self.depth();
+        let mut changes: BTreeMap<NodeIndex, BTreeMap<usize, Vec<u8>>> = BTreeMap::new();
+        for (index, value) in writes {
+            if self.get(*index).is_none() {
+                return Err(VerkleTreeError::UpdateError);
+            }
+            let (node_index, position) = NodeIndex::of_leaf(*index, width, depth);
+            changes.entry(node_index).or_default().insert(position, value.clone());
+        }
+
+        // deeper levels come last in the map, so every node is updated after its children
+        while let Some((node_index, changed)) = changes.pop_last() {
+            let node = self.nodes.get_mut(&node_index).ok_or(VerkleTreeError::UpdateError)?;
+            for (position, value) in changed {
+                let old = node.values.get(&position).ok_or(VerkleTreeError::UpdateError)?;
+                node.commitment
+                    .update(self.params.prover(), position, old, &value)
+                    .map_err(|_| VerkleTreeError::UpdateError)?;
+                node.values.insert(position, value);
+            }
+            if let Some(parent) = node_index.parent(width) {
+                let value = VerkleTree::map_commitment_to_vec_u8(&node.commitment);
+                changes.entry(parent).or_default().insert(node_index.position(width), value);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn root_commitment(&self) -> Option<Commitment<E>> {
+        self.nodes.get(&NodeIndex::root()).map(|node| node.commitment.clone())
+    }
+}
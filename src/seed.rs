@@ -0,0 +1,13 @@
+use rand::Rng;
+
+/// The environment variable that fixes the seed of the random data in tests and tools.
+pub const SEED_VAR: &str = "VERKLE_SEED";
+
+/// The seed set in `VERKLE_SEED`, or a new one if it is not set.
+/// The caller reports the seed, so a failing run can be repeated by setting it.
+pub fn seed_from_env() -> u64 {
+    match std::env::var(SEED_VAR) {
+        Ok(seed) => seed.parse().expect("VERKLE_SEED has to be a u64"),
+        Err(_) => rand::thread_rng().gen(),
+    }
+}
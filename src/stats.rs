@@ -0,0 +1,68 @@
+use std::ops::{Add, AddAssign};
+
+/// Number of bytes taken by the parts of a proof.
+/// A proof is reported per level (root first), see `VerkleProof::size_breakdown`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProofSize {
+    /// the commitments of the opened nodes
+    pub commitments: usize,
+    /// the opening proofs, one per opened node
+    pub opening_proofs: usize,
+    /// the opened values, i.e. leaf values or the child commitments mapped to values
+    pub values: usize,
+    /// the positions of the opened values and, for variable length values, their lengths
+    pub index_metadata: usize,
+}
+
+impl ProofSize {
+    pub fn total(&self) -> usize {
+        self.commitments + self.opening_proofs + self.values + self.index_metadata
+    }
+}
+
+impl Add for ProofSize {
+    type Output = ProofSize;
+
+    fn add(self, other: ProofSize) -> ProofSize {
+        ProofSize {
+            commitments: self.commitments + other.commitments,
+            opening_proofs: self.opening_proofs + other.opening_proofs,
+            values: self.values + other.values,
+            index_metadata: self.index_metadata + other.index_metadata,
+        }
+    }
+}
+
+impl AddAssign for ProofSize {
+    fn add_assign(&mut self, other: ProofSize) {
+        *self = *self + other;
+    }
+}
+
+/// Shape and memory usage of a built tree, see `VerkleTree::stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeStats {
+    pub width: usize,
+    /// number of levels below the root, 0 if the root holds the values
+    pub depth: usize,
+    pub leaf_count: usize,
+    pub node_count: usize,
+    /// leaf_count divided by the number of leaves a full tree of this depth holds
+    pub fill_ratio: f64,
+    /// estimated number of bytes used by the nodes, including their heap allocations
+    pub memory_usage: usize,
+}
+
+impl TreeStats {
+    pub(crate) fn new(width: usize, depth: usize, leaf_count: usize, node_count: usize, memory_usage: usize) -> Self {
+        let capacity = (width as f64).powi(depth as i32 + 1);
+        TreeStats {
+            width,
+            depth,
+            leaf_count,
+            node_count,
+            fill_ratio: leaf_count as f64 / capacity,
+            memory_usage,
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use ark_bls12_381::{Fr as F, G1Affine};
+
+use crate::kzg::Kzg;
+use crate::verkle_tree::{RangeProof, VerkleTree};
+
+/// The tree a client wants to fetch. The root has to come from a trusted source, e.g. a block header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncTarget {
+    pub root: G1Affine,
+    pub width: usize,
+    pub leaf_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncRequest {
+    /// The values in `range` of the tree with root `root`, with a range proof.
+    GetRange { root: G1Affine, range: Range<usize> },
+}
+
+#[derive(Debug, Clone)]
+pub enum SyncResponse {
+    Range { range: Range<usize>, proof: RangeProof },
+    /// The server does not have the requested root or range.
+    NotFound,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncError {
+    TransportError(String),
+    NotFound,
+    UnexpectedResponse,
+    InvalidProof,
+    Incomplete,
+}
+
+/// Sends a request to a server and waits for its response.
+pub trait SyncTransport {
+    fn request(&mut self, request: SyncRequest) -> Result<SyncResponse, SyncError>;
+}
+
+/// Answers requests with range proofs from a tree.
+pub struct SyncServer<'a> {
+    tree: &'a VerkleTree,
+}
+
+impl<'a> SyncServer<'a> {
+    pub fn new(tree: &'a VerkleTree) -> Self {
+        SyncServer { tree }
+    }
+
+    pub fn handle(&self, request: SyncRequest) -> SyncResponse {
+        match request {
+            SyncRequest::GetRange { root, range } => {
+                if self.tree.root_commitment() != Some(root) {
+                    return SyncResponse::NotFound;
+                }
+                match self.tree.prove_range(range.clone()) {
+                    Ok(proof) => SyncResponse::Range { range, proof },
+                    Err(_) => SyncResponse::NotFound,
+                }
+            }
+        }
+    }
+}
+
+/// Passes the requests directly to a server in the same process.
+pub struct LoopbackTransport<'a> {
+    server: &'a SyncServer<'a>,
+}
+
+impl<'a> LoopbackTransport<'a> {
+    pub fn new(server: &'a SyncServer<'a>) -> Self {
+        LoopbackTransport { server }
+    }
+}
+
+impl SyncTransport for LoopbackTransport<'_> {
+    fn request(&mut self, request: SyncRequest) -> Result<SyncResponse, SyncError> {
+        Ok(self.server.handle(request))
+    }
+}
+
+/// Fetches a tree range by range, from the first index to the last.
+/// Every range is checked against the target root before its values are kept,
+/// so the values received so far can be stored and passed to `resume` after an interruption.
+pub struct SyncClient {
+    target: SyncTarget,
+    range_size: usize,
+    values: Vec<F>,
+    // the setup of the tree, the range proofs are verified with it
+    kzg: Arc<Kzg>,
+}
+
+impl SyncClient {
+    /// `range_size` is the number of values requested at once, `kzg` is the setup of the target tree.
+    pub fn new(target: SyncTarget, range_size: usize, kzg: Arc<Kzg>) -> Self {
+        Self::resume(target, range_size, Vec::new(), kzg)
+    }
+
+    /// Continues a sync with the verified values of an earlier client.
+    pub fn resume(target: SyncTarget, range_size: usize, values: Vec<F>, kzg: Arc<Kzg>) -> Self {
+        SyncClient {
+            target,
+            range_size: range_size.max(1),
+            values,
+            kzg,
+        }
+    }
+
+    /// The verified values, starting at index 0.
+    pub fn values(&self) -> &[F] {
+        &self.values
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.values.len() >= self.target.leaf_count
+    }
+
+    /// The next request to send, None once the sync is complete.
+    pub fn next_request(&self) -> Option<SyncRequest> {
+        if self.is_complete() {
+            return None;
+        }
+        let start = self.values.len();
+        let end = (start + self.range_size).min(self.target.leaf_count);
+        Some(SyncRequest::GetRange { root: self.target.root, range: start..end })
+    }
+
+    /// Checks the response to `next_request` and keeps its values.
+    pub fn handle_response(&mut self, response: SyncResponse) -> Result<(), SyncError> {
+        let expected = match self.next_request() {
+            Some(SyncRequest::GetRange { range, .. }) => range,
+            None => return Err(SyncError::UnexpectedResponse),
+        };
+        match response {
+            SyncResponse::Range { range, proof } => {
+                if range != expected {
+                    return Err(SyncError::UnexpectedResponse);
+                }
+                let target = self.target;
+                let values = VerkleTree::verify_range_proof(target.root, &proof, target.width, target.leaf_count, range, &self.kzg)
+                    .ok_or(SyncError::InvalidProof)?;
+                self.values.extend(values);
+                Ok(())
+            }
+            SyncResponse::NotFound => Err(SyncError::NotFound),
+        }
+    }
+
+    /// Sends requests until the sync is complete. After an error the client can be run again.
+    pub fn run<T: SyncTransport>(&mut self, transport: &mut T) -> Result<(), SyncError> {
+        while let Some(request) = self.next_request() {
+            let response = transport.request(request)?;
+            self.handle_response(response)?;
+        }
+        Ok(())
+    }
+
+    /// Builds the tree from the fetched values, returns the tree and its values.
+    pub fn finish(self) -> Result<(VerkleTree, Vec<F>), SyncError> {
+        if !self.is_complete() {
+            return Err(SyncError::Incomplete);
+        }
+        let tree = VerkleTree::with_kzg(self.kzg, &self.values, self.target.width).map_err(|_| SyncError::Incomplete)?;
+        if tree.root_commitment() != Some(self.target.root) {
+            return Err(SyncError::InvalidProof);
+        }
+        Ok((tree, self.values))
+    }
+}
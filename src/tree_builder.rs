@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use ark_bls12_381::{Fr as F, G1Affine};
+use crate::kzg::Kzg;
+
+use crate::verkle_tree::{VerkleTree, VerkleTreeError};
+
+// number of nodes a level commits at once, they are committed in parallel
+const BATCH_SIZE: usize = 256;
+
+/// Receives the commitments and the values of a tree while it is built.
+pub trait NodeStore {
+    /// Called once for every node, the nodes of a height are written from left to right.
+    /// `height` is the number of levels below the node, 0 for the leaf nodes, since the depth
+    /// of the tree is only known at the end.
+    fn write_node(&mut self, height: usize, offset: usize, commitment: &G1Affine);
+
+    /// Called once for every value when it is pushed, the values are not stored by default.
+    fn write_value(&mut self, _index: usize, _value: &F) {}
+}
+
+/// Does not store the nodes.
+impl NodeStore for () {
+    fn write_node(&mut self, _height: usize, _offset: usize, _commitment: &G1Affine) {}
+}
+
+/// Keeps the commitments of every height, starting at the leaf nodes.
+impl NodeStore for Vec<Vec<G1Affine>> {
+    fn write_node(&mut self, height: usize, offset: usize, commitment: &G1Affine) {
+        if self.len() == height {
+            self.push(Vec::new());
+        }
+        debug_assert_eq!(self[height].len(), offset);
+        self[height].push(*commitment);
+    }
+}
+
+impl<S: NodeStore + ?Sized> NodeStore for &mut S {
+    fn write_node(&mut self, height: usize, offset: usize, commitment: &G1Affine) {
+        (**self).write_node(height, offset, commitment)
+    }
+
+    fn write_value(&mut self, index: usize, value: &F) {
+        (**self).write_value(index, value)
+    }
+}
+
+/*  Builds the root of a KZG tree from a stream of values without holding them all in memory.
+    Every level keeps a frontier of values that have no node yet, the values of the leaf nodes or the
+    mapped commitments of the nodes below. Once a level holds enough values its nodes are committed and
+    passed up, so only a few nodes per level are kept and the root is the same as for `VerkleTree::new`.
+*/
+pub struct TreeBuilder<S: NodeStore = ()> {
+    kzg: Arc<Kzg>,
+    width: usize,
+    leaf_count: usize,
+    levels: Vec<Level>,
+    store: S,
+}
+
+#[derive(Default)]
+struct Level {
+    pending: Vec<F>,
+    committed: usize,
+    last: Option<G1Affine>,
+}
+
+impl TreeBuilder {
+    pub fn new(width: usize) -> Self {
+        Self::with_store(width, ())
+    }
+}
+
+impl<S: NodeStore> TreeBuilder<S> {
+    /// A builder that writes every node to `store` once it is committed.
+    pub fn with_store(width: usize, store: S) -> Self {
+        Self::with_kzg(Arc::new(Kzg::insecure_test_setup(width)), width, store)
+    }
+
+    /// A builder that commits with `kzg`, the other constructors use `Kzg::insecure_test_setup`.
+    pub fn with_kzg(kzg: Arc<Kzg>, width: usize, store: S) -> Self {
+        assert!(width >= 2, "the width has to be at least 2");
+        TreeBuilder {
+            kzg,
+            width,
+            leaf_count: 0,
+            levels: vec![Level::default()],
+            store,
+        }
+    }
+
+    /// The number of values pushed so far.
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    pub fn push(&mut self, value: F) {
+        self.store.write_value(self.leaf_count, &value);
+        self.leaf_count += 1;
+        self.push_to_level(0, value);
+    }
+
+    /// Commits the nodes that are not full yet and returns the root commitment.
+    pub fn finish(mut self) -> Result<G1Affine, VerkleTreeError> {
+        if self.leaf_count == 0 {
+            return Err(VerkleTreeError::BuildError);
+        }
+        let mut height = 0;
+        loop {
+            if !self.levels[height].pending.is_empty() {
+                self.commit_pending(height);
+            }
+            // a level with a single node is the root, the level above only holds its pending value
+            if self.levels[height].committed == 1 {
+                return self.levels[height].last.ok_or(VerkleTreeError::BuildError);
+            }
+            height += 1;
+        }
+    }
+
+    fn push_to_level(&mut self, height: usize, value: F) {
+        if self.levels.len() == height {
+            self.levels.push(Level::default());
+        }
+        self.levels[height].pending.push(value);
+        // a multiple of the width, so the nodes are cut at the same positions as in `VerkleTree::new`
+        if self.levels[height].pending.len() == self.width * BATCH_SIZE {
+            self.commit_pending(height);
+        }
+    }
+
+    fn commit_pending(&mut self, height: usize) {
+        let pending = std::mem::take(&mut self.levels[height].pending);
+        for commitment in VerkleTree::commit_chunks(&self.kzg, &pending, self.width) {
+            let level = &mut self.levels[height];
+            self.store.write_node(height, level.committed, &commitment);
+            level.committed += 1;
+            level.last = Some(commitment);
+            self.push_to_level(height + 1, <VerkleTree>::map_commitment_to_field(&commitment));
+        }
+    }
+}
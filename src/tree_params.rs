@@ -0,0 +1,131 @@
+use std::io::{Read, Write};
+
+use ark_bls12_381::{Bls12_381, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+
+use crate::ark_pointproofs::{self, is_consistent, params_from_generators, read_points, Precomputation, PointproofsCurve, ProverParams, VerifierParams};
+use crate::ciphersuite::{check_ciphersuite, Ciphersuite, CIPHERSUITE_SHA512, MAX_N};
+use crate::verkle_tree_point::VerkleTreeError;
+
+/// The seed of the parameters that `VerkleTree_point::new` generates, only meant for testing.
+pub const DEFAULT_SEED: &str = "This is our Favourite very very long Seed";
+
+// e(g1, g2)^{α^{n+1}} in a `pointproofs-paramgen` file, 12 field elements of 48 bytes
+const PARAMGEN_GT_LEN: usize = 576;
+
+/*  The pointproofs parameters of a tree. Parameters for n commit to at most n values, a node with
+    less children is committed as a prefix where the missing values are zero. So one parameter set
+    sized to the widest tree can be shared by all trees with a width up to n.
+    The parameters are not cut down to the width: they contain g^{α^i} for every i but n + 1, a set
+    for n cut to its first m < n powers would contain g^{α^{m+1}} and the commitments would not be binding.
+    Proofs depend on n, so they have to be verified with the same parameters they were made with.
+*/
+#[derive(Debug, Clone)]
+pub struct TreeParams<E: PointproofsCurve = Bls12_381> {
+    prover: ProverParams<E>,
+    verifier: VerifierParams<E>,
+}
+
+impl TreeParams {
+    /// The parameters for `n` values generated from `DEFAULT_SEED`.
+    pub fn new(n: usize) -> Result<Self, VerkleTreeError> {
+        Self::from_seed(DEFAULT_SEED, n)
+    }
+
+    /// Reads the output of the `pointproofs-paramgen` ceremony.
+    pub fn read_param<R: Read>(reader: &mut R) -> Result<Self, VerkleTreeError> {
+        Self::read_param_with_ciphersuite(reader, CIPHERSUITE_SHA512)
+    }
+
+    /*  As `read_param`, the values are hashed as defined by `ciphersuite`. The ceremony writes
+        `| n: u32 | g1^{α^i} | g2^{α^i} | e(g1, g2)^{α^{n+1}} |` for i in 1..=n and n+2..=2n, n little-endian
+        and the points compressed. The target group element is recomputed. As in the pointproofs crate the
+        points have to be the powers of one α, and with `group_switched` the prover gets the G2 points.
+    */
+    pub fn read_param_with_ciphersuite<R: Read>(reader: &mut R, ciphersuite: Ciphersuite) -> Result<Self, VerkleTreeError> {
+        let mut n = [0u8; 4];
+        reader.read_exact(&mut n).map_err(|_| VerkleTreeError::ParamError)?;
+        let n = u32::from_le_bytes(n) as usize;
+        if !check_ciphersuite(ciphersuite) || n == 0 || n > MAX_N {
+            return Err(VerkleTreeError::ParamError);
+        }
+        let g1_powers = read_points::<G1Affine, _>(reader, 2 * n - 1)?;
+        let g2_powers = read_points::<G2Affine, _>(reader, 2 * n - 1)?;
+        reader.read_exact(&mut [0u8; PARAMGEN_GT_LEN]).map_err(|_| VerkleTreeError::ParamError)?;
+
+        #[cfg(not(feature = "group_switched"))]
+        let (prover_powers, verifier_powers) = (with_identity(g1_powers, n), g2_powers[..n].to_vec());
+        #[cfg(feature = "group_switched")]
+        let (prover_powers, verifier_powers) = (with_identity(g2_powers, n), g1_powers[..n].to_vec());
+        let (prover, verifier) = params_from_generators(ciphersuite, n, prover_powers, verifier_powers)?;
+        if !is_consistent(&prover, &verifier) {
+            return Err(VerkleTreeError::ParamError);
+        }
+        Ok(TreeParams { prover, verifier })
+    }
+}
+
+// the ceremony leaves out α^{n+1}, the prover parameters hold the identity in its place
+fn with_identity<P: AffineRepr>(mut powers: Vec<P>, n: usize) -> Vec<P> {
+    powers.insert(n, P::zero());
+    powers
+}
+
+impl<E: PointproofsCurve> TreeParams<E> {
+    /// Parameters for up to `n` values, `n` can be at most `MAX_N`.
+    pub fn from_seed<Blob: AsRef<[u8]>>(seed: Blob, n: usize) -> Result<Self, VerkleTreeError> {
+        Self::from_seed_with_ciphersuite(seed, CIPHERSUITE_SHA512, n)
+    }
+
+    /// As `from_seed`, the values are hashed as defined by `ciphersuite`.
+    pub fn from_seed_with_ciphersuite<Blob: AsRef<[u8]>>(seed: Blob, ciphersuite: Ciphersuite, n: usize) -> Result<Self, VerkleTreeError> {
+        if n == 0 || n > MAX_N {
+            return Err(VerkleTreeError::ParamError);
+        }
+        let (prover, verifier) = ark_pointproofs::paramgen_from_seed(seed, ciphersuite, n)?;
+        Ok(TreeParams { prover, verifier })
+    }
+
+    /// Precomputes the prover parameters to `precomputation`, `Precomputation::None` drops the tables.
+    /// Commit, prove and update pick the precomputed code paths by themselves.
+    pub fn with_precomputation(mut self, precomputation: Precomputation) -> Self {
+        self.prover.precompute(precomputation);
+        self
+    }
+
+    /// Reads parameters written with `write`. The precomputed tables are read back as well, so they
+    /// only have to be computed once, see `ProverParams::read_precomputation`.
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, VerkleTreeError> {
+        let (mut prover, verifier) = ark_pointproofs::read_params(reader)?;
+        prover.read_precomputation(reader)?;
+        Ok(TreeParams { prover, verifier })
+    }
+
+    /// Writes the parameters as `ark_pointproofs::write_params` followed by the precomputed tables.
+    pub fn write<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        ark_pointproofs::write_params(&self.prover, &self.verifier, writer)?;
+        self.prover.write_precomputation(writer)
+    }
+
+    /// The widest tree these parameters can be used for.
+    pub fn max_width(&self) -> usize {
+        self.prover.n()
+    }
+
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.prover.ciphersuite()
+    }
+
+    /// The precomputation of the prover parameters.
+    pub fn precomputation(&self) -> Precomputation {
+        self.prover.precomputation()
+    }
+
+    pub fn prover(&self) -> &ProverParams<E> {
+        &self.prover
+    }
+
+    pub fn verifier(&self) -> &VerifierParams<E> {
+        &self.verifier
+    }
+}
@@ -1,125 +1,309 @@
-use std::{collections::HashSet, vec};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
 
-use ark_bls12_381::{Fr as F, G1Affine};
-use ark_ec:: AffineRepr;
+use ark_bls12_381::{Bls12_381, Fr as F, G1Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
 use ark_poly::univariate::DensePolynomial;
-use kzg_commitment::KZGCommitment;
 
-use ark_ff::PrimeField;
-use kzg_commitment::ProofError;
-use num_bigint::BigUint;
+use ark_ff::{PrimeField, Zero};
 
+use ark_serialize::CanonicalSerialize;
 use rayon::prelude::*;
 
-pub struct VerkleTree {
-    root: Option<VerkleNode>,
+use crate::arena::Arena;
+use crate::kzg::{Kzg, Opening};
+use crate::node_index::{create_index_for_proof, create_index_for_range, level_sizes, NodeIndex};
+use crate::stats::{ProofSize, TreeStats};
+use crate::tree_builder::{NodeStore, TreeBuilder};
+
+/// The nodes are stored in an arena and shared between versions of the tree, they are only copied when
+/// they are updated, so the handles returned by `snapshot` are cheap and not affected by later updates.
+/// A handle is a tree of its own: updating it copies the shared nodes and leaves the retained version as it was.
+pub struct VerkleTree<E: Pairing = Bls12_381> {
+    nodes: TreeNodes<E>,
     width: usize,
-    kzg: KZGCommitment,
+    leaf_count: usize,
+    depth: usize,
+    kzg: Arc<Kzg<E>>,
+    version: u64,
+    // the retained versions, see `snapshot` and `prune`
+    versions: BTreeMap<u64, TreeNodes<E>>,
 }
 
+/*  The commitments of the nodes level by level, addressed by their NodeIndex, and the values in one flat level.
+    The polynomials are not stored, a node that is opened interpolates its polynomial again from its values
+    or from the commitments of its children.
+*/
 #[derive(Debug, Clone)]
-struct VerkleNode {
-    commitment: G1Affine,
-    polynomial: DensePolynomial<F>,
-    children: Option<Vec<VerkleNode>>,
+struct TreeNodes<E: Pairing> {
+    commitments: Arena<E::G1Affine>,
+    values: Arena<E::ScalarField>,
 }
 
+impl<E: Pairing> TreeNodes<E> {
+    // the values are the children of the leaf nodes, so the offset of a value below a leaf node is its index
+    fn value(&self, index: usize) -> Option<&E::ScalarField> {
+        self.values.get(&NodeIndex { level: 0, offset: index })
+    }
+
+    fn value_mut(&mut self, index: usize) -> Option<&mut E::ScalarField> {
+        self.values.get_mut(&NodeIndex { level: 0, offset: index })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VerkleProof<E: Pairing = Bls12_381> {
+    pub proofs: Vec<ProofNode<E>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProofNode<E: Pairing = Bls12_381> {
+    pub commitment: E::G1Affine,
+    pub proof: E::G1Affine,
+    pub point: Vec<(E::ScalarField, E::ScalarField)>,
+}
+
+/// A proof for several indices, every opened node is stored under its position in the tree.
 #[derive(Debug, Clone)]
-pub struct VerkleProof {
-    pub proofs: Vec<ProofNode>,
+pub struct BatchProof<E: Pairing = Bls12_381> {
+    pub nodes: BTreeMap<NodeIndex, ProofNode<E>>,
 }
 
+/// A proof for all values in a range of indices.
+/// Only the nodes on the borders of the range are opened, the subtrees that lie
+/// completely in the range are recomputed by the verifier from `values`.
 #[derive(Debug, Clone)]
-pub struct ProofNode {
-    pub commitment: G1Affine,
-    pub proof: G1Affine,
-    pub point: Vec<(F, F)>,
+pub struct RangeProof<E: Pairing = Bls12_381> {
+    pub nodes: BTreeMap<NodeIndex, ProofNode<E>>,
+    pub values: Vec<E::ScalarField>,
 }
 
 impl VerkleTree {
+    /// A tree over BLS12-381 with `Kzg::insecure_test_setup`, only meant for tests. See `with_kzg` for other setups and curves.
     pub fn new(datas: &Vec<F>, width: usize) -> Result<Self, VerkleTreeError> {
-        let kzg = KZGCommitment::new(width);
-        Self::build_tree(kzg, datas, width)
+        Self::with_kzg(Arc::new(Kzg::insecure_test_setup(width)), datas, width)
     }
 
-    fn build_tree(kzg: KZGCommitment, datas: &Vec<F>, width: usize) -> Result<VerkleTree, VerkleTreeError> {
-        if datas.is_empty() {
+    /*  Builds the tree from a stream of values with a `TreeBuilder`, the nodes are committed while
+        the values are read. The builder writes the values and the commitments straight into the pages
+        of the tree, so besides the tree itself only a few pending nodes per level are held.
+        As `new` the tree uses `Kzg::insecure_test_setup`.
+    */
+    pub fn from_iter<I: IntoIterator<Item = F>>(values: I, width: usize) -> Result<Self, VerkleTreeError> {
+        let kzg = Arc::new(Kzg::insecure_test_setup(width));
+        let mut nodes = TreeNodes {
+            commitments: Arena::new(),
+            values: Arena::new(),
+        };
+        let mut builder = TreeBuilder::with_kzg(kzg.clone(), width, &mut nodes);
+        values.into_iter().for_each(|value| builder.push(value));
+        let root = builder.finish()?;
+        // the builder writes the commitments starting at the leaf nodes
+        nodes.commitments.reverse_levels();
+        let tree = Self::from_nodes(kzg, nodes, width);
+        debug_assert_eq!(tree.root_commitment(), Some(root));
+        Ok(tree)
+    }
+}
+
+// the store of `from_iter`, the values are the only level of `values`
+impl NodeStore for TreeNodes<Bls12_381> {
+    fn write_node(&mut self, height: usize, _offset: usize, commitment: &G1Affine) {
+        self.commitments.push(height, *commitment);
+    }
+
+    fn write_value(&mut self, _index: usize, value: &F) {
+        self.values.push(0, *value);
+    }
+}
+
+impl<E: Pairing> VerkleTree<E> {
+    /// A tree over the curve of `kzg`, which needs a degree of at least `width`.
+    /// Its proofs are verified with the same setup, which is passed to the verifiers.
+    pub fn with_kzg(kzg: Arc<Kzg<E>>, datas: &[E::ScalarField], width: usize) -> Result<Self, VerkleTreeError> {
+        if datas.is_empty() || kzg.max_coefficients() < width {
           return Err(VerkleTreeError::BuildError);
         }
-        if datas.len() <= width {
-            let polynomial = KZGCommitment::vector_to_polynomial(datas);
-            let commitment = kzg.commit_polynomial(&polynomial);
-            return Ok(VerkleTree {
-                root: Some(VerkleNode {
-                    commitment,
-                    polynomial,
-                    children: None,
-                }),
-                width,
-                kzg,
-            });
+        // The levels are built from the leaves up, the root is the only node of the last level
+        let mut levels = vec![Self::commit_chunks(&kzg, datas, width)];
+        while levels[levels.len() - 1].len() > 1 {
+            let next_level = Self::build_from_nodes(&kzg, &levels[levels.len() - 1], width);
+            levels.push(next_level);
         }
-        let leaf_nodes = Self::create_leaf_nodes(&kzg, datas, width);
-        let root = Self::build_tree_recursively(&kzg, &leaf_nodes, width);
+        Ok(Self::from_levels(kzg, levels, datas.to_vec(), width))
+    }
 
-        Ok(VerkleTree {
-            root: Some(root),
+    // `levels` holds the commitments of every level, starting at the leaf nodes.
+    fn from_levels(kzg: Arc<Kzg<E>>, mut levels: Vec<Vec<E::G1Affine>>, datas: Vec<E::ScalarField>, width: usize) -> Self {
+        levels.reverse();
+        let nodes = TreeNodes {
+            commitments: Arena::from_levels(levels),
+            values: Arena::from_levels(vec![datas]),
+        };
+        Self::from_nodes(kzg, nodes, width)
+    }
+
+    fn from_nodes(kzg: Arc<Kzg<E>>, nodes: TreeNodes<E>, width: usize) -> Self {
+        VerkleTree {
+            depth: nodes.commitments.depth(),
+            leaf_count: nodes.values.len(),
+            nodes,
             width,
             kzg,
-        })
+            version: 0,
+            versions: BTreeMap::new(),
+        }
     }
     
-    fn create_leaf_nodes(kzg: &KZGCommitment, datas: &[F], width: usize) -> Vec<VerkleNode> {
+    // Commits to every chunk of `width` values, the last chunk can be shorter.
+    pub(crate) fn commit_chunks(kzg: &Kzg<E>, datas: &[E::ScalarField], width: usize) -> Vec<E::G1Affine> {
         datas
             .par_chunks(width)
             .map(|chunk| {
-                let polynomial = KZGCommitment::vector_to_polynomial(&chunk.to_vec());
-                let commitment = kzg.commit_polynomial(&polynomial);
-                VerkleNode {
-                    commitment,
-                    polynomial,
-                    children: None,
-                }
+                let polynomial = Kzg::<E>::vector_to_polynomial(chunk);
+                kzg.commit_polynomial(&polynomial)
             })
             .collect()
     }
 
     fn build_from_nodes(
-        kzg: &KZGCommitment,
-        nodes: &[VerkleNode],
+        kzg: &Kzg<E>,
+        commitments: &[E::G1Affine],
         width: usize,
-    ) -> Vec<VerkleNode> {
-        nodes
+    ) -> Vec<E::G1Affine> {
+        commitments
         .par_chunks(width)
             .map(|chunk| {
-                let vector_commitment_mapping = chunk
+                let vector_commitment_mapping: Vec<E::ScalarField> = chunk
                     .par_iter()
-                    .map(|node| Self::map_commitment_to_field(&node.commitment))
+                    .map(Self::map_commitment_to_field)
                     .collect();
-                let polynomial = KZGCommitment::vector_to_polynomial(&vector_commitment_mapping);
-                let commitment = kzg.commit_polynomial(&polynomial);
-                VerkleNode {
-                    commitment,
-                    polynomial,
-                    children: Some(chunk.to_vec()),
-                }
+                let polynomial = Kzg::<E>::vector_to_polynomial(&vector_commitment_mapping);
+                kzg.commit_polynomial(&polynomial)
             })
             .collect()
     }
 
-    fn build_tree_recursively(
-        kzg: &KZGCommitment,
-        nodes: &[VerkleNode],
-        width: usize,
-    ) -> VerkleNode {
-        if nodes.len() == 1 {
-            return nodes[0].clone();
+/* The next functions update the tree and keep track of its versions  */
+
+    /*  Sets the value at `index`. Only the nodes on the path to the index are changed,
+        starting at the leaf every node adds the change of one child to its commitment.
+        Pages that are shared with a snapshot are copied first, the snapshot keeps the old nodes.
+    */
+    pub fn update(&mut self, index: usize, value: E::ScalarField) -> Result<(), VerkleTreeError> {
+        let old_value = self.nodes.value_mut(index).ok_or(VerkleTreeError::UpdateError)?;
+        let mut delta = value - *old_value;
+        *old_value = value;
+        let (mut node_index, mut position) = NodeIndex::of_leaf(index, self.width, self.depth);
+        loop {
+            let domain_size = node_index.child_count(self.width, self.depth, self.leaf_count);
+            let commitment = self.nodes.commitments.get_mut(&node_index).ok_or(VerkleTreeError::UpdateError)?;
+            let old_commitment = Self::map_commitment_to_field(commitment);
+            *commitment = Self::update_commitment(&self.kzg, *commitment, domain_size, &[(position, delta)]);
+            match node_index.parent(self.width) {
+                Some(parent) => {
+                    delta = Self::map_commitment_to_field(commitment) - old_commitment;
+                    position = node_index.position(self.width);
+                    node_index = parent;
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Retains the current state as version `self.version()` and returns a handle to it.
+    /// The following updates belong to the next version. The handle is copy on write, updating it
+    /// does not change the retained version or the tree.
+    pub fn snapshot(&mut self) -> Self {
+        self.versions.insert(self.version, self.nodes.clone());
+        let handle = self.handle(self.version, self.nodes.clone());
+        self.version += 1;
+        handle
+    }
+
+    /// The version the next snapshot gets, or for a handle the version it points to.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// The versions that are retained, from old to new.
+    pub fn retained_versions(&self) -> Vec<u64> {
+        self.versions.keys().copied().collect()
+    }
+
+    /// A handle to a retained version, proofs can be generated against it as for any other tree.
+    /// As for `snapshot`, updates to the handle are not seen by the retained version.
+    pub fn at_version(&self, version: u64) -> Option<Self> {
+        let nodes = self.versions.get(&version)?;
+        Some(self.handle(version, nodes.clone()))
+    }
+
+    /// Drops every retained version except the last `keep_last`.
+    /// The nodes that are only used by dropped versions are freed once no handle uses them.
+    pub fn prune(&mut self, keep_last: usize) {
+        while self.versions.len() > keep_last {
+            self.versions.pop_first();
         }
-        let next_level = Self::build_from_nodes(kzg, nodes, width);
-        Self::build_tree_recursively(kzg, &next_level, width)
     }
 
-    pub fn generate_proof(&self, index: usize, data: &F) -> Result<VerkleProof, VerkleTreeError> {
+    /*  The indices whose value differs between the two trees, with the value in self and in other.
+        Both trees need the same width and number of values. Subtrees with equal commitments are equal
+        and are skipped, so this only walks the paths to the changed values.
+    */
+    pub(crate) fn changed_leaves(&self, other: &Self) -> Vec<(usize, E::ScalarField, E::ScalarField)> {
+        let mut changes = Vec::new();
+        self.changed_leaves_of_node(other, NodeIndex::root(), &mut changes);
+        changes
+    }
+
+    fn changed_leaves_of_node(&self, other: &Self, node_index: NodeIndex, changes: &mut Vec<(usize, E::ScalarField, E::ScalarField)>) {
+        if self.find_node(&node_index) == other.find_node(&node_index) {
+            return;
+        }
+        let child_count = node_index.child_count(self.width, self.depth, self.leaf_count);
+        for position in 0..child_count {
+            let child = node_index.child(self.width, position);
+            if node_index.level < self.depth {
+                self.changed_leaves_of_node(other, child, changes);
+            } else {
+                let (value, other_value) = (self.nodes.value(child.offset), other.nodes.value(child.offset));
+                if value != other_value {
+                    changes.push((child.offset, *value.unwrap(), *other_value.unwrap()));
+                }
+            }
+        }
+    }
+
+    fn handle(&self, version: u64, nodes: TreeNodes<E>) -> Self {
+        VerkleTree {
+            nodes,
+            width: self.width,
+            leaf_count: self.leaf_count,
+            depth: self.depth,
+            kzg: self.kzg.clone(),
+            version,
+            versions: BTreeMap::new(),
+        }
+    }
+
+    /// The value at `index`.
+    pub fn get(&self, index: usize) -> Option<E::ScalarField> {
+        self.nodes.value(index).copied()
+    }
+
+    /// The value at `index` with a proof for it.
+    pub fn prove(&self, index: usize) -> Result<(E::ScalarField, VerkleProof<E>), VerkleTreeError> {
+        let value = self.get(index).ok_or(VerkleTreeError::ProofGenerateError)?;
+        let proof = self.generate_proof(index, &value)?;
+        Ok((value, proof))
+    }
+
+    pub fn generate_proof(&self, index: usize, data: &E::ScalarField) -> Result<VerkleProof<E>, VerkleTreeError> {
+        if index >= self.leaf_count {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
         let mut node_positions = Vec::<usize>::new();
         let mut value_positions = Vec::<usize>::new();
 
@@ -138,29 +322,24 @@ impl VerkleTree {
         node_positions.reverse();
         value_positions.reverse();
 
-        let mut current_node = self.root.clone().unwrap();
-
-        let mut proofs = Vec::<ProofNode>::new();
-        for (i, &_node_position) in node_positions.iter().enumerate() {
-            let current_commitment = current_node.commitment;
-            let current_polynomial = current_node.polynomial.clone();
+        let mut proofs = Vec::<ProofNode<E>>::new();
+        for (i, &node_position) in node_positions.iter().enumerate() {
+            let node_index = NodeIndex { level: i, offset: node_position };
             let node_to_prove_position = value_positions[i];
-            let data_to_prove = if let Some(children) = current_node.children {
-                let next_node = children[node_to_prove_position].clone();
-                let next_node_commitment = next_node.commitment;
-                current_node = next_node;
-                Self::map_commitment_to_field(&next_node_commitment)
+            let data_to_prove = if i < depth {
+                let next_node = self.find_node(&node_index.child(self.width, node_to_prove_position));
+                Self::map_commitment_to_field(next_node)
             } else {
                 *data
             };
 
-            let points = vec![(F::from(node_to_prove_position as u32), data_to_prove)];
-            let proof = self.kzg.generate_proof(&current_polynomial, &points);
+            let points = vec![(E::ScalarField::from(node_to_prove_position as u32), data_to_prove)];
+            let proof = self.kzg.generate_proof(&self.node_polynomial(&node_index), &points);
 
             match proof {
                 Ok(proof) => {
                     proofs.push(ProofNode {
-                        commitment: current_commitment,
+                        commitment: *self.find_node(&node_index),
                         proof,
                         point: points,
                     });
@@ -174,140 +353,75 @@ impl VerkleTree {
 
 /* The next functions are to generate proofs for several indices simultaeusly  */
 
-    /*  This function returns a long vector which reads the nodes from top to bottom left to right
-        Each index contains either a proof of some children, or a None value
+    /*  This function opens every node on the paths from the root to the given indices.
+        Each opened node is stored under its NodeIndex, the proof of a node opens all children
+        on these paths at once. The tree does not need to be full and the width can be any number >= 2.
     */
-    pub fn generate_batch_proof (&self, index: Vec<usize>, data: &[F]) -> Vec<Option<ProofNode>> {
-        assert!(data.len() % self.width == 0, "Please give a tree that is compeletly filled, i.e. log_{{width}}(data) is a natural number");
-        assert!(!index.is_empty(), "Please give a non empty index");
-        let width = self.width;
-        let depth = self.depth();
-        // The following line creates a vector, on each index is a vector which incidates which children nodes need to be proven
-        let index_for_proof = VerkleTree::create_index_for_proof(index, width, self.depth());
-        
-        let proofs: Vec<Option<ProofNode>> = (0.. index_for_proof.len())
-        .into_par_iter()
-        .map(|ind| {
-            if index_for_proof[ind]!= vec![]  {
-                let path = Self::path_to_child(ind, width);
-
-                let node: VerkleNode = Self::find_commitment_node(self, path);
-                let index_first_child: usize =
-                    if node.children.is_none(){
-                        let index_first_child_data: usize = 
-                            if width == 2 {
-                                usize::pow(width, (depth+1).try_into().unwrap())/(width-1) -1
-                            }
-                            else {
-                                usize::pow(width, (depth+1).try_into().unwrap())/(width-1)
-                            };
-                        width*ind +1 - index_first_child_data
-                    }
-                    else {
-                        width*ind +1
-                    };
-                let proof_of_node = self.find_proof_node(node,  index_for_proof[ind].clone(), data, index_first_child).expect("failed to generate proof for node");
-                Some(proof_of_node)
-            }
-            else {
-                None
-            }
-        }).collect();
-        proofs
+    pub fn prove_batch(&self, indices: &[usize]) -> Result<(Vec<E::ScalarField>, BatchProof<E>), VerkleTreeError> {
+        if indices.is_empty() || indices.iter().any(|&i| i >= self.leaf_count) {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
+        // The following line creates a map, for each node to open it contains the children that need to be proven
+        let index_for_proof = create_index_for_proof(indices, self.width, self.depth());
+        let nodes = self.open_nodes(&index_for_proof)?;
+        let values = indices.iter().map(|&index| self.get(index).unwrap()).collect();
+        Ok((values, BatchProof { nodes }))
     }
 
-    fn path_to_child (mut index: usize, width: usize) -> Vec<usize> {
-        let mut path : Vec<usize> = Vec::new();
-        if index == 0 {
-            return path;
-        }
-        else {
-            path.push((index-1) % width); //push current node index
-            while index > width{
-                index = (index-1)/width; //compute parent
-                path.push((index-1) % width); //push parent
-            }
+    /// As `prove_batch`, fails if `data` does not hold the values of the tree at `index`.
+    pub fn generate_batch_proof (&self, index: Vec<usize>, data: &[E::ScalarField]) -> Result<BatchProof<E>, VerkleTreeError> {
+        let (values, proof) = self.prove_batch(&index)?;
+        if index.iter().zip(values).any(|(&i, value)| data.get(i) != Some(&value)) {
+            return Err(VerkleTreeError::ProofGenerateError);
         }
-        path.reverse();
-        path
-
-    }
-
-    fn create_index_for_proof(index: Vec<usize>, width: usize, depth: usize) -> Vec<Vec<usize>> {
-
-        /* This creates a vector of the form:
-            [[2, 0], [1, 2], [], [0]] 
-            The root is on index 0 and children [2,0] need to be proven.
-            etc.
-            */
-        let mut tree_path: Vec<Vec<usize>>  = Vec::new();
-        let mut indexes = index.clone();
-        for level in 1.. (depth+1){
-            let data_level_above = width.pow((depth+1-level) as u32);
-            // This creates for each parent node an empty vector
-            let mut level_above: Vec<Vec<usize>> = vec![vec![]; data_level_above];
-            // This is a hash set to only insert if the node is "new"
-            let mut new_indices: HashSet<usize> = HashSet::new();
-            /* The loop adds the index of the child that needs to be proven in the vector of the parent node
-                This is done modulus the width of the tree. 
-                Also the loop creates a vector for the indices of the parent node for the next layer*/
-            for i in 0.. indexes.len(){
-                level_above[indexes[i] / width].push(indexes[i]% width);
-                new_indices.insert(indexes[i]/ width);
-            }
-            level_above.reverse();
-            for parent in level_above{
-                tree_path.push(parent);
-            }
-            indexes = new_indices.into_iter().collect();
-                
-        }
-        // We need to add the root manually
-        if depth != 0 {
-            let mut node_root: Vec<usize> = Vec::new();
-            for child in 0.. width{
-                if !tree_path[tree_path.len()- child-1].is_empty(){
-                    node_root.push(child);
+        Ok(proof)
+    }
+
+    // Opens the given positions of every node in the map.
+    fn open_nodes(&self, index_for_proof: &BTreeMap<NodeIndex, Vec<usize>>) -> Result<BTreeMap<NodeIndex, ProofNode<E>>, VerkleTreeError> {
+        index_for_proof
+        .par_iter()
+        .map(|(node_index, positions)| {
+            let proof_of_node = self.find_proof_node(node_index, positions)?;
+            Ok((*node_index, proof_of_node))
+        }).collect()
+    }
+
+    fn find_node(&self, node_index: &NodeIndex) -> &E::G1Affine {
+        self.nodes.commitments.get(node_index).expect("failed to find node")
+    }
+
+    // The values a node commits to, the values below a leaf node or the mapped commitments of the children.
+    fn child_values(&self, node_index: &NodeIndex) -> Vec<E::ScalarField> {
+        (0..node_index.child_count(self.width, self.depth, self.leaf_count))
+            .map(|position| {
+                let child = node_index.child(self.width, position);
+                if node_index.level < self.depth {
+                    Self::map_commitment_to_field(self.find_node(&child))
+                } else {
+                    self.nodes.value(child.offset).copied().expect("failed to find value")
                 }
-            }
-            tree_path.push( node_root);
-        }
-        else {
-            // If the tree has 1 layer, the "root" is just the commitment of all indices
-            tree_path = vec![index];
-        }
-        tree_path.reverse();
-        tree_path
+            })
+            .collect()
     }
-    
-    fn find_commitment_node(&self, path: Vec<usize>) -> VerkleNode{
-        let mut current_node = self.root.clone().unwrap();
-        for i in path {
-            current_node = current_node.children.expect("failed to find children")[i].clone();
-        }
-        current_node
+
+    fn node_polynomial(&self, node_index: &NodeIndex) -> DensePolynomial<E::ScalarField> {
+        Kzg::<E>::vector_to_polynomial(&self.child_values(node_index))
     }
 
-    fn find_proof_node (&self, node: VerkleNode, indices_to_proof: Vec<usize>, data: &[F], index_first_child: usize) ->  Result<ProofNode, VerkleTreeError>  {
-        let mut points = Vec::new();
-        if  node.children.is_some() {
-            for ind in indices_to_proof {
-                let point_1 = &node.children.clone().expect("this child was actually None")[ind].commitment;
-                let child_commitment = Self::map_commitment_to_field(point_1);
-                points.push((F::from(ind as u32),child_commitment));
-            }
-        }
-        else {
-            for ind in indices_to_proof {
-                points.push((F::from(ind as u32), data[index_first_child + ind]));
-            }
-        }
-        let proof: Result<G1Affine, ProofError> = self.kzg.generate_proof(&node.polynomial, &points);
-        
+    fn find_proof_node (&self, node_index: &NodeIndex, indices_to_proof: &[usize]) ->  Result<ProofNode<E>, VerkleTreeError>  {
+        let child_values = self.child_values(node_index);
+        let points: Vec<(E::ScalarField, E::ScalarField)> = indices_to_proof
+            .iter()
+            .map(|&ind| (E::ScalarField::from(ind as u32), child_values[ind]))
+            .collect();
+        let polynomial = Kzg::<E>::vector_to_polynomial(&child_values);
+        let proof = self.kzg.generate_proof(&polynomial, &points);
+
         match proof {
             Ok(proof) => {
                 let proof_node = ProofNode {
-                    commitment: node.commitment,
+                    commitment: *self.find_node(node_index),
                     proof,
                     point: points,
                 };
@@ -317,90 +431,320 @@ impl VerkleTree {
         }
     }
 
-    // This function computes batch proofs, is also works if the NONE values are already deleted.
-    pub fn batch_proof_verify (root: G1Affine, mut tree_proofs: Vec<Option<ProofNode>>, width: usize, indices: Vec<usize>, depth: usize, data: Vec<F>) -> bool {
-        assert!(tree_proofs[0].is_some());
-
+    /*  Verifies a batch proof for the values `data` at `indices` with `kzg`, the setup of the tree.
+        The opened nodes have to be exactly the nodes on the paths to the indices, every opened child
+        has to be the commitment of the next opened node and the opened leaf values have to be `data`.
+    */
+    pub fn batch_proof_verify (root: E::G1Affine, tree_proofs: &BatchProof<E>, width: usize, indices: &[usize], depth: usize, data: &[E::ScalarField], kzg: &Kzg<E>) -> bool {
+        if indices.len() != data.len() || kzg.max_coefficients() < width {
+            return false;
+        }
         // Check if the root is correct
-        if root != tree_proofs[0].as_ref().unwrap().commitment {
-            println!("Root commitment is not correct");
+        if !tree_proofs.nodes.get(&NodeIndex::root()).is_some_and(|node| node.commitment == root) {
             return false;
         }
 
-        // Check if the proofs are of the correct size, also works if NONE values were already deleted
-        tree_proofs.retain(|node| node.is_some());
-        // The expected length
-        let mut check_vector: Vec<Vec<usize>> = Self::create_index_for_proof(indices, width, depth);
-        check_vector.retain(|vector| !vector.is_empty());
-        if tree_proofs.len() != check_vector.len() {
-            println!("The tree proofs vector is not of the correct length");
+        // Check if the proof opens exactly the nodes and children on the paths to the indices
+        let check_vector = create_index_for_proof(indices, width, depth);
+        if tree_proofs.nodes.len() != check_vector.len() {
             return false;
         }
-        // To find the commitment value easier, we dont save the ProofNodes but the commitments in the next vector
-        let mut commitments_vector: Vec<F> = tree_proofs.iter().map(|proof_node|
-            {
-                let node = proof_node.as_ref().unwrap();
-                Self::map_commitment_to_field(&node.commitment)
+        let mut values: HashMap<usize, E::ScalarField> = HashMap::new();
+        for (index, value) in indices.iter().zip(data) {
+            if *values.entry(*index).or_insert(*value) != *value {
+                return false;
             }
-        ).collect();
-        data.iter().for_each(|d| commitments_vector.push(*d));
-        
-        let kzg = KZGCommitment::new(width + 1);
-        tree_proofs.par_iter().all(|proof_node| {
-            if let Some(node) = proof_node{
-                let b1 = kzg.verify_proof(&node.commitment, &node.point, &node.proof);
-                // For simplicity we check if there is a commitment that matches
-                let b2 = node.point.par_iter().all(|point| {
-                    commitments_vector.iter().any(|n| *n == point.1)
-                });
-                b1 & b2
+        }
+
+        let valid_points = check_vector.par_iter().all(|(node_index, positions)| {
+            let node = match tree_proofs.nodes.get(node_index) {
+                Some(node) => node,
+                None => return false,
+            };
+            if node.point.len() != positions.len() {
+                return false;
             }
-            else {
-                true
+            let expected_points = positions.iter().zip(&node.point).all(|(&position, point)| {
+                let expected = if node_index.level == depth {
+                    values[&(node_index.offset * width + position)]
+                } else {
+                    match tree_proofs.nodes.get(&node_index.child(width, position)) {
+                        Some(child) => Self::map_commitment_to_field(&child.commitment),
+                        None => return false,
+                    }
+                };
+                point.0 == E::ScalarField::from(position as u32) && point.1 == expected
+            });
+            expected_points
+        });
+        // the proof has exactly the nodes of check_vector, so every opening is checked once
+        valid_points && kzg.batch_verify(&tree_proofs.nodes.values().map(ProofNode::opening).collect::<Vec<_>>())
+    }
+
+/* The next functions are to generate proofs for a range of indices  */
+
+    /*  Proves all values in `range`. The nodes that are partly covered by the range open
+        every child (or value) that overlaps with the range, the nodes below them that are
+        completely covered are not opened since the verifier can recommit them from the values.
+    */
+    pub fn prove_range(&self, range: Range<usize>) -> Result<RangeProof<E>, VerkleTreeError> {
+        if range.is_empty() || range.end > self.leaf_count {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
+        let index_for_proof = create_index_for_range(&range, self.width, self.depth(), self.leaf_count);
+        let nodes = self.open_nodes(&index_for_proof)?;
+        let values = self.iter_range(range).map(|(_, value)| value).collect();
+        Ok(RangeProof { nodes, values })
+    }
+
+    /// As `prove_range`, fails if `data` does not hold the values of the tree in `range`.
+    pub fn generate_range_proof(&self, range: Range<usize>, data: &[E::ScalarField]) -> Result<RangeProof<E>, VerkleTreeError> {
+        let proof = self.prove_range(range.clone())?;
+        if data.get(range) != Some(&proof.values[..]) {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
+        Ok(proof)
+    }
+
+    /*  Verifies a range proof of a tree with `leaf_count` values and setup `kzg`, returns the values in `range`.
+        The opened nodes have to be exactly the nodes partly covered by the range, every opened child
+        is either the next opened node or a covered subtree that is recommitted from the values.
+    */
+    pub fn verify_range_proof(root: E::G1Affine, range_proof: &RangeProof<E>, width: usize, leaf_count: usize, range: Range<usize>, kzg: &Kzg<E>) -> Option<Vec<E::ScalarField>> {
+        if range.is_empty() || range.end > leaf_count || range_proof.values.len() != range.len() || kzg.max_coefficients() < width {
+            return None;
+        }
+        let depth = level_sizes(leaf_count, width).len() - 1;
+        let values = &range_proof.values;
+        let covered_commitment = |node_index: NodeIndex| {
+            let leaves = node_index.leaf_range(width, depth, leaf_count);
+            let subtree_values = &values[leaves.start - range.start..leaves.end - range.start];
+            Self::commit_subtree(kzg, subtree_values, width, depth - node_index.level)
+        };
+
+        let check_vector = create_index_for_range(&range, width, depth, leaf_count);
+        if check_vector.is_empty() {
+            // the range covers the whole tree
+            let valid = range_proof.nodes.is_empty() && covered_commitment(NodeIndex::root()) == root;
+            return valid.then(|| values.clone());
+        }
+        if !range_proof.nodes.get(&NodeIndex::root()).is_some_and(|node| node.commitment == root) {
+            return None;
+        }
+        if range_proof.nodes.len() != check_vector.len() {
+            return None;
+        }
+
+        let valid_points = check_vector.par_iter().all(|(node_index, positions)| {
+            let node = match range_proof.nodes.get(node_index) {
+                Some(node) => node,
+                None => return false,
+            };
+            if node.point.len() != positions.len() {
+                return false;
             }
+            let expected_points = positions.iter().zip(&node.point).all(|(&position, point)| {
+                let expected = if node_index.level == depth {
+                    values[node_index.offset * width + position - range.start]
+                } else {
+                    let child = node_index.child(width, position);
+                    if check_vector.contains_key(&child) {
+                        match range_proof.nodes.get(&child) {
+                            Some(child) => Self::map_commitment_to_field(&child.commitment),
+                            None => return false,
+                        }
+                    } else {
+                        Self::map_commitment_to_field(&covered_commitment(child))
+                    }
+                };
+                point.0 == E::ScalarField::from(position as u32) && point.1 == expected
+            });
+            expected_points
         });
-        true
+        let valid = valid_points && kzg.batch_verify(&range_proof.nodes.values().map(ProofNode::opening).collect::<Vec<_>>());
+        valid.then(|| values.clone())
     }
 
+    // Commits to the values of a subtree with `height` levels above its leaf nodes.
+    fn commit_subtree(kzg: &Kzg<E>, values: &[E::ScalarField], width: usize, height: usize) -> E::G1Affine {
+        let mut nodes = Self::commit_chunks(kzg, values, width);
+        for _ in 0..height {
+            nodes = Self::build_from_nodes(kzg, &nodes, width);
+        }
+        nodes[0]
+    }
 
-    pub fn verify_proof(root: G1Affine, verkle_proof: &VerkleProof, width: usize) -> bool {
-        let proof_root = verkle_proof.proofs[0].commitment;
-        if proof_root != root {
+    /*  Verifies a proof for `value` at `index` of a tree with `depth` and setup `kzg`. The proof opens one child per level,
+        root first: the positions are the digits of the index, every opened child is the commitment of
+        the next node and the last one is `value`.
+    */
+    pub fn verify_proof(root: E::G1Affine, verkle_proof: &VerkleProof<E>, width: usize, depth: usize, index: usize, value: E::ScalarField, kzg: &Kzg<E>) -> bool {
+        let proofs = &verkle_proof.proofs;
+        if width < 2 || kzg.max_coefficients() < width || proofs.len() != depth + 1 || proofs[0].commitment != root {
             return false;
         }
-        let kzg = KZGCommitment::new(width+1);
-        let verkle_proofs = verkle_proof.proofs.clone();
-        for proof in verkle_proofs {
-            if !kzg.verify_proof(&proof.commitment, &proof.point, &proof.proof){
-                return  false;
+        let mut position = index;
+        for (level, node) in proofs.iter().enumerate().rev() {
+            let expected = if level == depth {
+                value
+            } else {
+                Self::map_commitment_to_field(&proofs[level + 1].commitment)
+            };
+            if node.point != [(E::ScalarField::from((position % width) as u32), expected)] {
+                return false;
             }
+            position /= width;
+        }
+        // the index has no more digits than the tree has levels
+        if position != 0 {
+            return false;
+        }
+        kzg.batch_verify(&verkle_proof.proofs.iter().map(ProofNode::opening).collect::<Vec<_>>())
+    }
+
+    /*  Adds delta_i * L_i to the committed polynomial for every (i, delta_i), where L_i is the
+        lagrange polynomial of position i over the domain 0..domain_size of the node.
+        Since the commitment is linear this only needs one commitment to the vector of deltas.
+    */
+    pub(crate) fn update_commitment(kzg: &Kzg<E>, commitment: E::G1Affine, domain_size: usize, deltas: &[(usize, E::ScalarField)]) -> E::G1Affine {
+        let mut delta_vector = vec![E::ScalarField::from(0u32); domain_size];
+        for &(position, delta) in deltas {
+            delta_vector[position] += delta;
         }
-        true
+        if delta_vector.iter().all(|delta| *delta == E::ScalarField::from(0u32)) {
+            return commitment;
+        }
+        let delta_polynomial = Kzg::<E>::vector_to_polynomial(&delta_vector);
+        (commitment + kzg.commit_polynomial(&delta_polynomial)).into_affine()
+    }
+
+    /// The point at infinity, the commitment of a node whose values are all zero, is mapped to zero.
+    pub(crate) fn map_commitment_to_field(g1_point: &E::G1Affine) -> E::ScalarField {
+        let Some((x, y)) = g1_point.xy() else {
+            return E::ScalarField::zero();
+        };
+        // a base field element is serialized as its little-endian integer
+        let mut fq_bytes = Vec::new();
+        (*x + y).serialize_uncompressed(&mut fq_bytes).expect("serializing to a vector does not fail");
+        E::ScalarField::from_le_bytes_mod_order(&fq_bytes)
     }
 
-    fn map_commitment_to_field(g1_point: &G1Affine) -> F {
-        let fq_value = g1_point.x().expect("its the x value") + g1_point.y().expect("its the y value");
-        let fq_bigint: BigUint = fq_value.into_bigint().into();
-        F::from_le_bytes_mod_order(&fq_bigint.to_bytes_le())
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The setup of the tree, its proofs are verified with it.
+    pub fn kzg(&self) -> &Arc<Kzg<E>> {
+        &self.kzg
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
     }
 
     pub fn depth(&self) -> usize {
-        let mut depth = 0;
+        self.depth
+    }
 
-        let mut current_node = self.root.clone().unwrap(); // TODO: error handling
-        while current_node.children.is_some() {
-            depth += 1;
-            current_node = current_node.children.unwrap()[0].clone();
+    /// The values of the tree with their index.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, E::ScalarField)> + '_ {
+        self.iter_range(0..self.leaf_count)
+    }
+
+    /// The values with an index in `range`.
+    pub fn iter_range(&self, range: Range<usize>) -> impl Iterator<Item = (usize, E::ScalarField)> + '_ {
+        let range = range.start.min(self.leaf_count)..range.end.min(self.leaf_count);
+        range.map(move |index| (index, *self.nodes.value(index).expect("failed to find value")))
+    }
+
+    /// Calls `visitor` for every node, parents before their children and children from left to right,
+    /// with the positions of the children to follow from the root, the commitment and whether the node is a leaf.
+    pub fn walk<V: FnMut(&[usize], &E::G1Affine, bool)>(&self, mut visitor: V) {
+        self.walk_node(NodeIndex::root(), &mut Vec::new(), &mut visitor);
+    }
+
+    fn walk_node<V: FnMut(&[usize], &E::G1Affine, bool)>(&self, node_index: NodeIndex, path: &mut Vec<usize>, visitor: &mut V) {
+        visitor(path, self.find_node(&node_index), node_index.level == self.depth);
+        if node_index.level < self.depth {
+            for position in 0..node_index.child_count(self.width, self.depth, self.leaf_count) {
+                path.push(position);
+                self.walk_node(node_index.child(self.width, position), path, visitor);
+                path.pop();
+            }
         }
-        depth
     }
 
-    pub fn root_commitment(&self) -> Option<G1Affine> {
+    pub fn stats(&self) -> TreeStats {
+        // the commitments and values own no heap memory, so the pages are all the tree uses
+        let memory_usage = self.nodes.commitments.page_memory_usage() + self.nodes.values.page_memory_usage();
+        TreeStats::new(self.width, self.depth, self.leaf_count, self.nodes.commitments.len(), memory_usage)
+    }
+
+    pub fn root_commitment(&self) -> Option<E::G1Affine> {
         // match &self.root {
         //     None => None,
         //     Some(verkle_node) => Some(verkle_node.commitment),
         // }
-        self.root.as_ref().map(|verkle_node| verkle_node.commitment)
+        self.nodes.commitments.get(&NodeIndex::root()).copied()
+    }
+}
+
+impl<E: Pairing> ProofNode<E> {
+    pub(crate) fn opening(&self) -> Opening<'_, E> {
+        Opening { commitment: &self.commitment, points: &self.point, proof: &self.proof }
+    }
+
+    /// The commitment and the opening proof are compressed points,
+    /// every opened point is an (index, value) pair of field elements.
+    pub fn size(&self) -> ProofSize {
+        let point_len = E::ScalarField::from(0u32).compressed_size();
+        ProofSize {
+            commitments: self.commitment.compressed_size(),
+            opening_proofs: self.proof.compressed_size(),
+            values: self.point.len() * point_len,
+            index_metadata: self.point.len() * point_len,
+        }
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.size().total()
+    }
+}
+
+impl<E: Pairing> VerkleProof<E> {
+    /// The size of the proof per level, starting at the root.
+    pub fn size_breakdown(&self) -> Vec<ProofSize> {
+        self.proofs.iter().map(|proof| proof.size()).collect()
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.proofs.iter().map(|proof| proof.size_in_bytes()).sum()
+    }
+}
+
+impl<E: Pairing> BatchProof<E> {
+    /// The size of the proof per level, starting at the root.
+    pub fn size_breakdown(&self) -> Vec<ProofSize> {
+        let mut levels: Vec<ProofSize> = Vec::new();
+        for (node_index, node) in &self.nodes {
+            if levels.len() <= node_index.level {
+                levels.resize(node_index.level + 1, ProofSize::default());
+            }
+            levels[node_index.level] += node.size();
+        }
+        levels
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.nodes.values().map(|node| node.size_in_bytes()).sum()
+    }
+}
+
+impl<E: Pairing> RangeProof<E> {
+    /// The opened nodes and the values of the range, the values are needed to recompute the covered subtrees.
+    pub fn size_in_bytes(&self) -> usize {
+        let value_len = E::ScalarField::from(0u32).compressed_size();
+        self.nodes.values().map(|node| node.size_in_bytes()).sum::<usize>() + self.values.len() * value_len
     }
 }
 
@@ -409,4 +753,7 @@ pub enum VerkleTreeError {
     BuildError,
     ProofGenerateError,
     EmptyTree,
+    UpdateError,
+    IncompatibleTrees,
+    ParamError,
 }
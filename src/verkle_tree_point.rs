@@ -1,87 +1,126 @@
-use std::{collections::HashSet, vec};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
 
-use pairing_plus::serdes::SerDes;
-
-use pointproofs::pairings::{param::paramgen_from_seed, *};
-use pointproofs::pairings::Commitment;
+use ark_bls12_381::Bls12_381;
 use rayon::prelude::*;
 
-pub struct VerkleTree {
-    root: Option<VerkleNode>,
+use crate::ark_pointproofs::{Commitment, PointproofsCurve, Proof, ProverParams};
+use crate::arena::Arena;
+use crate::encoding::PointEncoding;
+use crate::node_index::{create_index_for_proof, create_index_for_range, level_sizes, NodeIndex};
+use crate::stats::{ProofSize, TreeStats};
+use crate::tree_params::TreeParams;
+
+// serialized size of an index and of the length prefix of a value
+const INDEX_LEN: usize = 8;
+const VALUE_LEN_PREFIX: usize = 4;
+
+/// The pointproofs tree over a pairing curve, BLS12-381 by default, see `ark_pointproofs`.
+/// As in the KZG tree the nodes are stored in an arena that is shared with the handles of `snapshot`,
+/// an update only copies the pages it changes.
+pub struct VerkleTree<E: PointproofsCurve = Bls12_381> {
+    nodes: Arena<VerkleNode<E>>,
     width: usize,
-    pp: ProverParams,
+    leaf_count: usize,
+    depth: usize,
+    params: Arc<TreeParams<E>>,
+    version: u64,
+    // the retained versions, see `snapshot` and `prune`
+    versions: BTreeMap<u64, Arena<VerkleNode<E>>>,
 }
 
+// The children of a node are found in the arena by their NodeIndex.
 #[derive(Debug, Clone)]
-struct VerkleNode {
-    commitment: Commitment,
+struct VerkleNode<E: PointproofsCurve> {
+    commitment: Commitment<E>,
     values: Vec<Vec<u8>>, //should maybe be a vector 
-    children: Option<Vec<VerkleNode>>,
 }
 
 #[derive(Debug, Clone)]
-pub struct VerkleProof {
-    pub proofs: Vec<ProofNode>,
+pub struct VerkleProof<E: PointproofsCurve = Bls12_381> {
+    pub proofs: Vec<ProofNode<E>>,
 }
 
 #[derive(Debug, Clone)]
-pub struct ProofNode {
-    pub commitment: Commitment, //mayb be commitment
-    pub proof: Proof, //of type Proof
+pub struct ProofNode<E: PointproofsCurve = Bls12_381> {
+    pub commitment: Commitment<E>, //mayb be commitment
+    pub proof: Proof<E>, //of type Proof
     pub indices : Vec<usize>,
     pub values: Vec<Vec<u8>> , // just a vector  (index:usize, value: vec<vec<u8>>)
 }
 
+/// A proof for several indices, every opened node is stored under its position in the tree.
+#[derive(Debug, Clone)]
+pub struct BatchProof<E: PointproofsCurve = Bls12_381> {
+    pub nodes: BTreeMap<NodeIndex, ProofNode<E>>,
+}
+
+/// A proof for all values in a range of indices.
+/// Only the nodes on the borders of the range are opened, the subtrees that lie
+/// completely in the range are recomputed by the verifier from `values`.
+#[derive(Debug, Clone)]
+pub struct RangeProof<E: PointproofsCurve = Bls12_381> {
+    pub nodes: BTreeMap<NodeIndex, ProofNode<E>>,
+    pub values: Vec<Vec<u8>>,
+}
+
 impl VerkleTree {
     pub fn new(datas: &Vec<Vec<u8>>, width: usize) -> Result<Self, VerkleTreeError> {
-        let (prover_params, _) =
-            paramgen_from_seed("This is our Favourite very very long Seed", 0, width).unwrap();
-        
-        Self::build_tree(prover_params, datas, width)
+        Self::with_params(datas, width, Arc::new(TreeParams::new(width)?))
     }
+}
 
-    fn build_tree(prover_params: ProverParams, datas: &Vec<Vec<u8>>, width: usize) -> Result<VerkleTree, VerkleTreeError> {
+impl<E: PointproofsCurve> VerkleTree<E> {
+    /// Builds the tree with shared parameters, they have to be for at least `width` values.
+    /// Its proofs are verified with the same parameters, see `params`.
+    pub fn with_params(datas: &Vec<Vec<u8>>, width: usize, params: Arc<TreeParams<E>>) -> Result<Self, VerkleTreeError> {
+        if width > params.max_width() {
+            return Err(VerkleTreeError::ParamError);
+        }
+        Self::build_tree(params, datas, width)
+    }
+
+    fn build_tree(params: Arc<TreeParams<E>>, datas: &Vec<Vec<u8>>, width: usize) -> Result<Self, VerkleTreeError> {
         if datas.is_empty() {
           return Err(VerkleTreeError::BuildError);
         }
-        if datas.len() <= width {
-            let commitment: Commitment = Commitment::new(&prover_params, &datas).unwrap();
-            return Ok(VerkleTree {
-                root: Some(VerkleNode {
-                    commitment,
-                    values: datas.to_vec(),
-                    children: None,
-                }),
-                width,
-                pp: prover_params,
-            });
+        let prover_params = params.prover();
+        // The levels are built from the leaves up, the root is the only node of the last level
+        let mut levels = vec![Self::create_leaf_nodes(prover_params, datas, width)];
+        while levels[levels.len() - 1].len() > 1 {
+            let next_level = Self::build_from_nodes(prover_params, &levels[levels.len() - 1], width);
+            levels.push(next_level);
         }
-        let leaf_nodes = Self::create_leaf_nodes(&prover_params, datas, width);
-        let root = Self::build_tree_recursively(&prover_params, &leaf_nodes, width);
+        levels.reverse();
+        let nodes = Arena::from_levels(levels);
 
         Ok(VerkleTree {
-            root: Some(root),
+            depth: nodes.depth(),
+            nodes,
             width,
-            pp: prover_params,
+            leaf_count: datas.len(),
+            params,
+            version: 0,
+            versions: BTreeMap::new(),
         })
     }
     
-    fn create_leaf_nodes(prover_params: &ProverParams, datas: &Vec<Vec<u8>>, width: usize) -> Vec<VerkleNode> {
+    fn create_leaf_nodes(prover_params: &ProverParams<E>, datas: &Vec<Vec<u8>>, width: usize) -> Vec<VerkleNode<E>> {
         datas
             .par_chunks(width)
             .map(|chunk| {
-                let values = chunk.to_vec();
-                let commitment: Commitment = Commitment::new(&prover_params, &values).unwrap();
+                let values = Self::pad_values(chunk.to_vec(), width);
+                let commitment = Commitment::new(prover_params, &values).unwrap();
                 VerkleNode {
                     commitment,
                     values,
-                    children: None,
                 }
             })
             .collect()
     }
 
-    fn build_from_nodes(prover_params: &ProverParams, nodes: &[VerkleNode], width: usize) -> Vec<VerkleNode> {
+    fn build_from_nodes(prover_params: &ProverParams<E>, nodes: &[VerkleNode<E>], width: usize) -> Vec<VerkleNode<E>> {
         nodes
         .par_chunks(width)
             .map(|chunk| {
@@ -89,25 +128,120 @@ impl VerkleTree {
                     .par_iter()
                     .map(|node| Self::map_commitment_to_vec_u8(&node.commitment))
                     .collect();
-                let commitment: Commitment = Commitment::new(&prover_params, &values).unwrap();
+                let values = Self::pad_values(values, width);
+                let commitment = Commitment::new(prover_params, &values).unwrap();
                 VerkleNode {
                     commitment,
                     values,
-                    children: Some(chunk.to_vec()),
                 }
             })
             .collect()
     }
 
-    fn build_tree_recursively(prover_params: &ProverParams, nodes: &[VerkleNode], width: usize) -> VerkleNode {
-        if nodes.len() == 1 {
-            return nodes[0].clone();
+    // every node commits to `width` values, also with parameters for more values,
+    // the last node of a level is padded with empty values if it has less children
+    fn pad_values(mut values: Vec<Vec<u8>>, width: usize) -> Vec<Vec<u8>> {
+        values.resize(width, Vec::new());
+        values
+    }
+
+/* The next functions update the tree and keep track of its versions  */
+
+    /*  Sets the value at `index`. Only the nodes on the path to the index are changed, starting at
+        the leaf every node updates its commitment with the changed value with Commitment::update,
+        which changes the value opened in its parent. Pages that are shared with a snapshot are
+        copied first, the snapshot keeps the old nodes.
+    */
+    pub fn update(&mut self, index: usize, value: Vec<u8>) -> Result<(), VerkleTreeError> {
+        if index >= self.leaf_count {
+            return Err(VerkleTreeError::UpdateError);
+        }
+        let (mut node_index, mut position) = NodeIndex::of_leaf(index, self.width, self.depth);
+        let mut value = value;
+        loop {
+            let node = self.nodes.get_mut(&node_index).ok_or(VerkleTreeError::UpdateError)?;
+            node.commitment
+                .update(self.params.prover(), position, &node.values[position], &value)
+                .map_err(|_| VerkleTreeError::UpdateError)?;
+            node.values[position] = value;
+            match node_index.parent(self.width) {
+                Some(parent) => {
+                    value = Self::map_commitment_to_vec_u8(&node.commitment);
+                    position = node_index.position(self.width);
+                    node_index = parent;
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Retains the current state as version `self.version()` and returns a handle to it.
+    /// The following updates belong to the next version. The handle is copy on write, updating it
+    /// does not change the retained version or the tree.
+    pub fn snapshot(&mut self) -> Self {
+        self.versions.insert(self.version, self.nodes.clone());
+        let handle = self.handle(self.version, self.nodes.clone());
+        self.version += 1;
+        handle
+    }
+
+    /// The version the next snapshot gets, or for a handle the version it points to.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// The versions that are retained, from old to new.
+    pub fn retained_versions(&self) -> Vec<u64> {
+        self.versions.keys().copied().collect()
+    }
+
+    /// A handle to a retained version, proofs can be generated against it as for any other tree.
+    /// As for `snapshot`, updates to the handle are not seen by the retained version.
+    pub fn at_version(&self, version: u64) -> Option<Self> {
+        let nodes = self.versions.get(&version)?;
+        Some(self.handle(version, nodes.clone()))
+    }
+
+    /// Drops every retained version except the last `keep_last`.
+    /// The nodes that are only used by dropped versions are freed once no handle uses them.
+    pub fn prune(&mut self, keep_last: usize) {
+        while self.versions.len() > keep_last {
+            self.versions.pop_first();
+        }
+    }
+
+    fn handle(&self, version: u64, nodes: Arena<VerkleNode<E>>) -> Self {
+        VerkleTree {
+            nodes,
+            width: self.width,
+            leaf_count: self.leaf_count,
+            depth: self.depth,
+            params: self.params.clone(),
+            version,
+            versions: BTreeMap::new(),
         }
-        let next_level = Self::build_from_nodes(prover_params, nodes, width);
-        Self::build_tree_recursively(prover_params, &next_level, width)
     }
 
-    pub fn generate_proof(&self, index: usize, data: &Vec<u8>) -> Result<VerkleProof, VerkleTreeError> {
+    /// The value at `index`.
+    pub fn get(&self, index: usize) -> Option<&Vec<u8>> {
+        if index >= self.leaf_count {
+            return None;
+        }
+        let (leaf, position) = NodeIndex::of_leaf(index, self.width, self.depth());
+        Some(&self.find_node(&leaf).values[position])
+    }
+
+    /// The value at `index` with a proof for it.
+    pub fn prove(&self, index: usize) -> Result<(Vec<u8>, VerkleProof<E>), VerkleTreeError> {
+        let value = self.get(index).ok_or(VerkleTreeError::ProofGenerateError)?.clone();
+        let proof = self.generate_proof(index, &value)?;
+        Ok((value, proof))
+    }
+
+    pub fn generate_proof(&self, index: usize, data: &Vec<u8>) -> Result<VerkleProof<E>, VerkleTreeError> {
+        if index >= self.leaf_count {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
         let mut node_positions = Vec::<usize>::new();
         let mut value_positions = Vec::<usize>::new();
 
@@ -126,28 +260,24 @@ impl VerkleTree {
         node_positions.reverse();
         value_positions.reverse();
 
-        let mut current_node = self.root.clone().unwrap();
-
-        let mut proofs = Vec::<ProofNode>::new();
-        for (i, &_node_position) in node_positions.iter().enumerate() {
-            let current_commitment = current_node.commitment.clone();
-            let current_values = current_node.values.clone();
+        let mut proofs = Vec::<ProofNode<E>>::new();
+        for (i, &node_position) in node_positions.iter().enumerate() {
+            let node_index = NodeIndex { level: i, offset: node_position };
+            let current_node = self.find_node(&node_index);
             let node_to_prove_position = value_positions[i];
-            let data_to_prove = if let Some(children) = current_node.children {
-                let next_node = children[node_to_prove_position].clone();
-                let next_node_commitment = next_node.commitment.clone();
-                current_node = next_node.clone();
-                Self::map_commitment_to_vec_u8(&next_node_commitment)
+            let data_to_prove = if i < depth {
+                let next_node = self.find_node(&node_index.child(self.width, node_to_prove_position));
+                Self::map_commitment_to_vec_u8(&next_node.commitment)
             } else {
                 data.to_vec()
             };
 
             //let points: Vec<(usize, Vec<u8>)> = vec![(node_to_prove_position, data_to_prove)];
-            let proof = Proof::new(&self.pp, &current_values, index);
+            let proof = Proof::new(self.params.prover(), &current_node.values, node_to_prove_position);
             match proof {
                 Ok(proof) => {
                     proofs.push(ProofNode {
-                        commitment: current_commitment,
+                        commitment: current_node.commitment.clone(),
                         proof,
                         indices: vec![node_to_prove_position],
                         values: vec![data_to_prove],
@@ -162,142 +292,54 @@ impl VerkleTree {
 
 /* The next functions are to generate proofs for several indices simultaeusly  */
 
-    /*  This function returns a long vector which reads the nodes from top to bottom left to right
-        Each index contains either a proof of some children, or a None value
+    /*  This function opens every node on the paths from the root to the given indices.
+        Each opened node is stored under its NodeIndex, the proof of a node is one aggregated
+        proof for all children on these paths. The tree does not need to be full.
     */
-    pub fn generate_batch_proof (&self, index: Vec<usize>, data: &Vec<Vec<u8>>) -> Vec<Option<ProofNode>> {
-        assert!(data.len() % self.width == 0, "Please give a tree that is compeletly filled, i.e. log_{{width}}(data) is a natural number");
-        assert!(!index.is_empty(), "Please give a non empty index");
-        let width = self.width;
-        let depth = self.depth();
-        // The following line creates a vector, on each index is a vector which incidates which children nodes need to be proven
-        let index_for_proof = VerkleTree::create_index_for_proof(index, width, self.depth());
-        
-        let proofs: Vec<Option<ProofNode>> = (0.. index_for_proof.len())
-        .into_par_iter()
-        .map(|ind| {
-            if index_for_proof[ind]!= vec![]  {
-                let path = Self::path_to_child(ind, width);
-
-                let node: VerkleNode = Self::find_commitment_node(self, path);
-                let index_first_child: usize =
-                    if node.children.is_none(){
-                        let index_first_child_data: usize = 
-                            if width == 2 {
-                                usize::pow(width, (depth+1).try_into().unwrap())/(width-1) -1
-                            }
-                            else {
-                                usize::pow(width, (depth+1).try_into().unwrap())/(width-1)
-                            };
-                        width*ind +1 - index_first_child_data
-                    }
-                    else {
-                        width*ind +1
-                    };
-                let proof_of_node = self.find_proof_node(node,  index_for_proof[ind].clone(), data, index_first_child).expect("failed to generate proof for node");
-                Some(proof_of_node)
-            }
-            else {
-                None
-            }
-        }).collect();
-        proofs
+    pub fn prove_batch(&self, indices: &[usize]) -> Result<(Vec<Vec<u8>>, BatchProof<E>), VerkleTreeError> {
+        if indices.is_empty() || indices.iter().any(|&i| i >= self.leaf_count) {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
+        // The following line creates a map, for each node to open it contains the children that need to be proven
+        let index_for_proof = create_index_for_proof(indices, self.width, self.depth());
+        let nodes = self.open_nodes(&index_for_proof)?;
+        let values = indices.iter().map(|&index| self.get(index).unwrap().clone()).collect();
+        Ok((values, BatchProof { nodes }))
     }
 
-    fn path_to_child (mut index: usize, width: usize) -> Vec<usize> {
-        let mut path : Vec<usize> = Vec::new();
-        if index == 0 {
-            return path;
-        }
-        else {
-            path.push((index-1) % width); //push current node index
-            while index > width{
-                index = (index-1)/width; //compute parent
-                path.push((index-1) % width); //push parent
-            }
-        }
-        path.reverse();
-        path
-
-    }
-
-    fn create_index_for_proof(index: Vec<usize>, width: usize, depth: usize) -> Vec<Vec<usize>> {
-
-        /* This creates a vector of the form:
-            [[2, 0], [1, 2], [], [0]] 
-            The root is on index 0 and children [2,0] need to be proven.
-            etc.
-            */
-        let mut tree_path: Vec<Vec<usize>>  = Vec::new();
-        let mut indexes = index.clone();
-        for level in 1.. (depth+1){
-            let data_level_above = width.pow((depth+1-level) as u32);
-            // This creates for each parent node an empty vector
-            let mut level_above: Vec<Vec<usize>> = vec![vec![]; data_level_above];
-            // This is a hash set to only insert if the node is "new"
-            let mut new_indices: HashSet<usize> = HashSet::new();
-            /* The loop adds the index of the child that needs to be proven in the vector of the parent node
-                This is done modulus the width of the tree. 
-                Also the loop creates a vector for the indices of the parent node for the next layer*/
-            for i in 0.. indexes.len(){
-                level_above[indexes[i] / width].push(indexes[i]% width);
-                new_indices.insert(indexes[i]/ width);
-            }
-            level_above.reverse();
-            for parent in level_above{
-                tree_path.push(parent);
-            }
-            indexes = new_indices.into_iter().collect();
-                
-        }
-        // We need to add the root manually
-        if depth != 0 {
-            let mut node_root: Vec<usize> = Vec::new();
-            for child in 0.. width{
-                if !tree_path[tree_path.len()- child-1].is_empty(){
-                    node_root.push(child);
-                }
-            }
-            tree_path.push( node_root);
-        }
-        else {
-            // If the tree has 1 layer, the "root" is just the commitment of all indices
-            tree_path = vec![index];
+    /// As `prove_batch`, fails if `data` does not hold the values of the tree at `index`.
+    pub fn generate_batch_proof (&self, index: Vec<usize>, data: &[Vec<u8>]) -> Result<BatchProof<E>, VerkleTreeError> {
+        let (values, proof) = self.prove_batch(&index)?;
+        if index.iter().zip(values).any(|(&i, value)| data.get(i) != Some(&value)) {
+            return Err(VerkleTreeError::ProofGenerateError);
         }
-        tree_path.reverse();
-        tree_path
+        Ok(proof)
     }
-    
-    fn find_commitment_node(&self, path: Vec<usize>) -> VerkleNode{
-        let mut current_node = self.root.clone().unwrap();
-        for i in path {
-            current_node = current_node.children.expect("failed to find children")[i].clone();
-        }
-        current_node
+
+    // Opens the given positions of every node in the map.
+    fn open_nodes(&self, index_for_proof: &BTreeMap<NodeIndex, Vec<usize>>) -> Result<BTreeMap<NodeIndex, ProofNode<E>>, VerkleTreeError> {
+        index_for_proof
+        .par_iter()
+        .map(|(node_index, positions)| {
+            let proof_of_node = self.find_proof_node(self.find_node(node_index), positions)?;
+            Ok((*node_index, proof_of_node))
+        }).collect()
     }
 
-    fn find_proof_node (&self, node: VerkleNode, indices_to_proof: Vec<usize>, data: &Vec<Vec<u8>>, index_first_child: usize) ->  Result<ProofNode, VerkleTreeError>  {
-        //let mut indices: Vec<usize> = Vec::new();
-        let mut values: Vec<Vec<u8>> = Vec::new();
-        if  let Some(child) = node.children {
-            for child_node in child {
-                let com = child_node.commitment;
-                let child_commitment = Self::map_commitment_to_vec_u8(&com);
-                values.push(child_commitment);
-            }
-        }
-        else {
-            for ind in 0 .. self.width {
-                values.push(data[index_first_child + ind].clone());
-            }
-        }
-        let proof = Proof::batch_new_aggregated(&self.pp, &node.commitment, &values, &indices_to_proof);
+    fn find_node(&self, node_index: &NodeIndex) -> &VerkleNode<E> {
+        self.nodes.get(node_index).expect("failed to find node")
+    }
+
+    fn find_proof_node (&self, node: &VerkleNode<E>, indices_to_proof: &[usize]) ->  Result<ProofNode<E>, VerkleTreeError>  {
+        // The opened values are the child commitments, or the data for leaf nodes
+        let values: Vec<Vec<u8>> = indices_to_proof.iter().map(|&ind| node.values[ind].clone()).collect();
+        let proof = Proof::batch_new_aggregated(self.params.prover(), &node.commitment, &node.values, indices_to_proof);
         match proof {
             Ok(proof) => {
                 let proof_node = ProofNode {
-                    commitment: node.commitment,
+                    commitment: node.commitment.clone(),
                     proof,
-                    indices: indices_to_proof,
+                    indices: indices_to_proof.to_vec(),
                     values,
                 };
                 Ok(proof_node)
@@ -306,92 +348,293 @@ impl VerkleTree {
         }
     }
 
-    // This function computes batch proofs, is also works if the NONE values are already deleted.
-    pub fn batch_proof_verify (root: Commitment, mut tree_proofs: Vec<Option<ProofNode>>, width: usize, indices: Vec<usize>, depth: usize, data: Vec<Vec<u8>>) -> bool {
-        assert!(tree_proofs[0].is_some());
-
+    /*  Verifies a batch proof for the values `data` at `indices`.
+        The opened nodes have to be exactly the nodes on the paths to the indices, every opened child
+        has to be the commitment of the next opened node and the opened leaf values have to be `data`.
+        `params` are the parameters the tree was built with.
+    */
+    pub fn batch_proof_verify(root: Commitment<E>, tree_proofs: &BatchProof<E>, width: usize, indices: &[usize], depth: usize, data: &[Vec<u8>], params: &TreeParams<E>) -> bool {
+        if indices.len() != data.len() || width > params.max_width() {
+            return false;
+        }
         // Check if the root is correct
-        if root != tree_proofs[0].as_ref().unwrap().commitment {
-            println!("Root commitment is not correct");
+        if !tree_proofs.nodes.get(&NodeIndex::root()).is_some_and(|node| node.commitment == root) {
             return false;
         }
 
-        // Check if the proofs are of the correct size, also works if NONE values were already deleted
-        tree_proofs.retain(|node| node.is_some());
-        // The expected length
-        let mut check_vector: Vec<Vec<usize>> = Self::create_index_for_proof(indices, width, depth);
-        check_vector.retain(|vector| !vector.is_empty());
-        if tree_proofs.len() != check_vector.len() {
-            println!("The tree proofs vector is not of the correct length");
+        // Check if the proof opens exactly the nodes and children on the paths to the indices
+        let check_vector = create_index_for_proof(indices, width, depth);
+        if tree_proofs.nodes.len() != check_vector.len() {
             return false;
         }
-        // To find the commitment value easier, we dont save the ProofNodes but the commitments in the next vector
-        let mut commitments_vector: Vec<Vec<u8>> = tree_proofs.iter().map(|proof_node|
-            {
-                let node = proof_node.as_ref().unwrap();
-                Self::map_commitment_to_vec_u8(&node.commitment)
+        let mut values: HashMap<usize, &Vec<u8>> = HashMap::new();
+        for (index, value) in indices.iter().zip(data) {
+            if *values.entry(*index).or_insert(value) != value {
+                return false;
             }
-        ).collect();
-        data.iter().for_each(|d| commitments_vector.push(d.to_vec()));
-        
-        let (_, verifier_params) =
-            paramgen_from_seed("This is our Favourite very very long Seed", 0, width).unwrap();
-        tree_proofs.par_iter().all(|proof_node| {
-            if let Some(node) = proof_node{
-                let b1 = Proof::same_commit_batch_verify(&node.proof, &verifier_params, &node.commitment, &node.indices, &node.values);
-                // For simplicity we check if there is a commitment that matches
-                let b2 = node.values.par_iter().all(|point| {
-                    commitments_vector.par_iter().any(|n| n == point)
-                });
-                b1 & b2
+        }
+
+        let verifier_params = params.verifier();
+        check_vector.par_iter().all(|(node_index, positions)| {
+            let node = match tree_proofs.nodes.get(node_index) {
+                Some(node) => node,
+                None => return false,
+            };
+            if node.indices != *positions || node.values.len() != positions.len() {
+                return false;
             }
-            else {
-                true
+            let expected_values = positions.iter().zip(&node.values).all(|(&position, value)| {
+                if node_index.level == depth {
+                    values[&(node_index.offset * width + position)] == value
+                } else {
+                    match tree_proofs.nodes.get(&node_index.child(width, position)) {
+                        Some(child) => Self::map_commitment_to_vec_u8(&child.commitment) == *value,
+                        None => false,
+                    }
+                }
+            });
+            expected_values
+                && Proof::same_commit_batch_verify(&node.proof, verifier_params, &node.commitment, &node.indices, &node.values)
+        })
+    }
+
+/* The next functions are to generate proofs for a range of indices  */
+
+    /*  Proves all values in `range`. The nodes that are partly covered by the range open
+        every child (or value) that overlaps with the range, the nodes below them that are
+        completely covered are not opened since the verifier can recommit them from the values.
+    */
+    pub fn prove_range(&self, range: Range<usize>) -> Result<RangeProof<E>, VerkleTreeError> {
+        if range.is_empty() || range.end > self.leaf_count {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
+        let index_for_proof = create_index_for_range(&range, self.width, self.depth(), self.leaf_count);
+        let nodes = self.open_nodes(&index_for_proof)?;
+        let values = self.iter_range(range).map(|(_, value)| value.clone()).collect();
+        Ok(RangeProof { nodes, values })
+    }
+
+    /// As `prove_range`, fails if `data` does not hold the values of the tree in `range`.
+    pub fn generate_range_proof(&self, range: Range<usize>, data: &[Vec<u8>]) -> Result<RangeProof<E>, VerkleTreeError> {
+        let proof = self.prove_range(range.clone())?;
+        if data.get(range) != Some(&proof.values[..]) {
+            return Err(VerkleTreeError::ProofGenerateError);
+        }
+        Ok(proof)
+    }
+
+    /*  Verifies a range proof of a tree with `leaf_count` values and returns the values in `range`.
+        The opened nodes have to be exactly the nodes partly covered by the range, every opened child
+        is either the next opened node or a covered subtree that is recommitted from the values.
+        `params` are the parameters the tree was built with.
+    */
+    pub fn verify_range_proof(root: Commitment<E>, range_proof: &RangeProof<E>, width: usize, leaf_count: usize, range: Range<usize>, params: &TreeParams<E>) -> Option<Vec<Vec<u8>>> {
+        if range.is_empty() || range.end > leaf_count || range_proof.values.len() != range.len() || width > params.max_width() {
+            return None;
+        }
+        let depth = level_sizes(leaf_count, width).len() - 1;
+        // the prover parameters are needed to recommit the covered subtrees, without the precomputed
+        // tables since those are not checked when the parameters are read
+        let (prover_params, verifier_params) = (&params.prover().without_precomputation(), params.verifier());
+        let values = &range_proof.values;
+        let covered_commitment = |node_index: NodeIndex| {
+            let leaves = node_index.leaf_range(width, depth, leaf_count);
+            let subtree_values = values[leaves.start - range.start..leaves.end - range.start].to_vec();
+            Self::commit_subtree(prover_params, &subtree_values, width, depth - node_index.level)
+        };
+
+        let check_vector = create_index_for_range(&range, width, depth, leaf_count);
+        if check_vector.is_empty() {
+            // the range covers the whole tree
+            let valid = range_proof.nodes.is_empty() && covered_commitment(NodeIndex::root()) == root;
+            return valid.then(|| values.clone());
+        }
+        if !range_proof.nodes.get(&NodeIndex::root()).is_some_and(|node| node.commitment == root) {
+            return None;
+        }
+        if range_proof.nodes.len() != check_vector.len() {
+            return None;
+        }
+
+        let valid = check_vector.par_iter().all(|(node_index, positions)| {
+            let node = match range_proof.nodes.get(node_index) {
+                Some(node) => node,
+                None => return false,
+            };
+            if node.indices != *positions || node.values.len() != positions.len() {
+                return false;
             }
+            let expected_values = positions.iter().zip(&node.values).all(|(&position, value)| {
+                if node_index.level == depth {
+                    values[node_index.offset * width + position - range.start] == *value
+                } else {
+                    let child = node_index.child(width, position);
+                    if check_vector.contains_key(&child) {
+                        match range_proof.nodes.get(&child) {
+                            Some(child) => Self::map_commitment_to_vec_u8(&child.commitment) == *value,
+                            None => false,
+                        }
+                    } else {
+                        Self::map_commitment_to_vec_u8(&covered_commitment(child)) == *value
+                    }
+                }
+            });
+            expected_values
+                && Proof::same_commit_batch_verify(&node.proof, verifier_params, &node.commitment, &node.indices, &node.values)
         });
-        true
+        valid.then(|| values.clone())
     }
 
+    // Commits to the values of a subtree with `height` levels above its leaf nodes.
+    fn commit_subtree(prover_params: &ProverParams<E>, values: &Vec<Vec<u8>>, width: usize, height: usize) -> Commitment<E> {
+        let mut nodes = Self::create_leaf_nodes(prover_params, values, width);
+        for _ in 0..height {
+            nodes = Self::build_from_nodes(prover_params, &nodes, width);
+        }
+        nodes[0].commitment.clone()
+    }
 
-    pub fn verify_proof(root: Commitment, verkle_proof: &VerkleProof, width: usize) -> bool {
-        if verkle_proof.proofs[0].commitment != root {
+    /*  Checks that `verkle_proof` opens `value` at `index` in the tree with root `root`.
+        The proof has one node per level, every node opens the commitment of the next one at the
+        position of the path to `index`, and the leaf node opens `value`.
+        `params` are the parameters the tree was built with.
+    */
+    pub fn verify_proof(root: Commitment<E>, verkle_proof: &VerkleProof<E>, width: usize, depth: usize, index: usize, value: &[u8], params: &TreeParams<E>) -> bool {
+        let proofs = &verkle_proof.proofs;
+        if width < 2 || width > params.max_width() || proofs.len() != depth + 1 || proofs[0].commitment != root {
             return false;
         }
-         let (_, verifier_params) =
-            paramgen_from_seed("This is our Favourite very very long Seed", 0, width).unwrap();
-        let verkle_proofs = verkle_proof.proofs.clone();
-        for node in verkle_proofs {
-            if !Proof::verify(&node.proof, &verifier_params, &node.commitment, &node.values[0], node.indices[0]){
-                return  false;
+        let mut position = index;
+        for (level, node) in proofs.iter().enumerate().rev() {
+            let expected = if level == depth { value.to_vec() } else { Self::map_commitment_to_vec_u8(&proofs[level + 1].commitment) };
+            if node.indices != [position % width] || node.values != [expected] {
+                return false;
             }
+            position /= width;
         }
-        true
+        position == 0
+            && proofs.iter().all(|node| Proof::verify(&node.proof, params.verifier(), &node.commitment, &node.values[0], node.indices[0]))
     }
 
-    pub fn map_commitment_to_vec_u8(com: &Commitment) -> Vec<u8> {
-        let mut old_commitment_bytes: Vec<u8> = vec![];
-        com.serialize(&mut old_commitment_bytes, true).unwrap();
-        old_commitment_bytes
-        //Vec::new()
+    /// The encoded commitment, `PointEncoding::ENCODED_LEN` bytes.
+    pub fn map_commitment_to_vec_u8(com: &Commitment<E>) -> Vec<u8> {
+        com.encode()
     }
 
     pub fn depth(&self) -> usize {
-        let mut depth = 0;
+        self.depth
+    }
+
+    /// The parameters the tree was built with, they verify its proofs.
+    pub fn params(&self) -> &Arc<TreeParams<E>> {
+        &self.params
+    }
 
-        let mut current_node = self.root.clone().unwrap(); // TODO: error handling
-        while current_node.children.is_some() {
-            depth += 1;
-            current_node = current_node.children.unwrap()[0].clone();
+    /// The values of the tree with their index.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Vec<u8>)> + '_ {
+        self.iter_range(0..self.leaf_count)
+    }
+
+    /// The values with an index in `range`, the values are borrowed from the leaf nodes.
+    pub fn iter_range(&self, range: Range<usize>) -> impl Iterator<Item = (usize, &Vec<u8>)> + '_ {
+        let range = range.start.min(self.leaf_count)..range.end.min(self.leaf_count);
+        let width = self.width;
+        let depth = self.depth();
+        (range.start / width..range.end.div_ceil(width)).flat_map(move |offset| {
+            let node = self.find_node(&NodeIndex { level: depth, offset });
+            let first_index = offset * width;
+            (first_index.max(range.start)..(first_index + width).min(range.end))
+                .map(move |index| (index, &node.values[index - first_index]))
+        })
+    }
+
+    /// Calls `visitor` for every node, parents before their children and children from left to right,
+    /// with the positions of the children to follow from the root, the commitment and whether the node is a leaf.
+    pub fn walk<V: FnMut(&[usize], &Commitment<E>, bool)>(&self, mut visitor: V) {
+        self.walk_node(NodeIndex::root(), &mut Vec::new(), &mut visitor);
+    }
+
+    fn walk_node<V: FnMut(&[usize], &Commitment<E>, bool)>(&self, node_index: NodeIndex, path: &mut Vec<usize>, visitor: &mut V) {
+        visitor(path, &self.find_node(&node_index).commitment, node_index.level == self.depth);
+        if node_index.level < self.depth {
+            for position in 0..node_index.child_count(self.width, self.depth, self.leaf_count) {
+                path.push(position);
+                self.walk_node(node_index.child(self.width, position), path, visitor);
+                path.pop();
+            }
         }
-        depth
     }
 
-    pub fn root_commitment(&self) -> Option<Commitment> {
+    pub fn stats(&self) -> TreeStats {
+        // the arena pages and the values the nodes hold
+        let memory_usage = self.nodes.page_memory_usage()
+            + self.nodes.iter().map(|(_, node)| {
+                node.values.capacity() * std::mem::size_of::<Vec<u8>>()
+                    + node.values.iter().map(|value| value.capacity()).sum::<usize>()
+            }).sum::<usize>();
+        TreeStats::new(self.width, self.depth, self.leaf_count, self.nodes.len(), memory_usage)
+    }
+
+    pub fn root_commitment(&self) -> Option<Commitment<E>> {
         // match &self.root {
         //     None => None,
         //     Some(verkle_node) => Some(verkle_node.commitment),
         // }
-        self.root.as_ref().map(|verkle_node| verkle_node.commitment.clone())
+        self.nodes.get(&NodeIndex::root()).map(|verkle_node| verkle_node.commitment.clone())
+    }
+}
+
+impl<E: PointproofsCurve> ProofNode<E> {
+    /// The commitment and the proof are counted with their serialized length.
+    /// Every index is a u64 and every value carries a u32 length prefix.
+    pub fn size(&self) -> ProofSize {
+        ProofSize {
+            commitments: Commitment::<E>::ENCODED_LEN,
+            opening_proofs: Proof::<E>::ENCODED_LEN,
+            values: self.values.iter().map(|value| value.len()).sum(),
+            index_metadata: self.indices.len() * INDEX_LEN + self.values.len() * VALUE_LEN_PREFIX,
+        }
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.size().total()
+    }
+}
+
+impl<E: PointproofsCurve> VerkleProof<E> {
+    /// The size of the proof per level, starting at the root.
+    pub fn size_breakdown(&self) -> Vec<ProofSize> {
+        self.proofs.iter().map(|proof| proof.size()).collect()
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.proofs.iter().map(|proof| proof.size_in_bytes()).sum()
+    }
+}
+
+impl<E: PointproofsCurve> BatchProof<E> {
+    /// The size of the proof per level, starting at the root.
+    pub fn size_breakdown(&self) -> Vec<ProofSize> {
+        let mut levels: Vec<ProofSize> = Vec::new();
+        for (node_index, node) in &self.nodes {
+            if levels.len() <= node_index.level {
+                levels.resize(node_index.level + 1, ProofSize::default());
+            }
+            levels[node_index.level] += node.size();
+        }
+        levels
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.nodes.values().map(|node| node.size_in_bytes()).sum()
+    }
+}
+
+impl<E: PointproofsCurve> RangeProof<E> {
+    /// The opened nodes and the values of the range, the values are needed to recompute the covered subtrees.
+    pub fn size_in_bytes(&self) -> usize {
+        let values: usize = self.values.iter().map(|value| value.len() + VALUE_LEN_PREFIX).sum();
+        self.nodes.values().map(|node| node.size_in_bytes()).sum::<usize>() + values
     }
 }
 
@@ -400,4 +643,7 @@ pub enum VerkleTreeError {
     BuildError,
     ProofGenerateError,
     EmptyTree,
+    UpdateError,
+    ParamError,
+    DecodeError,
 }
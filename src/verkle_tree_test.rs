@@ -1,13 +1,27 @@
 #[cfg(test)]
 mod tests {
 
-    use crate::VerkleTree;
+    use crate::{diff, diff_with_proof, level_sizes, verify_diff, LeafChange, LoopbackTransport, NodeIndex, PartialTree, PartialTree_point, PointEncoding, Precomputation, ProofSize, TreeBuilder, TreeParams, COMMIT_LEN, PROOF_LEN, VerkleProof, VerkleTree, VerkleTree_point, WitnessRecorder};
+    use crate::{BatchProof_point, Commitment, ProofNode_point, RangeProof_point, VerkleProof_point};
+    use crate::{ark_pointproofs, evm, Kzg, Opening, PointproofsCurve};
+    use crate::{seed_from_env, SyncClient, SyncError, SyncRequest, SyncResponse, SyncServer, SyncTarget, SyncTransport};
+    use std::sync::Arc;
     use ark_bls12_381::Fr as F;
-    use rand::Rng;
+    use rand::rngs::StdRng;
+    use rand::seq::IteratorRandom;
+    use rand::{Rng, SeedableRng};
+
+    /// A random number generator seeded with `seed_from_env`, the seed is printed so a
+    /// failing test can be repeated by setting `VERKLE_SEED`.
+    fn seeded_rng() -> StdRng {
+        let seed = seed_from_env();
+        println!("{}={}", crate::SEED_VAR, seed);
+        StdRng::seed_from_u64(seed)
+    }
 
     #[test]
     fn test_build_tree() {
-        let (tree, _, _) = build_verkle_tree();
+        let (tree, _, _) = build_verkle_tree(&mut seeded_rng());
         assert!(
             tree.root_commitment().is_some(),
             "Failed building verkle tree"
@@ -16,8 +30,8 @@ mod tests {
 
     #[test]
     fn test_generate_proof() {
-        let (tree, datas, _) = build_verkle_tree();
-        let mut rng = rand::thread_rng();
+        let mut rng = seeded_rng();
+        let (tree, datas, _) = build_verkle_tree(&mut rng);
         let ranom_index = rng.gen_range(0..=datas.len()-1);
         let random_point = datas[ranom_index];
         let proof = tree.generate_proof(ranom_index, &random_point);
@@ -26,8 +40,8 @@ mod tests {
 
     #[test]
     fn test_generate_invalid_proof() {
-        let (tree, datas, _) = build_verkle_tree();
-        let mut rng = rand::thread_rng();
+        let mut rng = seeded_rng();
+        let (tree, datas, _) = build_verkle_tree(&mut rng);
         let ranom_index = rng.gen_range(0..=datas.len()-1);
         let fake_point = F::from(rng.gen_range(-100..=100));
         let proof = tree.generate_proof(ranom_index, &fake_point);
@@ -39,51 +53,1425 @@ mod tests {
 
     #[test]
     fn test_verify_proof() {
-        let (tree, datas, width) = build_verkle_tree();
-        let mut rng = rand::thread_rng();
+        let mut rng = seeded_rng();
+        let (tree, datas, width) = build_verkle_tree(&mut rng);
         let ranom_index = rng.gen_range(0..=datas.len()-1);
         let random_point = datas[ranom_index];
         let proof = tree.generate_proof(ranom_index, &random_point).unwrap();
         let root = VerkleTree::root_commitment(&tree).unwrap();
-        let verification = VerkleTree::verify_proof(root, &proof, width);
+        let verification = VerkleTree::verify_proof(root, &proof, width, tree.depth(), ranom_index, random_point, tree.kzg());
 
         assert!(verification, "Given point should generate a valid proof");
     }
 
     #[test]
     fn test_invalid_proof_verification() {
-        let (tree, _, _) = build_verkle_tree();
-        let (invalid_tree, datas, width) = build_verkle_tree();
-        let mut rng = rand::thread_rng();
+        let mut rng = seeded_rng();
+        let (tree, _, _) = build_verkle_tree(&mut rng);
+        let (invalid_tree, datas, width) = build_verkle_tree(&mut rng);
         let ranom_index = rng.gen_range(0..=datas.len()-1);
         let random_point = datas[ranom_index];
         let proof = invalid_tree.generate_proof(ranom_index, &random_point);
         let root = VerkleTree::root_commitment(&tree).unwrap();
-        let verification = VerkleTree::verify_proof(root,&proof.unwrap(), width);
+        let verification = VerkleTree::verify_proof(root,&proof.unwrap(), width, tree.depth(), ranom_index, random_point, tree.kzg());
 
         assert_eq!(verification, false, "Should not accept invalid proof");
     }
 
-    fn build_verkle_tree() -> (VerkleTree, Vec<F>, usize) {
+    #[test]
+    fn test_verify_proof_checks_path() {
+        let (tree, datas, width) = build_small_verkle_tree();
+        let root = tree.root_commitment().unwrap();
+        let depth = tree.depth();
+        let proof = tree.generate_proof(37, &datas[37]).unwrap();
+        assert!(VerkleTree::verify_proof(root, &proof, width, depth, 37, datas[37], tree.kzg()));
+        assert!(!VerkleTree::verify_proof(root, &proof, width, depth, 37, datas[38], tree.kzg()));
+        assert!(!VerkleTree::verify_proof(root, &proof, width, depth, 38, datas[37], tree.kzg()));
+        // 37 + 64 has the same digits on the path but one more
+        assert!(!VerkleTree::verify_proof(root, &proof, width, depth, 37 + 64, datas[37], tree.kzg()));
+        assert!(!VerkleTree::verify_proof(root, &proof, width, depth + 1, 37, datas[37], tree.kzg()));
+        let empty: VerkleProof = VerkleProof { proofs: vec![] };
+        assert!(!VerkleTree::verify_proof(root, &empty, width, depth, 37, datas[37], tree.kzg()));
+
+        // every opening of the spliced proof is valid, but the leaf node is not the child opened above it
+        let mut spliced = proof.clone();
+        spliced.proofs[depth] = tree.generate_proof(5, &datas[5]).unwrap().proofs[depth].clone();
+        assert!(!VerkleTree::verify_proof(root, &spliced, width, depth, 37, datas[5], tree.kzg()));
+    }
+
+    // every node opens the position of the child on the path, also for leaves past the first node
+    #[test]
+    fn test_verify_proof_point() {
+        let width = 4;
+        let datas: Vec<Vec<u8>> = (0..64u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let tree = VerkleTree_point::new(&datas, width).unwrap();
+        let root = tree.root_commitment().unwrap();
+        for index in [0, width - 1, width, datas.len() - 1] {
+            let proof = tree.generate_proof(index, &datas[index]).unwrap();
+            assert!(VerkleTree_point::verify_proof(root.clone(), &proof, width, tree.depth(), index, &datas[index], tree.params()));
+        }
+
+        let depth = tree.depth();
+        let proof = tree.generate_proof(37, &datas[37]).unwrap();
+        assert!(!VerkleTree_point::verify_proof(root.clone(), &proof, width, depth, 37, &datas[38], tree.params()));
+        assert!(!VerkleTree_point::verify_proof(root.clone(), &proof, width, depth, 38, &datas[37], tree.params()));
+        assert!(!VerkleTree_point::verify_proof(root.clone(), &proof, width, depth, 37 + 64, &datas[37], tree.params()));
+        let empty = VerkleProof_point { proofs: vec![] };
+        assert!(!VerkleTree_point::verify_proof(root.clone(), &empty, width, depth, 37, &datas[37], tree.params()));
+        let mut spliced = proof.clone();
+        spliced.proofs[depth] = tree.generate_proof(5, &datas[5]).unwrap().proofs[depth].clone();
+        assert!(!VerkleTree_point::verify_proof(root.clone(), &spliced, width, depth, 37, &datas[5], tree.params()));
+        // parameters for less than `width` values
+        assert!(!VerkleTree_point::verify_proof(root, &proof, width, depth, 37, &datas[37], &TreeParams::new(width - 1).unwrap()));
+    }
+
+    #[test]
+    fn test_proof_size() {
+        let (tree, datas, _) = build_small_verkle_tree();
+        let proof = tree.generate_proof(37, &datas[37]).unwrap();
+        let breakdown = proof.size_breakdown();
+        assert_eq!(breakdown.len(), tree.depth() + 1);
+        assert_eq!(breakdown.iter().map(|level| level.total()).sum::<usize>(), proof.size_in_bytes());
+        // every level opens a single (index, value) pair
+        let level = ProofSize { commitments: 48, opening_proofs: 48, values: 32, index_metadata: 32 };
+        assert!(breakdown.iter().all(|size| *size == level));
+    }
+
+    #[test]
+    fn test_batch_proof_size() {
+        let (tree, datas, _) = build_small_verkle_tree();
+        let proof = tree.generate_batch_proof(vec![0, 1, 63], &datas).unwrap();
+        let breakdown = proof.size_breakdown();
+        assert_eq!(breakdown.len(), tree.depth() + 1);
+        // the root opens 2 children, the level below 2 nodes and the leaves 2 nodes with 3 values
+        assert_eq!(breakdown[0].values, 2 * 32);
+        assert_eq!(breakdown[1].commitments, 2 * 48);
+        assert_eq!(breakdown[2].values, 3 * 32);
+        let total: usize = breakdown.iter().map(|level| level.total()).sum();
+        assert_eq!(total, proof.size_in_bytes());
+    }
+
+    #[test]
+    fn test_point_commitment_len() {
+        // a ciphersuite byte and a compressed point, in G2 with group_switched
+        if cfg!(feature = "group_switched") {
+            assert_eq!((COMMIT_LEN, PROOF_LEN), (97, 97));
+            assert_eq!(<ark_pointproofs::Commitment<ark_bn254::Bn254> as PointEncoding>::ENCODED_LEN, 65);
+        } else {
+            assert_eq!((COMMIT_LEN, PROOF_LEN), (49, 49));
+            assert_eq!(<ark_pointproofs::Commitment<ark_bn254::Bn254> as PointEncoding>::ENCODED_LEN, 33);
+        }
+
+        let datas: Vec<Vec<u8>> = (0..20u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let tree = VerkleTree_point::new(&datas, 4).unwrap();
+        let root = tree.root_commitment().unwrap();
+        assert_eq!(VerkleTree_point::map_commitment_to_vec_u8(&root).len(), COMMIT_LEN);
+        let (values, proof) = tree.prove_batch(&[0, 19]).unwrap();
+        // the root opens the commitments of two children
+        let root_size = proof.size_breakdown()[0];
+        assert_eq!((root_size.commitments, root_size.opening_proofs, root_size.values), (COMMIT_LEN, PROOF_LEN, 2 * COMMIT_LEN));
+        assert!(VerkleTree_point::batch_proof_verify(root, &proof, 4, &[0, 19], tree.depth(), &values, tree.params()));
+    }
+
+    #[test]
+    fn test_tree_stats() {
+        let (tree, datas, width) = build_small_verkle_tree();
+        let stats = tree.stats();
+        assert_eq!(stats.width, width);
+        assert_eq!(stats.depth, 2);
+        assert_eq!(stats.leaf_count, datas.len());
+        assert_eq!(stats.node_count, 16 + 4 + 1);
+        assert_eq!(stats.fill_ratio, 1.0);
+        assert!(stats.memory_usage > 0);
+
+        let datas_point: Vec<Vec<u8>> = (0..64u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let tree_point = VerkleTree_point::new(&datas_point, 8).unwrap();
+        let stats = tree_point.stats();
+        assert_eq!(stats.depth, 1);
+        assert_eq!(stats.leaf_count, 64);
+        assert_eq!(stats.node_count, 8 + 1);
+        assert_eq!(stats.fill_ratio, 1.0);
+    }
+
+    fn build_small_verkle_tree() -> (VerkleTree, Vec<F>, usize) {
+        let width: usize = 4;
+        let datas: Vec<F> = (0..64u32).map(|i| F::from(i * 7 + 1)).collect();
+        let tree = VerkleTree::new(&datas, width).unwrap();
+        (tree, datas, width)
+    }
+
+    fn build_verkle_tree(rng: &mut StdRng) -> (VerkleTree, Vec<F>, usize) {
         let mut datas: Vec<F> = Vec::new();
         let width: usize = 6;
         for _i in 0..i32::pow(width as i32, 5){
-            datas.push(F::from(rand::thread_rng().gen_range(0..=u32::pow(10, 8))));
+            datas.push(F::from(rng.gen_range(0..=u32::pow(10, 8))));
         }
         let tree = VerkleTree::new(&datas, width).unwrap();
         (tree, datas, width)
     }
 
-    /*
     #[test]
     fn test_batch_proof_verify() {
-        let (tree, datas, width) = build_verkle_tree();
-        let indices: Vec<usize> = (0..=(datas.len()-1) as usize).choose_multiple(
-            &mut thread_rng(),((datas.len() as f64) *0.2 )as usize);
-        let proof = tree.generate_batch_proof(indices, &datas);
+        let (tree, datas, width) = build_small_verkle_tree();
+        let indices: Vec<usize> = (0..datas.len()).choose_multiple(
+            &mut seeded_rng(),((datas.len() as f64) *0.2 )as usize);
+        let values: Vec<F> = indices.iter().map(|&i| datas[i]).collect();
+        let proof = tree.generate_batch_proof(indices.clone(), &datas).unwrap();
         let root = VerkleTree::root_commitment(&tree).unwrap();
-        let verification = VerkleTree::verify_batch_proof(root, proof, width);
+        let verification = VerkleTree::batch_proof_verify(root, &proof, width, &indices, tree.depth(), &values, tree.kzg());
         assert!(verification, "Given point should generate a valid proof");
     }
-     */
+
+    #[test]
+    fn test_batch_proof_partial_tree() {
+        // widths that are not powers of two and leaf counts that leave the last node of every level ragged
+        for (width, leaves) in [(3, 50), (5, 31), (7, 7), (2, 9)] {
+            let datas: Vec<F> = (0..leaves as u32).map(|i| F::from(i + 11)).collect();
+            let tree = VerkleTree::new(&datas, width).unwrap();
+            let root = tree.root_commitment().unwrap();
+            let indices = vec![leaves - 1, 0, leaves / 2, leaves - 1];
+            let values: Vec<F> = indices.iter().map(|&i| datas[i]).collect();
+            let proof = tree.generate_batch_proof(indices.clone(), &datas).unwrap();
+            assert!(VerkleTree::batch_proof_verify(root, &proof, width, &indices, tree.depth(), &values, tree.kzg()));
+
+            let mut wrong_values = values.clone();
+            wrong_values[1] = F::from(1000u32);
+            assert!(!VerkleTree::batch_proof_verify(root, &proof, width, &indices, tree.depth(), &wrong_values, tree.kzg()));
+            assert!(!VerkleTree::batch_proof_verify(root, &proof, width, &indices[..2], tree.depth(), &values[..2], tree.kzg()));
+        }
+        let (tree, datas, _) = build_small_verkle_tree();
+        assert!(tree.generate_batch_proof(vec![datas.len()], &datas).is_err());
+    }
+
+    #[test]
+    fn test_batch_proof_point_partial_tree() {
+        for (width, leaves) in [(3, 50), (6, 40)] {
+            let datas: Vec<Vec<u8>> = (0..leaves as u32).map(|i| i.to_le_bytes().to_vec()).collect();
+            let tree = VerkleTree_point::new(&datas, width).unwrap();
+            let root = tree.root_commitment().unwrap();
+            let indices = vec![0, 1, leaves - 1];
+            let values: Vec<Vec<u8>> = indices.iter().map(|&i| datas[i].clone()).collect();
+            let proof = tree.generate_batch_proof(indices.clone(), &datas).unwrap();
+            assert!(VerkleTree_point::batch_proof_verify(root.clone(), &proof, width, &indices, tree.depth(), &values, tree.params()));
+
+            let mut wrong_values = values.clone();
+            wrong_values[2] = vec![1, 2, 3];
+            assert!(!VerkleTree_point::batch_proof_verify(root, &proof, width, &indices, tree.depth(), &wrong_values, tree.params()));
+        }
+    }
+
+    #[test]
+    fn test_level_sizes() {
+        assert_eq!(level_sizes(64, 4), vec![1, 4, 16]);
+        assert_eq!(level_sizes(50, 3), vec![1, 2, 6, 17]);
+        assert_eq!(level_sizes(3, 8), vec![1]);
+        let node = NodeIndex { level: 3, offset: 13 };
+        assert_eq!(node.path(3), vec![1, 1, 1]);
+        assert_eq!(node.parent(3), Some(NodeIndex { level: 2, offset: 4 }));
+        assert_eq!(node.parent(3).unwrap().child(3, node.position(3)), node);
+    }
+
+    #[test]
+    fn test_range_proof() {
+        for (width, leaves) in [(4, 64), (3, 50), (5, 31), (2, 9)] {
+            let datas: Vec<F> = (0..leaves as u32).map(|i| F::from(i * 3 + 5)).collect();
+            let tree = VerkleTree::new(&datas, width).unwrap();
+            let root = tree.root_commitment().unwrap();
+            for range in [0..leaves, 0..1, leaves - 1..leaves, 1..leaves - 1, width..leaves / 2 + 1] {
+                let proof = tree.generate_range_proof(range.clone(), &datas).unwrap();
+                let values = VerkleTree::verify_range_proof(root, &proof, width, leaves, range.clone(), tree.kzg());
+                assert_eq!(values.as_deref(), Some(&datas[range.clone()]));
+
+                let mut wrong_proof = proof.clone();
+                wrong_proof.values[0] = F::from(1000u32);
+                assert!(VerkleTree::verify_range_proof(root, &wrong_proof, width, leaves, range.clone(), tree.kzg()).is_none());
+                assert!(VerkleTree::verify_range_proof(root, &proof, width, leaves, range.start..range.end + 1, tree.kzg()).is_none());
+            }
+        }
+        let (tree, datas, _) = build_small_verkle_tree();
+        assert!(tree.generate_range_proof(10..10, &datas).is_err());
+        assert!(tree.generate_range_proof(10..65, &datas).is_err());
+    }
+
+    #[test]
+    fn test_range_proof_opens_only_borders() {
+        let (tree, datas, width) = build_small_verkle_tree();
+        // 16..48 covers two subtrees below the root completely
+        let proof = tree.generate_range_proof(16..48, &datas).unwrap();
+        assert_eq!(proof.nodes.keys().copied().collect::<Vec<_>>(), vec![NodeIndex::root()]);
+        assert_eq!(proof.nodes[&NodeIndex::root()].point.len(), 2);
+        // 5..48 opens the leaf node holding 4..8 and its parent
+        let proof = tree.generate_range_proof(5..48, &datas).unwrap();
+        assert_eq!(proof.nodes.len(), 3);
+        assert_eq!(proof.nodes[&NodeIndex { level: 2, offset: 1 }].point.len(), 3);
+        let root = tree.root_commitment().unwrap();
+        assert!(VerkleTree::verify_range_proof(root, &proof, width, datas.len(), 5..48, tree.kzg()).is_some());
+    }
+
+    #[test]
+    fn test_range_proof_point() {
+        for (width, leaves) in [(3, 50), (8, 64)] {
+            let datas: Vec<Vec<u8>> = (0..leaves as u32).map(|i| i.to_le_bytes().to_vec()).collect();
+            let tree = VerkleTree_point::new(&datas, width).unwrap();
+            let root = tree.root_commitment().unwrap();
+            for range in [0..leaves, 2..leaves - 1, leaves - 1..leaves] {
+                let proof = tree.generate_range_proof(range.clone(), &datas).unwrap();
+                let values = VerkleTree_point::verify_range_proof(root.clone(), &proof, width, leaves, range.clone(), tree.params());
+                assert_eq!(values.as_deref(), Some(&datas[range.clone()]));
+
+                let mut wrong_proof = proof.clone();
+                wrong_proof.values[0] = vec![1, 2, 3];
+                assert!(VerkleTree_point::verify_range_proof(root.clone(), &wrong_proof, width, leaves, range, tree.params()).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_witness() {
+        for (width, leaves) in [(4, 64), (3, 50), (5, 23)] {
+            let datas: Vec<F> = (0..leaves as u32).map(|i| F::from(i * 13 + 2)).collect();
+            let tree = VerkleTree::new(&datas, width).unwrap();
+            let pre_root = tree.root_commitment().unwrap();
+
+            let mut recorder = WitnessRecorder::new(&tree);
+            assert_eq!(recorder.read(3), Some(datas[3]));
+            assert_eq!(recorder.write(leaves - 1, F::from(7u32)), Some(datas[leaves - 1]));
+            assert_eq!(recorder.write(leaves - 1, F::from(8u32)), Some(F::from(7u32)));
+            assert_eq!(recorder.write(leaves / 2, F::from(9u32)), Some(datas[leaves / 2]));
+            assert_eq!(recorder.read(leaves / 2), Some(F::from(9u32)));
+            assert_eq!(recorder.read(leaves), None);
+
+            let witness = recorder.witness().unwrap();
+            assert_eq!(witness.indices, vec![3, leaves / 2, leaves - 1]);
+            assert!(witness.verify(pre_root, tree.kzg()));
+
+            let writes: Vec<(usize, F)> = recorder.writes().iter().map(|(&index, &value)| (index, value)).collect();
+            let mut post_datas = datas.clone();
+            for &(index, value) in &writes {
+                post_datas[index] = value;
+            }
+            let post_tree = VerkleTree::new(&post_datas, width).unwrap();
+            assert_eq!(witness.post_state_root(pre_root, &writes, tree.kzg().clone()), post_tree.root_commitment());
+            assert_eq!(witness.post_state_root(pre_root, &[], tree.kzg().clone()), Some(pre_root));
+            assert_eq!(witness.post_state_root(pre_root, &[(0, F::from(1u32))], tree.kzg().clone()), None);
+            // a witness with other pre-state values is rejected before any write is applied
+            let mut forged = witness.clone();
+            forged.pre_values[0] += F::from(1u32);
+            forged.proof.nodes.last_entry().unwrap().get_mut().point[0].1 += F::from(1u32);
+            assert!(!forged.verify(pre_root, tree.kzg()));
+            assert_eq!(forged.post_state_root(pre_root, &writes, tree.kzg().clone()), None);
+            assert_eq!(witness.post_state_root(post_tree.root_commitment().unwrap(), &writes, tree.kzg().clone()), None);
+        }
+    }
+
+    #[test]
+    fn test_partial_tree() {
+        for (width, leaves) in [(4, 64), (3, 50), (6, 6)] {
+            let datas: Vec<F> = (0..leaves as u32).map(|i| F::from(i + 100)).collect();
+            let tree = VerkleTree::new(&datas, width).unwrap();
+            let root = tree.root_commitment().unwrap();
+            let indices = vec![0, 1, leaves / 2, leaves - 1];
+            let values: Vec<F> = indices.iter().map(|&i| datas[i]).collect();
+            let proof = tree.generate_batch_proof(indices.clone(), &datas).unwrap();
+
+            let mut wrong_values = values.clone();
+            wrong_values[0] = F::from(1u32);
+            assert!(PartialTree::new(root, &proof, width, leaves, &indices, &wrong_values, tree.kzg().clone()).is_none());
+
+            let mut partial_tree = PartialTree::new(root, &proof, width, leaves, &indices, &values, tree.kzg().clone()).unwrap();
+            assert_eq!(partial_tree.get(1), Some(datas[1]));
+            assert_eq!(partial_tree.get(2), None);
+            assert!(partial_tree.update(2, F::from(5u32)).is_err());
+            assert_eq!(partial_tree.root_commitment(), Some(root));
+
+            partial_tree.update(0, F::from(5u32)).unwrap();
+            partial_tree.update_batch(&[(leaves - 1, F::from(6u32)), (leaves / 2, F::from(7u32))]).unwrap();
+            let mut post_datas = datas.clone();
+            post_datas[0] = F::from(5u32);
+            post_datas[leaves - 1] = F::from(6u32);
+            post_datas[leaves / 2] = F::from(7u32);
+            let post_tree = VerkleTree::new(&post_datas, width).unwrap();
+            assert_eq!(partial_tree.root_commitment(), post_tree.root_commitment());
+        }
+    }
+
+    #[test]
+    fn test_partial_tree_point() {
+        for (width, leaves) in [(4, 64), (3, 50)] {
+            let datas: Vec<Vec<u8>> = (0..leaves as u32).map(|i| i.to_le_bytes().to_vec()).collect();
+            let tree = VerkleTree_point::new(&datas, width).unwrap();
+            let root = tree.root_commitment().unwrap();
+            let indices = vec![0, leaves / 2, leaves - 1];
+            let values: Vec<Vec<u8>> = indices.iter().map(|&i| datas[i].clone()).collect();
+            let proof = tree.generate_batch_proof(indices.clone(), &datas).unwrap();
+
+            let mut partial_tree = PartialTree_point::new(root, &proof, width, leaves, &indices, &values, tree.params().clone()).unwrap();
+            assert_eq!(partial_tree.get(leaves / 2), Some(&datas[leaves / 2]));
+            assert!(partial_tree.update(1, vec![1]).is_err());
+            partial_tree.update_batch(&[(0, vec![9, 9]), (leaves - 1, vec![])]).unwrap();
+
+            let mut post_datas = datas.clone();
+            post_datas[0] = vec![9, 9];
+            post_datas[leaves - 1] = vec![];
+            let post_tree = VerkleTree_point::new(&post_datas, width).unwrap();
+            assert!(partial_tree.root_commitment() == post_tree.root_commitment());
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let (mut tree, mut datas, width) = build_small_verkle_tree();
+        for (index, value) in [(0, 3u32), (37, 4), (63, 5), (37, 6)] {
+            tree.update(index, F::from(value)).unwrap();
+            datas[index] = F::from(value);
+        }
+        let rebuilt = VerkleTree::new(&datas, width).unwrap();
+        assert_eq!(tree.root_commitment(), rebuilt.root_commitment());
+        assert!(tree.update(64, F::from(1u32)).is_err());
+
+        let proof = tree.generate_proof(37, &datas[37]).unwrap();
+        assert!(VerkleTree::verify_proof(tree.root_commitment().unwrap(), &proof, width, tree.depth(), 37, datas[37], tree.kzg()));
+
+        let datas: Vec<F> = (0..50u32).map(F::from).collect();
+        let mut tree = VerkleTree::new(&datas, 3).unwrap();
+        tree.update(49, F::from(7u32)).unwrap();
+        let mut new_datas = datas.clone();
+        new_datas[49] = F::from(7u32);
+        assert_eq!(tree.root_commitment(), VerkleTree::new(&new_datas, 3).unwrap().root_commitment());
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let (mut tree, datas, width) = build_small_verkle_tree();
+        let root_0 = tree.root_commitment().unwrap();
+        let snapshot_0 = tree.snapshot();
+        assert_eq!(snapshot_0.version(), 0);
+
+        let mut datas_1 = datas.clone();
+        datas_1[5] = F::from(1000u32);
+        tree.update(5, datas_1[5]).unwrap();
+        let root_1 = tree.root_commitment().unwrap();
+        let snapshot_1 = tree.snapshot();
+        tree.update(6, F::from(1001u32)).unwrap();
+
+        assert_eq!(snapshot_0.root_commitment(), Some(root_0));
+        assert_eq!(tree.retained_versions(), vec![0, 1]);
+        let version_0 = tree.at_version(0).unwrap();
+        let proof = version_0.generate_proof(5, &datas[5]).unwrap();
+        assert!(VerkleTree::verify_proof(root_0, &proof, width, 2, 5, datas[5], tree.kzg()));
+        let proof = tree.at_version(1).unwrap().generate_batch_proof(vec![5, 6], &datas_1).unwrap();
+        assert!(VerkleTree::batch_proof_verify(root_1, &proof, width, &[5, 6], 2, &[datas_1[5], datas_1[6]], tree.kzg()));
+        assert_eq!(snapshot_1.root_commitment(), Some(root_1));
+        assert_ne!(tree.root_commitment(), Some(root_1));
+
+        tree.prune(1);
+        assert_eq!(tree.retained_versions(), vec![1]);
+        assert!(tree.at_version(0).is_none());
+        // handles stay valid after their version is pruned
+        assert!(snapshot_0.generate_proof(5, &datas[5]).is_ok());
+
+        // updating a handle copies the nodes it shares
+        let mut version_1 = tree.at_version(1).unwrap();
+        version_1.update(7, F::from(1002u32)).unwrap();
+        assert_ne!(version_1.root_commitment(), Some(root_1));
+        assert_eq!(tree.at_version(1).unwrap().root_commitment(), Some(root_1));
+        assert_eq!(snapshot_1.root_commitment(), Some(root_1));
+    }
+
+    #[test]
+    fn test_update_point() {
+        for (width, leaves) in [(4, 64), (3, 50)] {
+            let mut datas: Vec<Vec<u8>> = (0..leaves as u32).map(|i| i.to_le_bytes().to_vec()).collect();
+            let mut tree = VerkleTree_point::new(&datas, width).unwrap();
+            for (index, value) in [(0, vec![3]), (37, vec![4, 4]), (leaves - 1, vec![]), (37, vec![6])] {
+                tree.update(index, value.clone()).unwrap();
+                datas[index] = value;
+            }
+            let rebuilt = VerkleTree_point::new(&datas, width).unwrap();
+            assert!(tree.root_commitment() == rebuilt.root_commitment());
+            assert_eq!(tree.get(37), Some(&vec![6]));
+            assert!(tree.update(leaves, vec![1]).is_err());
+
+            let (value, proof) = tree.prove(37).unwrap();
+            assert!(VerkleTree_point::verify_proof(tree.root_commitment().unwrap(), &proof, width, tree.depth(), 37, &value, tree.params()));
+        }
+    }
+
+    #[test]
+    fn test_snapshots_point() {
+        let width = 4;
+        let datas: Vec<Vec<u8>> = (0..64u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let mut tree = VerkleTree_point::new(&datas, width).unwrap();
+        let root_0 = tree.root_commitment().unwrap();
+        let snapshot_0 = tree.snapshot();
+        assert_eq!(snapshot_0.version(), 0);
+
+        let mut datas_1 = datas.clone();
+        datas_1[5] = vec![10, 0];
+        tree.update(5, datas_1[5].clone()).unwrap();
+        let root_1 = tree.root_commitment().unwrap();
+        let snapshot_1 = tree.snapshot();
+        tree.update(6, vec![10, 1]).unwrap();
+
+        assert!(snapshot_0.root_commitment() == Some(root_0.clone()));
+        assert_eq!(tree.retained_versions(), vec![0, 1]);
+        let (value, proof) = tree.at_version(0).unwrap().prove(5).unwrap();
+        assert_eq!(value, datas[5]);
+        assert!(VerkleTree_point::verify_proof(root_0, &proof, width, 2, 5, &value, tree.params()));
+        let (values, proof) = tree.at_version(1).unwrap().prove_batch(&[5, 6]).unwrap();
+        assert_eq!(values, vec![datas_1[5].clone(), datas_1[6].clone()]);
+        assert!(VerkleTree_point::batch_proof_verify(root_1.clone(), &proof, width, &[5, 6], 2, &values, tree.params()));
+        assert!(snapshot_1.root_commitment() == Some(root_1.clone()));
+        assert!(tree.root_commitment() != Some(root_1.clone()));
+
+        tree.prune(1);
+        assert_eq!(tree.retained_versions(), vec![1]);
+        assert!(tree.at_version(0).is_none());
+        // handles stay valid after their version is pruned
+        assert!(snapshot_0.prove(5).is_ok());
+
+        // updating a handle copies the nodes it shares
+        let mut version_1 = tree.at_version(1).unwrap();
+        version_1.update(7, vec![10, 2]).unwrap();
+        assert!(version_1.root_commitment() != Some(root_1.clone()));
+        assert!(tree.at_version(1).unwrap().root_commitment() == Some(root_1.clone()));
+        assert!(snapshot_1.root_commitment() == Some(root_1));
+    }
+
+    #[test]
+    fn test_diff() {
+        let datas: Vec<F> = (0..50u32).map(|i| F::from(i * 2)).collect();
+        let width = 3;
+        let mut tree = VerkleTree::new(&datas, width).unwrap();
+        let old_tree = tree.snapshot();
+        assert!(diff(&old_tree, &tree).unwrap().is_empty());
+
+        tree.update(49, F::from(1u32)).unwrap();
+        tree.update(3, F::from(2u32)).unwrap();
+        tree.update(4, F::from(3u32)).unwrap();
+        tree.update(20, datas[20]).unwrap();
+        let changes = diff(&old_tree, &tree).unwrap();
+        assert_eq!(changes, vec![
+            LeafChange { index: 3, old: datas[3], new: F::from(2u32) },
+            LeafChange { index: 4, old: datas[4], new: F::from(3u32) },
+            LeafChange { index: 49, old: datas[49], new: F::from(1u32) },
+        ]);
+
+        let old_root = old_tree.root_commitment().unwrap();
+        let new_root = tree.root_commitment().unwrap();
+        let diff_proof = diff_with_proof(&old_tree, &tree).unwrap();
+        assert!(verify_diff(old_root, new_root, &diff_proof, width, datas.len(), tree.kzg().clone()));
+        assert!(!verify_diff(new_root, old_root, &diff_proof, width, datas.len(), tree.kzg().clone()));
+
+        // leaving out a change gives a different root
+        let mut incomplete = diff_with_proof(&old_tree, &tree).unwrap();
+        incomplete.changes.pop();
+        assert!(!verify_diff(old_root, new_root, &incomplete, width, datas.len(), tree.kzg().clone()));
+
+        let other = VerkleTree::new(&datas[..49].to_vec(), width).unwrap();
+        assert!(diff(&old_tree, &other).is_err());
+    }
+
+    // Fails every request after the first `requests`.
+    struct InterruptedTransport<'a> {
+        inner: LoopbackTransport<'a>,
+        requests: usize,
+    }
+
+    impl SyncTransport for InterruptedTransport<'_> {
+        fn request(&mut self, request: SyncRequest) -> Result<SyncResponse, SyncError> {
+            if self.requests == 0 {
+                return Err(SyncError::TransportError("connection closed".to_string()));
+            }
+            self.requests -= 1;
+            self.inner.request(request)
+        }
+    }
+
+    #[test]
+    fn test_sync() {
+        let datas: Vec<F> = (0..50u32).map(|i| F::from(i * 5 + 1)).collect();
+        let width = 3;
+        let tree = VerkleTree::new(&datas, width).unwrap();
+        let server = SyncServer::new(&tree);
+        let target = SyncTarget { root: tree.root_commitment().unwrap(), width, leaf_count: datas.len() };
+
+        let mut client = SyncClient::new(target, 7, tree.kzg().clone());
+        let mut transport = InterruptedTransport { inner: LoopbackTransport::new(&server), requests: 3 };
+        assert!(client.run(&mut transport).is_err());
+        assert_eq!(client.values(), &datas[..21]);
+
+        // resume from the stored values
+        let mut client = SyncClient::resume(target, 7, client.values().to_vec(), tree.kzg().clone());
+        client.run(&mut LoopbackTransport::new(&server)).unwrap();
+        assert!(client.next_request().is_none());
+        let (synced, synced_datas) = client.finish().unwrap();
+        assert_eq!(synced_datas, datas);
+        assert_eq!(synced.root_commitment(), tree.root_commitment());
+
+        assert!(matches!(SyncServer::new(&tree).handle(SyncRequest::GetRange { root: target.root, range: 0..51 }), SyncResponse::NotFound));
+        // a server with other values is rejected
+        let mut other_datas = datas.clone();
+        other_datas[10] = F::from(0u32);
+        let other_tree = VerkleTree::new(&other_datas, width).unwrap();
+        let mut client = SyncClient::new(target, 16, tree.kzg().clone());
+        assert_eq!(client.next_request(), Some(SyncRequest::GetRange { root: target.root, range: 0..16 }));
+        let proof = other_tree.generate_range_proof(0..16, &other_datas).unwrap();
+        let response = SyncResponse::Range { range: 0..16, proof };
+        assert_eq!(client.handle_response(response), Err(SyncError::InvalidProof));
+        assert!(SyncClient::new(target, 16, tree.kzg().clone()).finish().is_err());
+    }
+
+    #[test]
+    fn test_iter() {
+        let datas: Vec<F> = (0..50u32).map(|i| F::from(i * 3 + 1)).collect();
+        let tree = VerkleTree::new(&datas, 3).unwrap();
+        assert_eq!(tree.iter().collect::<Vec<_>>(), datas.iter().copied().enumerate().collect::<Vec<_>>());
+        assert_eq!(tree.iter_range(10..14).collect::<Vec<_>>(), (10..14).map(|i| (i, datas[i])).collect::<Vec<_>>());
+        assert_eq!(tree.iter_range(48..60).map(|(i, _)| i).collect::<Vec<_>>(), vec![48, 49]);
+        assert_eq!(tree.iter_range(7..7).count(), 0);
+
+        let datas_point: Vec<Vec<u8>> = (0..50u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let tree_point = VerkleTree_point::new(&datas_point, 4).unwrap();
+        assert_eq!(tree_point.iter().collect::<Vec<_>>(), datas_point.iter().enumerate().collect::<Vec<_>>());
+        assert_eq!(tree_point.iter_range(47..50).map(|(_, value)| value.clone()).collect::<Vec<_>>(), datas_point[47..].to_vec());
+    }
+
+    #[test]
+    fn test_walk() {
+        let datas: Vec<F> = (0..50u32).map(F::from).collect();
+        let tree = VerkleTree::new(&datas, 3).unwrap();
+        let mut paths = Vec::new();
+        let mut leaves = 0;
+        tree.walk(|path, commitment, is_leaf| {
+            if path.is_empty() {
+                assert_eq!(Some(*commitment), tree.root_commitment());
+            }
+            leaves += is_leaf as usize;
+            paths.push(path.to_vec());
+        });
+        assert_eq!(paths.len(), tree.stats().node_count);
+        assert_eq!(leaves, 17);
+        assert_eq!(paths[..3], [vec![], vec![0], vec![0, 0]]);
+        assert!(paths.iter().all(|path| path.len() <= tree.depth()));
+
+        let datas_point: Vec<Vec<u8>> = (0..16u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let tree_point = VerkleTree_point::new(&datas_point, 4).unwrap();
+        let mut leaf_paths = Vec::new();
+        tree_point.walk(|path, _, is_leaf| if is_leaf { leaf_paths.push(path.to_vec()) });
+        assert_eq!(leaf_paths, (0..4).map(|i| vec![i]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_get_and_prove() {
+        let (tree, datas, width) = build_small_verkle_tree();
+        let root = tree.root_commitment().unwrap();
+        assert_eq!(tree.get(17), Some(datas[17]));
+        assert_eq!(tree.get(64), None);
+        let (value, proof) = tree.prove(17).unwrap();
+        assert_eq!(value, datas[17]);
+        assert!(VerkleTree::verify_proof(root, &proof, width, tree.depth(), 17, value, tree.kzg()));
+        assert!(tree.prove(64).is_err());
+
+        let indices = [63, 2, 17];
+        let (values, proof) = tree.prove_batch(&indices).unwrap();
+        assert_eq!(values, vec![datas[63], datas[2], datas[17]]);
+        assert!(VerkleTree::batch_proof_verify(root, &proof, width, &indices, tree.depth(), &values, tree.kzg()));
+        let mut wrong_datas = datas.clone();
+        wrong_datas[2] = F::from(0u32);
+        assert!(tree.generate_batch_proof(indices.to_vec(), &wrong_datas).is_err());
+        assert!(tree.generate_range_proof(0..4, &wrong_datas).is_err());
+
+        let datas_point: Vec<Vec<u8>> = (0..50u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let tree_point = VerkleTree_point::new(&datas_point, 3).unwrap();
+        let root_point = tree_point.root_commitment().unwrap();
+        assert_eq!(tree_point.get(49), Some(&datas_point[49]));
+        let (value, proof) = tree_point.prove(49).unwrap();
+        assert_eq!(value, datas_point[49]);
+        assert!(VerkleTree_point::verify_proof(root_point.clone(), &proof, 3, tree_point.depth(), 49, &value, tree_point.params()));
+        let (values, proof) = tree_point.prove_batch(&[0, 49]).unwrap();
+        assert!(VerkleTree_point::batch_proof_verify(root_point, &proof, 3, &[0, 49], tree_point.depth(), &values, tree_point.params()));
+    }
+
+    #[test]
+    fn test_tree_builder() {
+        // more values than the builder commits at once, so the levels are committed while pushing
+        let datas: Vec<F> = (0..1100u32).map(F::from).collect();
+        let width = 4;
+        let tree = VerkleTree::new(&datas, width).unwrap();
+
+        let mut levels = Vec::new();
+        let mut builder = TreeBuilder::with_store(width, &mut levels);
+        datas.iter().for_each(|value| builder.push(*value));
+        assert_eq!(builder.leaf_count(), datas.len());
+        assert_eq!(builder.finish().ok(), tree.root_commitment());
+        let mut sizes: Vec<usize> = levels.iter().map(|level| level.len()).collect();
+        sizes.reverse();
+        assert_eq!(sizes, level_sizes(datas.len(), width));
+
+        let streamed = VerkleTree::from_iter(datas.iter().copied(), width).unwrap();
+        assert_eq!(streamed.root_commitment(), tree.root_commitment());
+        assert_eq!(streamed.depth(), tree.depth());
+        assert_eq!(streamed.leaf_count(), datas.len());
+        assert!(streamed.iter().map(|(_, value)| value).eq(datas.iter().copied()));
+        assert_eq!(streamed.stats().node_count, tree.stats().node_count);
+        let (value, proof) = streamed.prove(1099).unwrap();
+        assert_eq!(value, datas[1099]);
+        assert!(VerkleTree::verify_proof(tree.root_commitment().unwrap(), &proof, width, tree.depth(), 1099, value, tree.kzg()));
+
+        let empty = TreeBuilder::new(width);
+        assert!(empty.finish().is_err());
+        let single = VerkleTree::from_iter([F::from(7u32)], width).unwrap();
+        assert_eq!(single.root_commitment(), VerkleTree::new(&vec![F::from(7u32)], width).unwrap().root_commitment());
+    }
+
+    #[test]
+    fn test_seeded_trees_are_reproducible() {
+        // the same seed gives the same data, indices, roots and proofs
+        let build = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let datas: Vec<F> = (0..40).map(|_| F::from(rng.gen::<u64>())).collect();
+            let tree = VerkleTree::new(&datas, 4).unwrap();
+            let indices = (0..datas.len()).choose_multiple(&mut rng, 5);
+            let (_, proof) = tree.prove_batch(&indices).unwrap();
+            (tree.root_commitment(), indices, proof.nodes)
+        };
+        let (root, indices, nodes) = build(7);
+        let (same_root, same_indices, same_nodes) = build(7);
+        assert_eq!(root, same_root);
+        assert_eq!(indices, same_indices);
+        assert!(nodes.iter().zip(&same_nodes).all(|((index, node), (same_index, same_node))| {
+            index == same_index && node.commitment == same_node.commitment && node.proof == same_node.proof && node.point == same_node.point
+        }));
+        assert_ne!(build(8).0, root);
+    }
+
+    #[test]
+    fn test_shared_params_point() {
+        // one parameter set for 16 values is used by trees of width 3 and 4
+        let params = Arc::new(TreeParams::new(16).unwrap());
+        assert_eq!(params.max_width(), 16);
+        let mut bytes = vec![];
+        params.write(&mut bytes).unwrap();
+        assert_eq!(<TreeParams>::read(&mut &bytes[..]).unwrap().max_width(), 16);
+        assert!(TreeParams::new(crate::ciphersuite::MAX_N + 1).is_err());
+
+        for (width, leaves) in [(4, 64), (3, 50)] {
+            let datas: Vec<Vec<u8>> = (0..leaves as u32).map(|i| i.to_le_bytes().to_vec()).collect();
+            let tree = VerkleTree_point::with_params(&datas, width, params.clone()).unwrap();
+            let root = tree.root_commitment().unwrap();
+            // the nodes are committed to the same powers, so the root does not depend on n
+            assert!(Some(root.clone()) == VerkleTree_point::new(&datas, width).unwrap().root_commitment());
+
+            let (value, proof) = tree.prove(leaves - 1).unwrap();
+            assert!(VerkleTree_point::verify_proof(root.clone(), &proof, width, tree.depth(), leaves - 1, &value, &params));
+            // the proofs depend on n
+            assert!(!VerkleTree_point::verify_proof(root.clone(), &proof, width, tree.depth(), leaves - 1, &value, &TreeParams::new(width).unwrap()));
+
+            let indices = vec![0, leaves / 2, leaves - 1];
+            let (values, proof) = tree.prove_batch(&indices).unwrap();
+            assert!(VerkleTree_point::batch_proof_verify(root.clone(), &proof, width, &indices, tree.depth(), &values, &params));
+            let mut partial_tree = PartialTree_point::new(root.clone(), &proof, width, leaves, &indices, &values, params.clone()).unwrap();
+            partial_tree.update(0, vec![9, 9]).unwrap();
+            let mut post_datas = datas.clone();
+            post_datas[0] = vec![9, 9];
+            assert!(partial_tree.root_commitment() == VerkleTree_point::new(&post_datas, width).unwrap().root_commitment());
+
+            let proof = tree.prove_range(1..leaves - 1).unwrap();
+            let values = VerkleTree_point::verify_range_proof(root, &proof, width, leaves, 1..leaves - 1, &params);
+            assert_eq!(values.as_deref(), Some(&datas[1..leaves - 1]));
+        }
+        assert!(VerkleTree_point::with_params(&vec![vec![1]], 17, params).is_err());
+    }
+
+    #[test]
+    fn test_point_tree_bn254() {
+        use ark_bn254::Bn254;
+
+        let width = 4;
+        let leaves = 30;
+        let datas: Vec<Vec<u8>> = (0..leaves as u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let params = Arc::new(TreeParams::<Bn254>::from_seed(crate::DEFAULT_SEED, width).unwrap());
+        let mut bytes = vec![];
+        params.write(&mut bytes).unwrap();
+        assert_eq!(TreeParams::<Bn254>::read(&mut &bytes[..]).unwrap().prover(), params.prover());
+        assert!(TreeParams::<Bn254>::read(&mut &bytes[..bytes.len() - 1]).is_err());
+
+        let tree = VerkleTree_point::with_params(&datas, width, params.clone()).unwrap();
+        let root = tree.root_commitment().unwrap();
+        assert_eq!(root.encode().len(), if cfg!(feature = "group_switched") { 65 } else { 33 });
+        let (value, proof) = tree.prove(leaves - 1).unwrap();
+        assert!(VerkleTree_point::verify_proof(root.clone(), &proof, width, tree.depth(), leaves - 1, &value, &params));
+        let decoded = VerkleProof_point::<Bn254>::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(VerkleTree_point::verify_proof(root.clone(), &decoded, width, tree.depth(), leaves - 1, &value, &params));
+
+        let indices = vec![0, leaves / 2, leaves - 1];
+        let (values, proof) = tree.prove_batch(&indices).unwrap();
+        assert!(VerkleTree_point::batch_proof_verify(root.clone(), &proof, width, &indices, tree.depth(), &values, &params));
+        let mut partial_tree = PartialTree_point::new(root.clone(), &proof, width, leaves, &indices, &values, params.clone()).unwrap();
+        partial_tree.update(0, vec![9, 9]).unwrap();
+        let mut post_datas = datas.clone();
+        post_datas[0] = vec![9, 9];
+        assert!(partial_tree.root_commitment() == VerkleTree_point::with_params(&post_datas, width, params).unwrap().root_commitment());
+    }
+
+    // the parameters of the ceremony in pointproofs/crs.param
+    #[test]
+    fn test_read_param_point() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/pointproofs/crs.param");
+        let params = Arc::new(TreeParams::read_param(&mut std::fs::File::open(path).unwrap()).unwrap());
+        let width = params.max_width().min(4);
+        let datas: Vec<Vec<u8>> = (0..20u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let tree = VerkleTree_point::with_params(&datas, width, params.clone()).unwrap();
+        let root = tree.root_commitment().unwrap();
+        let (value, proof) = tree.prove(7).unwrap();
+        assert!(VerkleTree_point::verify_proof(root, &proof, width, tree.depth(), 7, &value, &params));
+    }
+
+    // parameters that are not the powers of one α are rejected
+    #[test]
+    fn test_read_inconsistent_params_point() {
+        use ark_bls12_381::Bls12_381;
+
+        let n = 4;
+        let write = |seed: &str| {
+            let (prover_params, verifier_params) = ark_pointproofs::paramgen_from_seed::<Bls12_381, _>(seed, 0, n).unwrap();
+            let mut bytes = vec![];
+            ark_pointproofs::write_params(&prover_params, &verifier_params, &mut bytes).unwrap();
+            bytes
+        };
+        let (bytes, other) = (write(crate::DEFAULT_SEED), write("This is another very very long seed of the tests"));
+        assert!(ark_pointproofs::read_params::<Bls12_381, _>(&mut &bytes[..]).is_ok());
+
+        // | ciphersuite | n | 2n prover points | n verifier points |
+        let point_len = <Bls12_381 as PointproofsCurve>::POINT_LEN;
+        let verifier_start = 5 + 2 * n * point_len;
+        let mixed = [&bytes[..verifier_start], &other[verifier_start..]].concat();
+        assert!(ark_pointproofs::read_params::<Bls12_381, _>(&mut &mixed[..]).is_err());
+        let mut swapped = bytes.clone();
+        let (first, second) = swapped[5..5 + 2 * point_len].split_at_mut(point_len);
+        first.swap_with_slice(second);
+        assert!(ark_pointproofs::read_params::<Bls12_381, _>(&mut &swapped[..]).is_err());
+        // α^{2n} of the other parameters
+        let mut last = bytes.clone();
+        last[verifier_start - point_len..verifier_start].copy_from_slice(&other[verifier_start - point_len..verifier_start]);
+        assert!(ark_pointproofs::read_params::<Bls12_381, _>(&mut &last[..]).is_err());
+
+        // the same for a `pointproofs-paramgen` file, the first two G2 points are swapped
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/pointproofs/crs.param");
+        let mut crs = std::fs::read(path).unwrap();
+        let g2_start = 4 + 15 * 48;
+        let (first, second) = crs[g2_start..g2_start + 2 * 96].split_at_mut(96);
+        first.swap_with_slice(second);
+        assert!(TreeParams::read_param(&mut &crs[..]).is_err());
+    }
+
+    #[test]
+    fn test_precomputed_params_point() {
+        let width = 4;
+        let leaves = 64;
+        let datas: Vec<Vec<u8>> = (0..leaves as u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let root = VerkleTree_point::new(&datas, width).unwrap().root_commitment().unwrap();
+
+        for precomputation in [Precomputation::Precomp3, Precomputation::Precomp256] {
+            let params = TreeParams::new(width).unwrap().with_precomputation(precomputation);
+            assert_eq!(params.precomputation(), precomputation);
+            // the tables are written and read back with the parameters
+            let mut bytes = vec![];
+            params.write(&mut bytes).unwrap();
+            let params = Arc::new(<TreeParams>::read(&mut &bytes[..]).unwrap());
+            assert_eq!(params.precomputation(), precomputation);
+
+            // pre-computation does not change the commitments or the proofs
+            let tree = VerkleTree_point::with_params(&datas, width, params.clone()).unwrap();
+            assert!(tree.root_commitment() == Some(root.clone()));
+            let (value, proof) = tree.prove(leaves / 2).unwrap();
+            assert!(VerkleTree_point::verify_proof(root.clone(), &proof, width, tree.depth(), leaves / 2, &value, &params));
+
+            let indices = vec![0, leaves - 1];
+            let (values, proof) = tree.prove_batch(&indices).unwrap();
+            assert!(VerkleTree_point::batch_proof_verify(root.clone(), &proof, width, &indices, tree.depth(), &values, &params));
+            let mut partial_tree = PartialTree_point::new(root.clone(), &proof, width, leaves, &indices, &values, params.clone()).unwrap();
+            partial_tree.update(leaves - 1, vec![9, 9]).unwrap();
+            let mut post_datas = datas.clone();
+            post_datas[leaves - 1] = vec![9, 9];
+            assert!(partial_tree.root_commitment() == VerkleTree_point::new(&post_datas, width).unwrap().root_commitment());
+        }
+        let params = TreeParams::new(width).unwrap().with_precomputation(Precomputation::Precomp3).with_precomputation(Precomputation::None);
+        assert_eq!(params.precomputation(), Precomputation::None);
+    }
+
+    #[test]
+    fn test_encoding_point() {
+        let width = 4;
+        let leaves = 40;
+        let datas: Vec<Vec<u8>> = (0..leaves as u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        let tree = VerkleTree_point::new(&datas, width).unwrap();
+        let root = tree.root_commitment().unwrap();
+
+        // | ciphersuite | point |, the point converts to arkworks and back
+        let bytes = root.encode();
+        assert_eq!(bytes.len(), COMMIT_LEN);
+        assert_eq!(Commitment::decode(&bytes).unwrap(), root);
+        let (ciphersuite, point) = root.to_ark();
+        assert_eq!(ciphersuite, bytes[0]);
+        assert_eq!(Commitment::from_ark(ciphersuite, &point).unwrap(), root);
+        assert!(<Commitment>::decode(&bytes[1..]).is_err());
+
+        let (value, proof) = tree.prove(leaves - 1).unwrap();
+        let bytes = proof.to_bytes();
+        let decoded = VerkleProof_point::from_bytes(&bytes).unwrap();
+        assert!(VerkleTree_point::verify_proof(root.clone(), &decoded, width, tree.depth(), leaves - 1, &value, tree.params()));
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(<ProofNode_point>::from_bytes(&proof.proofs[0].to_bytes()).unwrap().to_bytes(), proof.proofs[0].to_bytes());
+        // truncated or trailing bytes are rejected
+        assert!(<VerkleProof_point>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(<VerkleProof_point>::from_bytes(&[bytes, vec![0]].concat()).is_err());
+
+        let indices = vec![1, leaves / 2, leaves - 1];
+        let (values, proof) = tree.prove_batch(&indices).unwrap();
+        let decoded = BatchProof_point::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(VerkleTree_point::batch_proof_verify(root.clone(), &decoded, width, &indices, tree.depth(), &values, tree.params()));
+
+        let proof = tree.prove_range(3..leaves - 2).unwrap();
+        let decoded = RangeProof_point::from_bytes(&proof.to_bytes()).unwrap();
+        let values = VerkleTree_point::verify_range_proof(root, &decoded, width, leaves, 3..leaves - 2, tree.params());
+        assert_eq!(values.as_deref(), Some(&datas[3..leaves - 2]));
+    }
+
+    fn check_ark_pointproofs<E: PointproofsCurve>() {
+        let n = 8;
+        let (prover_params, verifier_params) = ark_pointproofs::paramgen_from_seed::<E, _>(crate::DEFAULT_SEED, 0, n).unwrap();
+        let values: Vec<Vec<u8>> = (0..6u8).map(|i| vec![i; 3]).collect();
+        let commitment = ark_pointproofs::Commitment::new(&prover_params, &values).unwrap();
+        for index in 0..values.len() {
+            let proof = ark_pointproofs::Proof::new(&prover_params, &values, index).unwrap();
+            assert!(proof.verify(&verifier_params, &commitment, &values[index], index));
+            assert!(!proof.verify(&verifier_params, &commitment, &values[(index + 1) % values.len()], index));
+        }
+        assert!(ark_pointproofs::Commitment::new(&prover_params, &vec![vec![0u8]; n + 1]).is_err());
+
+        let indices = [0, 2, 5];
+        let sub_values: Vec<&Vec<u8>> = indices.iter().map(|index| &values[*index]).collect();
+        let proof = ark_pointproofs::Proof::batch_new_aggregated(&prover_params, &commitment, &values, &indices).unwrap();
+        assert!(proof.same_commit_batch_verify(&verifier_params, &commitment, &indices, &sub_values));
+        assert!(!proof.same_commit_batch_verify(&verifier_params, &commitment, &indices[..2], &sub_values[..2]));
+
+        // updates give the commitment and proofs of the new values
+        let mut updated = commitment.clone();
+        updated.update(&prover_params, 4, &values[4], &vec![9]).unwrap();
+        let mut proof = ark_pointproofs::Proof::new(&prover_params, &values, 1).unwrap();
+        proof.update(&prover_params, 1, 4, &values[4], &vec![9]).unwrap();
+        let mut post_values = values.clone();
+        post_values[4] = vec![9];
+        assert_eq!(updated, ark_pointproofs::Commitment::new(&prover_params, &post_values).unwrap());
+        assert!(proof.verify(&verifier_params, &updated, &post_values[1], 1));
+
+        let decoded = ark_pointproofs::Commitment::<E>::from_bytes(&commitment.to_bytes()).unwrap();
+        assert_eq!(decoded, commitment);
+        assert!(ark_pointproofs::Commitment::<E>::from_bytes(&commitment.to_bytes()[1..]).is_err());
+
+        // a ciphersuite that is not registered is an error, not a fallback to another hash
+        assert!(ark_pointproofs::hash_to_field::<E>(2, b"value").is_err());
+        assert!(ark_pointproofs::paramgen_from_seed::<E, _>(crate::DEFAULT_SEED, 2, n).is_err());
+
+        // ciphersuite 1 on the same curve
+        let (prover_params, verifier_params) = ark_pointproofs::paramgen_from_seed::<E, _>(crate::DEFAULT_SEED, 1, n).unwrap();
+        let commitment = ark_pointproofs::Commitment::new(&prover_params, &values).unwrap();
+        let proof = ark_pointproofs::Proof::new(&prover_params, &values, 3).unwrap();
+        assert!(proof.verify(&verifier_params, &commitment, &values[3], 3));
+    }
+
+    #[test]
+    fn test_ark_pointproofs() {
+        check_ark_pointproofs::<ark_bls12_381::Bls12_381>();
+        check_ark_pointproofs::<ark_bn254::Bn254>();
+    }
+
+    // expand_message_xmd vectors of RFC 9380, appendix K.1, and the hash_to_field vectors of
+    // ciphersuite 1 in pointproofs/src/test/hashes.rs
+    #[test]
+    fn test_ciphersuite_vectors() {
+        use crate::ciphersuite::*;
+        use std::str::FromStr;
+
+        let hex = |bytes: &[u8]| bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(hex(&expand_message_xmd_sha256(b"", dst, 0x20)), "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235");
+        assert_eq!(hex(&expand_message_xmd_sha256(b"abc", dst, 0x20)), "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615");
+        assert_eq!(
+            hex(&expand_message_xmd_sha256(b"", dst, 0x80)),
+            "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbe\
+             e0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18\
+             eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dc\
+             c541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced"
+        );
+
+        let hash = |input: &[u8]| ark_pointproofs::hash_to_field::<ark_bls12_381::Bls12_381>(CIPHERSUITE_XMD_SHA256, input).unwrap();
+        assert_eq!(hash(b""), F::from_str("21457032588108123539429209975190460550568300993670103029949342587056332582634").unwrap());
+        assert_eq!(hash(b"abc"), F::from_str("51429740120356259001168133691812355506987378820486845347804175943220577054739").unwrap());
+
+        assert!(check_ciphersuite(CIPHERSUITE_SHA512) && check_ciphersuite(CIPHERSUITE_XMD_SHA256));
+        assert!(!check_ciphersuite(2));
+        assert_eq!(hash_to_field_of(CIPHERSUITE_XMD_SHA256), Some(HashToField::XmdSha256(DST_XMD_SHA256)));
+        // the preferred common ciphersuite is picked
+        assert_eq!(supported_ciphersuites(), vec![1, 0]);
+        assert_eq!(negotiate_ciphersuite(&[0, 1]), Some(CIPHERSUITE_XMD_SHA256));
+        assert_eq!(negotiate_ciphersuite(&[0, 5]), Some(CIPHERSUITE_SHA512));
+        assert_eq!(negotiate_ciphersuite(&[5]), None);
+    }
+
+    // openings of p = 1 + 2X + 3X² with τ = 5, the quotients are worked out by hand
+    #[test]
+    fn test_kzg_known_vectors() {
+        use ark_bls12_381::{Bls12_381, G1Affine};
+        use ark_ec::{AffineRepr, CurveGroup};
+        use ark_poly::univariate::DensePolynomial;
+        use ark_poly::DenseUVPolynomial;
+        use ark_serialize::CanonicalSerialize;
+
+        let kzg = Kzg::<Bls12_381>::from_tau(F::from(5u32), 4);
+        let g = |scalar: u64| (G1Affine::generator() * F::from(scalar)).into_affine();
+        // p(0), p(1), p(2) of 1 + X + X²
+        assert_eq!(<Kzg>::vector_to_polynomial(&[F::from(1u32), F::from(3u32), F::from(7u32)]).coeffs, vec![F::from(1u32); 3]);
+
+        // [1]₁ is the generator, in the ZCash compressed encoding
+        let mut generator = Vec::new();
+        kzg.commit_polynomial(&DensePolynomial::from_coefficients_vec(vec![F::from(1u32)])).serialize_compressed(&mut generator).unwrap();
+        assert_eq!(
+            generator.iter().map(|byte| format!("{:02x}", byte)).collect::<String>(),
+            "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+        );
+
+        let p = DensePolynomial::from_coefficients_vec(vec![F::from(1u32), F::from(2u32), F::from(3u32)]);
+        let commitment = kzg.commit_polynomial(&p);
+        assert_eq!(commitment, g(86));
+
+        // q = (p - 17) / (X - 2) = 3X + 8
+        let single = [(F::from(2u32), F::from(17u32))];
+        let proof = kzg.generate_proof(&p, &single).unwrap();
+        assert_eq!(proof, g(23));
+        assert!(kzg.verify_proof(&commitment, &single, &proof));
+        assert!(!kzg.verify_proof(&commitment, &[(F::from(2u32), F::from(18u32))], &proof));
+
+        // I = 11X - 5, Z = X² - 3X + 2 and p - I = 3Z
+        let multi = [(F::from(1u32), F::from(6u32)), (F::from(2u32), F::from(17u32))];
+        let proof = kzg.generate_proof(&p, &multi).unwrap();
+        assert_eq!(proof, g(3));
+        assert!(kzg.verify_proof(&commitment, &multi, &proof));
+        assert!(!kzg.verify_proof(&commitment, &multi[..1], &proof));
+
+        // three points determine p, the quotient is 0
+        let all = [(F::from(0u32), F::from(1u32)), multi[0], multi[1]];
+        let proof = kzg.generate_proof(&p, &all).unwrap();
+        assert!(proof.is_zero());
+        assert!(kzg.verify_proof(&commitment, &all, &proof));
+
+        assert!(kzg.generate_proof(&p, &[(F::from(2u32), F::from(18u32))]).is_err());
+        assert!(kzg.generate_proof(&p, &[multi[0], multi[0]]).is_err());
+        assert!(!kzg.verify_proof(&commitment, &[multi[0], multi[0]], &g(3)));
+        assert!(!kzg.verify_proof(&commitment, &[], &commitment));
+    }
+
+    // a commitment, the points it is opened at and the proof
+    type KzgClaim<E> = (<E as ark_ec::pairing::Pairing>::G1Affine, Vec<(<E as ark_ec::pairing::Pairing>::ScalarField, <E as ark_ec::pairing::Pairing>::ScalarField)>, <E as ark_ec::pairing::Pairing>::G1Affine);
+
+    fn openings<E: ark_ec::pairing::Pairing>(claims: &[KzgClaim<E>]) -> Vec<Opening<'_, E>> {
+        claims.iter().map(|(commitment, points, proof)| Opening { commitment, points, proof }).collect()
+    }
+
+    fn check_kzg_batch_verify<E: ark_ec::pairing::Pairing>(rng: &mut StdRng) {
+        use ark_ec::AffineRepr;
+        use ark_ff::UniformRand;
+        use ark_poly::univariate::DensePolynomial;
+        use ark_poly::{DenseUVPolynomial, Polynomial};
+
+        let kzg = Kzg::<E>::insecure_test_setup(8);
+        let mut claims = Vec::new();
+        for point_count in [1, 3, 1, 5, 2] {
+            let p = DensePolynomial::from_coefficients_vec((0..8).map(|_| E::ScalarField::rand(rng)).collect());
+            let points: Vec<_> = (0..point_count)
+                .map(|_| {
+                    let x = E::ScalarField::rand(rng);
+                    (x, p.evaluate(&x))
+                })
+                .collect();
+            let proof = kzg.generate_proof(&p, &points).unwrap();
+            claims.push((kzg.commit_polynomial(&p), points, proof));
+        }
+        assert!(kzg.batch_verify(&openings::<E>(&claims)));
+        assert!(kzg.batch_verify(&openings::<E>(&claims[1..2])));
+
+        for i in 0..claims.len() {
+            let mut wrong = claims.clone();
+            wrong[i].1[0].1 += E::ScalarField::from(1u32);
+            assert!(!kzg.batch_verify(&openings::<E>(&wrong)));
+        }
+        let mut wrong = claims.clone();
+        wrong[3].2 = wrong[1].2;
+        assert!(!kzg.batch_verify(&openings::<E>(&wrong)));
+
+        // two wrong proofs of the same claim whose errors cancel out in a plain sum
+        let error = E::G1Affine::rand(rng);
+        let (commitment, points, proof) = claims[0].clone();
+        let cancelling = [
+            (commitment, points.clone(), (proof + error).into()),
+            (commitment, points, (proof.into_group() - error).into()),
+        ];
+        assert!(!kzg.batch_verify(&openings::<E>(&cancelling)));
+    }
+
+    #[test]
+    fn test_kzg_batch_verify() {
+        let mut rng = seeded_rng();
+        check_kzg_batch_verify::<ark_bls12_381::Bls12_381>(&mut rng);
+        check_kzg_batch_verify::<ark_bn254::Bn254>(&mut rng);
+    }
+
+    #[test]
+    fn test_kzg_setup() {
+        let mut rng = seeded_rng();
+        let width = 4;
+        let kzg = Kzg::<ark_bls12_381::Bls12_381>::setup(width, &mut rng);
+        let mut bytes = Vec::new();
+        kzg.write(&mut bytes).unwrap();
+        let read = <Kzg>::read(&mut &bytes[..]).unwrap();
+        assert_eq!(read.g2_powers(), kzg.g2_powers());
+        assert_eq!(read.max_coefficients(), width + 2);
+        // powers that are not of one τ, a short input and a setup without [τ]₁ are rejected
+        let mut other = Vec::new();
+        <Kzg>::insecure_test_setup(width).write(&mut other).unwrap();
+        let g1_end = 4 + (width + 2) * 48;
+        let mut mixed = bytes[..g1_end].to_vec();
+        mixed.extend_from_slice(&other[g1_end..]);
+        assert!(<Kzg>::read(&mut &mixed[..]).is_err());
+        assert!(<Kzg>::read(&mut &bytes[..bytes.len() - 1]).is_err());
+        assert!(<Kzg>::read(&mut &[0u8, 0, 0, 0][..]).is_err());
+        assert!(<Kzg>::read(&mut &[255u8, 255, 255, 255][..]).is_err());
+
+        // the proofs of a tree only verify with its setup
+        let datas: Vec<F> = (0..50u32).map(F::from).collect();
+        let tree = VerkleTree::with_kzg(Arc::new(read), &datas, width).unwrap();
+        let root = tree.root_commitment().unwrap();
+        let (value, proof) = tree.prove(17).unwrap();
+        assert!(VerkleTree::verify_proof(root, &proof, width, tree.depth(), 17, value, &kzg));
+        assert!(!VerkleTree::verify_proof(root, &proof, width, tree.depth(), 17, value, &<Kzg>::insecure_test_setup(width)));
+        // a setup for a smaller width is rejected instead of panicking
+        assert!(!VerkleTree::verify_proof(root, &proof, width, tree.depth(), 17, value, &<Kzg>::insecure_test_setup(1)));
+    }
+
+    // whoever knows τ opens any commitment at any points with π = (C - [I(τ)]₁) / Z(τ)
+    fn forge_opening(commitment: &ark_bls12_381::G1Affine, points: &[(F, F)], tau: F) -> ark_bls12_381::G1Affine {
+        use ark_ec::{AffineRepr, CurveGroup};
+        use ark_ff::Field;
+        let interpolation: F = points
+            .iter()
+            .map(|(x, y)| points.iter().filter(|(other, _)| other != x).fold(*y, |term, (other, _)| term * (tau - other) / (*x - other)))
+            .sum();
+        let vanishing: F = points.iter().map(|(x, _)| tau - x).product();
+        ((commitment.into_group() - ark_bls12_381::G1Affine::generator() * interpolation) * vanishing.inverse().unwrap()).into_affine()
+    }
+
+    #[test]
+    fn test_kzg_forged_opening() {
+        use ark_ff::UniformRand;
+        use ark_poly::univariate::DensePolynomial;
+        use ark_poly::{DenseUVPolynomial, Polynomial};
+
+        let mut rng = seeded_rng();
+        let width = 4;
+        // the setup of the forger, as the one of a public seed, and a setup whose τ is unknown
+        let tau = F::rand(&mut rng);
+        let known = <Kzg>::from_tau(tau, width);
+        let kzg = <Kzg>::setup(width, &mut rng);
+
+        let p = DensePolynomial::from_coefficients_vec((0..width).map(|_| F::rand(&mut rng)).collect());
+        let commitment = kzg.commit_polynomial(&p);
+        let valid = [(F::from(1u32), p.evaluate(&F::from(1u32)))];
+        let valid_proof = kzg.generate_proof(&p, &valid).unwrap();
+        let claim = [(F::from(2u32), p.evaluate(&F::from(2u32)) + F::from(1u32))];
+        let forged = forge_opening(&commitment, &claim, tau);
+        assert!(known.verify_proof(&commitment, &claim, &forged));
+        assert!(!kzg.verify_proof(&commitment, &claim, &forged));
+        let batch = [
+            Opening { commitment: &commitment, points: &valid, proof: &valid_proof },
+            Opening { commitment: &commitment, points: &claim, proof: &forged },
+        ];
+        assert!(known.batch_verify(&batch[1..]));
+        assert!(kzg.batch_verify(&batch[..1]));
+        assert!(!kzg.batch_verify(&batch));
+
+        // a leaf value of a tree replaced with forged openings only passes with the forger's setup
+        let datas: Vec<F> = (0..50u32).map(|i| F::from(i * 7 + 3)).collect();
+        let tree = VerkleTree::with_kzg(Arc::new(kzg.clone()), &datas, width).unwrap();
+        let root = tree.root_commitment().unwrap();
+        let depth = tree.depth();
+        let wrong = datas[17] + F::from(1u32);
+        let (_, mut proof) = tree.prove(17).unwrap();
+        proof.proofs[depth].point[0].1 = wrong;
+        for node in &mut proof.proofs {
+            node.proof = forge_opening(&node.commitment, &node.point, tau);
+        }
+        assert!(VerkleTree::verify_proof(root, &proof, width, depth, 17, wrong, &known));
+        assert!(!VerkleTree::verify_proof(root, &proof, width, depth, 17, wrong, &kzg));
+
+        // 17 and 30 are in different leaf nodes, so the node of 17 opens one point
+        let indices = [17, 30];
+        let (_, mut batch_proof) = tree.prove_batch(&indices).unwrap();
+        batch_proof.nodes.get_mut(&NodeIndex { level: depth, offset: 17 / width }).unwrap().point[0].1 = wrong;
+        for node in batch_proof.nodes.values_mut() {
+            node.proof = forge_opening(&node.commitment, &node.point, tau);
+        }
+        assert!(VerkleTree::batch_proof_verify(root, &batch_proof, width, &indices, depth, &[wrong, datas[30]], &known));
+        assert!(!VerkleTree::batch_proof_verify(root, &batch_proof, width, &indices, depth, &[wrong, datas[30]], &kzg));
+    }
+
+    #[test]
+    fn test_bn254_tree() {
+        let mut rng = seeded_rng();
+        let width = 4;
+        let datas: Vec<ark_bn254::Fr> = (0..37).map(|_| ark_bn254::Fr::from(rng.gen::<u64>())).collect();
+        let kzg = Arc::new(Kzg::<ark_bn254::Bn254>::setup(width, &mut rng));
+        let mut tree = VerkleTree::with_kzg(kzg.clone(), &datas, width).unwrap();
+        let root = tree.root_commitment().unwrap();
+
+        let (value, proof) = tree.prove(17).unwrap();
+        assert_eq!(value, datas[17]);
+        assert!(VerkleTree::verify_proof(root, &proof, width, tree.depth(), 17, value, &kzg));
+
+        let indices = [0, 5, 36];
+        let (values, batch_proof) = tree.prove_batch(&indices).unwrap();
+        assert!(VerkleTree::batch_proof_verify(root, &batch_proof, width, &indices, tree.depth(), &values, &kzg));
+        assert!(!VerkleTree::batch_proof_verify(root, &batch_proof, width, &indices, tree.depth(), &datas[..3], &kzg));
+
+        let range_proof = tree.prove_range(6..30).unwrap();
+        assert_eq!(VerkleTree::verify_range_proof(root, &range_proof, width, datas.len(), 6..30, &kzg).as_deref(), Some(&datas[6..30]));
+
+        let mut updated = datas.clone();
+        updated[9] = ark_bn254::Fr::from(7u32);
+        tree.update(9, updated[9]).unwrap();
+        assert_eq!(tree.root_commitment(), VerkleTree::with_kzg(kzg, &updated, width).unwrap().root_commitment());
+        // the setup has to fit the width
+        assert!(VerkleTree::with_kzg(Arc::new(Kzg::<ark_bn254::Bn254>::insecure_test_setup(2)), &datas, 8).is_err());
+    }
+
+    /*  Runs the checks of the contract of `evm::solidity_verifier` on its calldata, with arkworks in place
+        of the precompiles. The challenge of the random linear combination is drawn from `rng` instead of
+        being hashed from the calldata.
+    */
+    fn evm_verify(calldata: &[u8], kzg: &Kzg<ark_bn254::Bn254>, width: usize, leaf_count: usize, rng: &mut StdRng) -> bool {
+        use ark_bn254::{Fq, Fr, G1Affine, G1Projective, G2Affine};
+        use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+        use ark_ff::{PrimeField, UniformRand, Zero};
+
+        let word = |i: usize| -> evm::Word { calldata[4 + 32 * i..4 + 32 * (i + 1)].try_into().unwrap() };
+        let small = |word: evm::Word| -> usize {
+            assert!(word[..24].iter().all(|byte| *byte == 0));
+            u64::from_be_bytes(word[24..].try_into().unwrap()) as usize
+        };
+        let array = |i: usize| -> Vec<evm::Word> {
+            let start = small(word(i)) / 32;
+            (0..small(word(start))).map(|k| word(start + 1 + k)).collect()
+        };
+        // None for a word that is not a canonical field element, the precompiles reject it
+        fn field<F: PrimeField>(word: &evm::Word) -> Option<F> {
+            let value = F::from_be_bytes_mod_order(word);
+            (evm::field_word(&value) == *word).then_some(value)
+        }
+        let point = |x: &evm::Word, y: &evm::Word| -> Option<G1Affine> {
+            if *x == [0; 32] && *y == [0; 32] {
+                return Some(G1Affine::zero());
+            }
+            let point = G1Affine::new_unchecked(field(x)?, field(y)?);
+            point.is_on_curve().then_some(point)
+        };
+
+        let (root, indices, values, proofs) = match calldata[..4].try_into().unwrap() {
+            evm::VERIFY_SELECTOR => ([word(0), word(1)], vec![word(2)], vec![word(3)], array(4)),
+            evm::VERIFY_PROOFS_SELECTOR => ([word(0), word(1)], array(2), array(3), array(4)),
+            _ => return false,
+        };
+        let levels = level_sizes(leaf_count, width).len();
+        if indices.is_empty() || values.len() != indices.len() || proofs.len() != indices.len() * levels * 6 {
+            return false;
+        }
+        let r = Fr::rand(rng);
+        let mut r_power = Fr::from(1u32);
+        let (mut sum, mut openings) = (G1Projective::zero(), G1Projective::zero());
+        for (i, (index, value)) in indices.iter().zip(&values).enumerate() {
+            let start = i * levels * 6;
+            let index = small(*index);
+            if index >= leaf_count || proofs[start] != root[0] || proofs[start + 1] != root[1] {
+                return false;
+            }
+            let mut path = 0;
+            for level in 0..levels {
+                let o = start + level * 6;
+                let position = small(proofs[o + 4]);
+                if position >= width {
+                    return false;
+                }
+                path = path * width + position;
+                let expected = if level + 1 < levels {
+                    let mapped = field::<Fq>(&proofs[o + 6]).unwrap() + field::<Fq>(&proofs[o + 7]).unwrap();
+                    evm::field_word(&Fr::from_be_bytes_mod_order(&evm::field_word(&mapped)))
+                } else {
+                    *value
+                };
+                if proofs[o + 5] != expected {
+                    return false;
+                }
+                let (commitment, opening) = match (point(&proofs[o], &proofs[o + 1]), point(&proofs[o + 2], &proofs[o + 3])) {
+                    (Some(commitment), Some(opening)) => (commitment, opening),
+                    _ => return false,
+                };
+                let y = match field::<Fr>(&proofs[o + 5]) {
+                    Some(y) => y,
+                    None => return false,
+                };
+                sum += (commitment.into_group() - G1Affine::generator() * y + opening * Fr::from(position as u64)) * r_power;
+                openings += opening * r_power;
+                r_power *= r;
+            }
+            if path != index {
+                return false;
+            }
+        }
+        ark_bn254::Bn254::multi_pairing([sum.into_affine(), openings.into_affine()], [-G2Affine::generator(), kzg.g2_powers()[1]]).is_zero()
+    }
+
+    #[test]
+    fn test_evm_export() {
+        let mut rng = seeded_rng();
+        let width = 4;
+        let datas: Vec<ark_bn254::Fr> = (0..37).map(|_| ark_bn254::Fr::from(rng.gen::<u64>())).collect();
+        let kzg = Kzg::<ark_bn254::Bn254>::insecure_test_setup(width);
+        let tree = VerkleTree::with_kzg(Arc::new(kzg.clone()), &datas, width).unwrap();
+        let root = tree.root_commitment().unwrap();
+
+        // the G2 generator in the order of EIP-197
+        let generator_words = evm::g2_words(&ark_ec::AffineRepr::generator());
+        assert_eq!(
+            generator_words.map(|word| word.iter().map(|byte| format!("{:02x}", byte)).collect::<String>()),
+            [
+                "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+                "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+                "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+                "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+            ]
+        );
+
+        let (value, proof) = tree.prove(22).unwrap();
+        let calldata = evm::verify_calldata(&root, 22, &value, &proof);
+        assert_eq!(calldata.len(), 4 + 32 * (6 + proof.proofs.len() * 6));
+        assert!(evm_verify(&calldata, &kzg, width, datas.len(), &mut rng));
+        assert!(!evm_verify(&evm::verify_calldata(&root, 23, &value, &proof), &kzg, width, datas.len(), &mut rng));
+        assert!(!evm_verify(&evm::verify_calldata(&root, 22, &datas[23], &proof), &kzg, width, datas.len(), &mut rng));
+        // the same proof for a tree with more levels
+        assert!(!evm_verify(&calldata, &kzg, width, 100, &mut rng));
+        let mut forged = proof.clone();
+        forged.proofs[1].proof = forged.proofs[0].proof;
+        assert!(!evm_verify(&evm::verify_calldata(&root, 22, &value, &forged), &kzg, width, datas.len(), &mut rng));
+
+        let indices = [3, 22, 36];
+        let (values, proofs): (Vec<_>, Vec<_>) = indices.iter().map(|&index| tree.prove(index).unwrap()).unzip();
+        let calldata = evm::verify_proofs_calldata(&root, &indices, &values, &proofs);
+        assert!(evm_verify(&calldata, &kzg, width, datas.len(), &mut rng));
+        let swapped = [values[1], values[0], values[2]];
+        assert!(!evm_verify(&evm::verify_proofs_calldata(&root, &indices, &swapped, &proofs), &kzg, width, datas.len(), &mut rng));
+        assert!(!evm_verify(&evm::verify_proofs_calldata(&root, &indices, &values, &proofs[..2]), &kzg, width, datas.len(), &mut rng));
+
+        let contract = evm::solidity_verifier(&kzg, width, datas.len());
+        let tau_words = evm::g2_words(&kzg.g2_powers()[1]);
+        let hex = |word: &evm::Word| word.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+        assert!(contract.contains(&format!("uint256 constant TAU_G2_X1 = 0x{};", hex(&tau_words[0]))));
+        assert!(contract.contains(&format!("uint256 constant TAU_G2_Y0 = 0x{};", hex(&tau_words[3]))));
+        assert!(contract.contains("uint256 constant WIDTH = 4;"));
+        assert!(contract.contains("uint256 constant LEVELS = 3;"));
+        assert!(contract.contains("uint256 constant R = 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001;"));
+        assert!(!contract.contains("= {"), "every constant is filled in");
+    }
+
+    type EvmVerifier = revm::Evm<'static, (), revm::InMemoryDB>;
+
+    // deploys the contract in an empty state
+    fn deploy_verifier(bytecode: Vec<u8>) -> (EvmVerifier, revm::primitives::Address) {
+        use revm::primitives::{ExecutionResult, Output, SpecId, TxKind};
+
+        let mut evm = revm::Evm::builder()
+            .with_db(revm::InMemoryDB::default())
+            .with_spec_id(SpecId::CANCUN)
+            .modify_tx_env(|tx| {
+                tx.transact_to = TxKind::Create;
+                tx.data = bytecode.into();
+            })
+            .build();
+        match evm.transact_commit().unwrap() {
+            ExecutionResult::Success { output: Output::Create(_, Some(address)), .. } => (evm, address),
+            result => panic!("the deployment failed: {:?}", result),
+        }
+    }
+
+    // the bool returned by the contract, None if the call reverts
+    fn call_verifier(evm: &mut EvmVerifier, address: revm::primitives::Address, calldata: Vec<u8>) -> Option<bool> {
+        use revm::primitives::{ExecutionResult, Output, TxKind};
+
+        let tx = evm.tx_mut();
+        tx.transact_to = TxKind::Call(address);
+        tx.data = calldata.into();
+        match evm.transact().unwrap().result {
+            ExecutionResult::Success { output: Output::Call(output), .. } => {
+                assert_eq!(output.len(), 32);
+                Some(output[31] == 1)
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_evm_contract() {
+        use ark_ff::{BigInteger, PrimeField};
+
+        // the selectors are the first bytes of the keccak256 of the signatures
+        assert_eq!(revm::primitives::keccak256("verify(uint256[2],uint256,uint256,uint256[])")[..4], evm::VERIFY_SELECTOR);
+        assert_eq!(revm::primitives::keccak256("verifyProofs(uint256[2],uint256[],uint256[],uint256[])")[..4], evm::VERIFY_PROOFS_SELECTOR);
+
+        let mut rng = seeded_rng();
+        let width = 4;
+        let datas: Vec<ark_bn254::Fr> = (0..37).map(|_| ark_bn254::Fr::from(rng.gen::<u64>())).collect();
+        let kzg = Kzg::<ark_bn254::Bn254>::setup(width, &mut rng);
+        let tree = VerkleTree::with_kzg(Arc::new(kzg.clone()), &datas, width).unwrap();
+        let root = tree.root_commitment().unwrap();
+        let (mut evm, address) = deploy_verifier(evm::verifier_bytecode(&kzg, width, datas.len()));
+
+        let (value, proof) = tree.prove(22).unwrap();
+        assert_eq!(call_verifier(&mut evm, address, evm::verify_calldata(&root, 22, &value, &proof)), Some(true));
+        assert_eq!(call_verifier(&mut evm, address, evm::verify_calldata(&root, 23, &value, &proof)), Some(false));
+        assert_eq!(call_verifier(&mut evm, address, evm::verify_calldata(&root, 22, &datas[23], &proof)), Some(false));
+        let mut forged = proof.clone();
+        forged.proofs[1].proof = forged.proofs[0].proof;
+        assert_eq!(call_verifier(&mut evm, address, evm::verify_calldata(&root, 22, &value, &forged)), Some(false));
+        // the leaf value plus R is not a scalar, it is rejected instead of reverting on R - value
+        let mut calldata = evm::verify_calldata(&root, 22, &value, &proof);
+        let leaf_value = calldata.len() - 32;
+        let mut out_of_range = value.into_bigint();
+        out_of_range.add_with_carry(&ark_bn254::Fr::MODULUS);
+        let out_of_range = out_of_range.to_bytes_be();
+        calldata[4 + 3 * 32..4 + 4 * 32].copy_from_slice(&out_of_range);
+        calldata[leaf_value..].copy_from_slice(&out_of_range);
+        assert_eq!(call_verifier(&mut evm, address, calldata.clone()), Some(false));
+        assert!(!evm_verify(&calldata, &kzg, width, datas.len(), &mut rng));
+
+        let indices = [3, 22, 36];
+        let (values, proofs): (Vec<_>, Vec<_>) = indices.iter().map(|&index| tree.prove(index).unwrap()).unzip();
+        assert_eq!(call_verifier(&mut evm, address, evm::verify_proofs_calldata(&root, &indices, &values, &proofs)), Some(true));
+        let swapped = [values[1], values[0], values[2]];
+        assert_eq!(call_verifier(&mut evm, address, evm::verify_proofs_calldata(&root, &indices, &swapped, &proofs)), Some(false));
+        assert_eq!(call_verifier(&mut evm, address, evm::verify_proofs_calldata(&root, &indices, &values, &proofs[..2])), Some(false));
+
+        // a contract for another setup rejects the proofs
+        let other = Kzg::<ark_bn254::Bn254>::insecure_test_setup(width);
+        let (mut other_evm, other_address) = deploy_verifier(evm::verifier_bytecode(&other, width, datas.len()));
+        assert_eq!(call_verifier(&mut other_evm, other_address, evm::verify_calldata(&root, 22, &value, &proof)), Some(false));
+    }
 }
@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use ark_bls12_381::{Fr as F, G1Affine};
+
+use crate::kzg::Kzg;
+use crate::node_index::level_sizes;
+use crate::partial_tree::PartialTree;
+use crate::verkle_tree::{BatchProof, VerkleTree, VerkleTreeError};
+
+/// Records the indices read and written while executing against a tree.
+/// Writes are buffered, the tree itself is not changed.
+pub struct WitnessRecorder<'a> {
+    tree: &'a VerkleTree,
+    accessed: BTreeSet<usize>,
+    writes: BTreeMap<usize, F>,
+}
+
+/// Everything a stateless verifier needs to re-execute a session.
+/// The proof opens the pre-state values of every accessed index, which are also
+/// the values that change on a write, so the post-state root can be computed from it
+/// with a `PartialTree` once it is verified.
+#[derive(Debug, Clone)]
+pub struct Witness {
+    pub width: usize,
+    pub leaf_count: usize,
+    /// the accessed indices in increasing order
+    pub indices: Vec<usize>,
+    /// the pre-state value of every index in `indices`
+    pub pre_values: Vec<F>,
+    pub proof: BatchProof,
+}
+
+impl<'a> WitnessRecorder<'a> {
+    pub fn new(tree: &'a VerkleTree) -> Self {
+        WitnessRecorder {
+            tree,
+            accessed: BTreeSet::new(),
+            writes: BTreeMap::new(),
+        }
+    }
+
+    /// The current value at `index`, including the writes of this session.
+    pub fn read(&mut self, index: usize) -> Option<F> {
+        let value = match self.writes.get(&index) {
+            Some(value) => *value,
+            None => self.tree.get(index)?,
+        };
+        self.accessed.insert(index);
+        Some(value)
+    }
+
+    /// Writes `value` at `index` and returns the value it replaces.
+    pub fn write(&mut self, index: usize, value: F) -> Option<F> {
+        let before = self.read(index)?;
+        self.writes.insert(index, value);
+        Some(before)
+    }
+
+    pub fn accessed(&self) -> &BTreeSet<usize> {
+        &self.accessed
+    }
+
+    pub fn writes(&self) -> &BTreeMap<usize, F> {
+        &self.writes
+    }
+
+    /// Proves the pre-state values of every index accessed so far.
+    pub fn witness(&self) -> Result<Witness, VerkleTreeError> {
+        let indices: Vec<usize> = self.accessed.iter().copied().collect();
+        let (pre_values, proof) = self.tree.prove_batch(&indices)?;
+        Ok(Witness {
+            width: self.tree.width(),
+            leaf_count: self.tree.leaf_count(),
+            pre_values,
+            indices,
+            proof,
+        })
+    }
+}
+
+impl Witness {
+    pub fn depth(&self) -> usize {
+        level_sizes(self.leaf_count, self.width).len() - 1
+    }
+
+    /// Checks the pre-state values against the pre-state root, `kzg` is the setup of the tree.
+    pub fn verify(&self, pre_root: G1Affine, kzg: &Kzg) -> bool {
+        VerkleTree::batch_proof_verify(pre_root, &self.proof, self.width, &self.indices, self.depth(), &self.pre_values, kzg)
+    }
+
+    /// The pre-state value at `index`, if it is part of the witness.
+    pub fn pre_value(&self, index: usize) -> Option<F> {
+        self.indices.binary_search(&index).ok().map(|position| self.pre_values[position])
+    }
+
+    /*  Verifies the witness against `pre_root` and computes the root after applying `writes` to the pre-state.
+        Only the opened nodes change, see `PartialTree::update_batch`.
+        Returns None if the witness is not valid or if a write is at an index that is not in the witness.
+    */
+    pub fn post_state_root(&self, pre_root: G1Affine, writes: &[(usize, F)], kzg: Arc<Kzg>) -> Option<G1Affine> {
+        let mut partial_tree = PartialTree::new(pre_root, &self.proof, self.width, self.leaf_count, &self.indices, &self.pre_values, kzg)?;
+        partial_tree.update_batch(writes).ok()?;
+        partial_tree.root_commitment()
+    }
+}